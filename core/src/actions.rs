@@ -2,16 +2,25 @@
 use log::warn;
 
 use crate::character::{
-    fight, player::PlayerEvent, Action, DamageReaction, Enemy, EnemyHandler, Player,
+    enemies::{Attack, EnemyAttr, EnemyId},
+    fight,
+    player::{spread, Effect, PlayerEvent, HUH_DURATION},
+    Action, Damage, DamageReaction, Dice, EnemyHandler, HitPoint, Level, Player,
 };
-use crate::dungeon::{Direction, Dungeon, DungeonPath};
+use crate::dungeon::{Direction, Dungeon, DungeonPath, TrapKind};
 use crate::error::*;
-use crate::item::{itembox::Entry as ItemEntry, ItemHandler, ItemToken};
-use crate::ui::UiState;
-use crate::{GameInfo, GameMsg, Reaction};
-use anyhow::{bail, Context};
+use crate::item::{
+    itembox::Entry as ItemEntry, ring::RingKind, scroll::ScrollKind, wand::WandKind, ItemAttr,
+    ItemHandler, ItemKind, ItemToken,
+};
+use crate::ui::{ItemAction, MordalKind, UiState};
+use crate::{
+    rng::{Parcent, RngHandle},
+    GameInfo, GameMsg, Reaction, SmallStr,
+};
+use anyhow::Context;
+use enum_iterator::IntoEnumIterator;
 use std::iter;
-use std::rc::Rc;
 
 pub(crate) fn process_action(
     action: Action,
@@ -35,14 +44,47 @@ pub(crate) fn process_action(
             ui = after_turn(player, enemies, dungeon, &mut out)?;
         }
         Action::UpStair => {
-            bail!(ErrorKind::Unimplemented("UpStair Command"));
+            if dungeon.is_upstair(&player.pos) && dungeon.level() == 1 && info.is_cleared {
+                let mordal = UiState::Mordal(MordalKind::Victory);
+                out.push(Reaction::Notify(GameMsg::Victory));
+                out.push(Reaction::UiTransition(mordal.clone()));
+                return Ok((Some(mordal), out));
+            } else if dungeon.is_upstair(&player.pos) && dungeon.level() > 1 {
+                prev_level(dungeon, player, enemies).context("action::process_action")?;
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+            } else {
+                out.push(Reaction::Notify(GameMsg::NoUpStair));
+            }
+            ui = after_turn(player, enemies, dungeon, &mut out)?;
         }
         Action::Move(d) => {
-            out.append(&mut move_player(d, dungeon, player, enemies)?.0);
-            ui = after_turn(player, enemies, dungeon, &mut out)?;
+            let before = player.pos.clone();
+            out.append(&mut move_player(d, info, dungeon, player, enemies)?.0);
+            let trap = (player.pos != before)
+                .then(|| dungeon.step_on_trap(&player.pos))
+                .flatten();
+            if let Some(kind) = trap {
+                ui = spring_trap(kind, info, dungeon, item, player, enemies, &mut out)?;
+            }
+            if ui.is_none() {
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
         }
         Action::MoveUntil(d) => loop {
-            let res = move_player(d, dungeon, player, enemies)?;
+            let before = player.pos.clone();
+            let res = move_player(d, info, dungeon, player, enemies)?;
+            // running stops at a trap
+            let trap = (player.pos != before)
+                .then(|| dungeon.step_on_trap(&player.pos))
+                .flatten();
+            if let Some(kind) = trap {
+                out.extend(res.0);
+                ui = spring_trap(kind, info, dungeon, item, player, enemies, &mut out)?;
+                if ui.is_none() {
+                    ui = after_turn(player, enemies, dungeon, &mut out)?;
+                }
+                break;
+            }
             let tile = dungeon
                 .tile(&player.pos)
                 .map(|t| t.to_char())
@@ -59,6 +101,148 @@ pub(crate) fn process_action(
             out.append(&mut search(dungeon, player)?);
             ui = after_turn(player, enemies, dungeon, &mut out)?;
         }
+        Action::Eat(slot) => {
+            let msg = eat(player, slot, enemies.rng());
+            let ate = matches!(msg, GameMsg::Ate(_));
+            out.push(Reaction::Notify(msg));
+            if ate {
+                out.push(Reaction::StatusUpdated);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Quaff(slot) => {
+            let kind = kind_in_slot(player, slot);
+            let msg = quaff(player, slot, enemies.rng());
+            let quaffed = matches!(msg, GameMsg::Quaffed(_));
+            out.push(Reaction::Notify(msg));
+            if quaffed {
+                identify(item, kind);
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Read(slot) => {
+            let kind = kind_in_slot(player, slot);
+            let msg = read(player, slot, dungeon, enemies)?;
+            let read = matches!(msg, GameMsg::Read(_) | GameMsg::ScrollFailed(_));
+            let identifies = msg == GameMsg::Read(ScrollKind::Identify);
+            if matches!(msg, GameMsg::Read(_)) {
+                identify(item, kind);
+            }
+            out.push(Reaction::Notify(msg));
+            if read {
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+            if identifies && ui.is_none() {
+                let mordal = UiState::Mordal(MordalKind::SelectItem(ItemAction::Identify));
+                out.push(Reaction::UiTransition(mordal.clone()));
+                ui = Some(mordal);
+            }
+        }
+        Action::Identify(slot) => {
+            let msg = match player.itembox.get_slot_mut(slot) {
+                Some(token) => {
+                    item.knowledge_mut().identify(&token.kind);
+                    token.attr.or(ItemAttr::IS_KNOWN);
+                    GameMsg::Identified(token.kind.clone())
+                }
+                None => GameMsg::InvalidItem,
+            };
+            out.push(Reaction::Notify(msg));
+        }
+        Action::PutOn(slot) => {
+            let msg = put_on(player, slot);
+            let put_on = matches!(msg, GameMsg::PutOnRing(_));
+            out.push(Reaction::Notify(msg));
+            if put_on {
+                enemies.sneak(player.wears_ring(RingKind::Stealth));
+                out.push(Reaction::StatusUpdated);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Remove(slot) => {
+            let msg = remove(player, slot);
+            let removed = matches!(msg, GameMsg::RemovedRing(_));
+            out.push(Reaction::Notify(msg));
+            if removed {
+                enemies.sneak(player.wears_ring(RingKind::Stealth));
+                out.push(Reaction::StatusUpdated);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Zap(slot, direction) => match use_wand(player, slot) {
+            Ok(kind) => {
+                if let Some(ui) = zap(kind, direction, dungeon, player, enemies, &mut out)? {
+                    return Ok((Some(ui), out));
+                }
+                if out.contains(&Reaction::Notify(GameMsg::Zapped(kind))) {
+                    identify(item, kind_in_slot(player, slot));
+                }
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+            Err(msg) => {
+                // zapping an empty wand still takes a turn
+                let zapped = msg == GameMsg::NoCharges;
+                out.push(Reaction::Notify(msg));
+                if zapped {
+                    ui = after_turn(player, enemies, dungeon, &mut out)?;
+                }
+            }
+        },
+        Action::Wield(slot) => {
+            let msg = wield(player, slot);
+            let wielded = matches!(msg, GameMsg::Wielded(_));
+            out.push(Reaction::Notify(msg));
+            if wielded {
+                out.push(Reaction::StatusUpdated);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Wear(slot) => {
+            let msg = wear(player, slot);
+            let wore = matches!(msg, GameMsg::Wore(_));
+            out.push(Reaction::Notify(msg));
+            if wore {
+                out.push(Reaction::StatusUpdated);
+                // as in rogue, wearing armor wastes an extra turn
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+                if ui.is_none() {
+                    ui = after_turn(player, enemies, dungeon, &mut out)?;
+                }
+            }
+        }
+        Action::TakeOff(slot) => {
+            let msg = take_off(player, slot);
+            let took_off = matches!(msg, GameMsg::TookOff(_));
+            out.push(Reaction::Notify(msg));
+            if took_off {
+                out.push(Reaction::StatusUpdated);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Drop(slot) => {
+            let msg = drop(player, slot, dungeon, item);
+            let dropped = matches!(msg, GameMsg::Dropped(_));
+            if msg == GameMsg::Dropped(ItemKind::Amulet) {
+                info.is_cleared = false;
+            }
+            out.push(Reaction::Notify(msg));
+            if dropped {
+                enemies.sneak(player.wears_ring(RingKind::Stealth));
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+        }
+        Action::Throw(slot, direction) => match take_projectile(player, slot, item) {
+            Ok(token) => {
+                throw(token, direction, dungeon, player, enemies, &mut out)?;
+                out.extend_from_slice(&[Reaction::Redraw, Reaction::StatusUpdated]);
+                ui = after_turn(player, enemies, dungeon, &mut out)?;
+            }
+            Err(msg) => out.push(Reaction::Notify(msg)),
+        },
         Action::NoOp => return Ok((None, out)),
     }
     Ok((ui, out))
@@ -70,13 +254,44 @@ fn after_turn(
     dungeon: &mut dyn Dungeon,
     res: &mut Vec<Reaction>,
 ) -> GameResult<Option<UiState>> {
-    for event in player.turn_passed(enemies.rng()) {
-        match event {
-            PlayerEvent::Dead => {}
-            PlayerEvent::Healed | PlayerEvent::Hungry => res.push(Reaction::StatusUpdated),
+    // turns pass until the player has enough energy for the next action
+    loop {
+        if player.spend_energy() {
+            return Ok(None);
         }
+        for event in player.turn_passed(enemies.rng()) {
+            match event {
+                PlayerEvent::Dead => {
+                    let mordal = UiState::die("Starved to death".to_owned());
+                    res.push(Reaction::UiTransition(mordal.clone()));
+                    return Ok(Some(mordal));
+                }
+                PlayerEvent::Fainted => res.push(Reaction::Notify(GameMsg::Fainted)),
+                PlayerEvent::Healed => res.push(Reaction::StatusUpdated),
+                PlayerEvent::Hunger(hunger) => {
+                    res.push(Reaction::Notify(GameMsg::HungerChanged(hunger)));
+                    res.push(Reaction::StatusUpdated);
+                }
+                PlayerEvent::Search => res.append(&mut search(dungeon, player)?),
+                PlayerEvent::Teleport => {
+                    player.pos = dungeon
+                        .teleport(&player.pos, enemies)
+                        .context("actions::after_turn")?;
+                    res.push(Reaction::Redraw);
+                }
+                PlayerEvent::EffectEnded(effect) => {
+                    res.push(Reaction::Notify(GameMsg::EffectEnded(effect)));
+                    res.push(Reaction::Redraw);
+                }
+            }
+        }
+        enemies.regenerate();
+        if let Some(ui) = move_active_enemies(enemies, dungeon, player, res)? {
+            return Ok(Some(ui));
+        }
+        // a fainted player loses turns
+        player.charge_energy();
     }
-    move_active_enemies(enemies, dungeon, player, res)
 }
 
 fn move_active_enemies(
@@ -85,30 +300,33 @@ fn move_active_enemies(
     player: &mut Player,
     res: &mut Vec<Reaction>,
 ) -> GameResult<Option<UiState>> {
-    let attacks = enemies.move_actives(&player.pos, None, dungeon);
+    let attacks = enemies.move_actives(&player.pos, dungeon);
     if !attacks.is_empty() {
         player.buttle();
     }
     let mut did_hit = false;
     for at in attacks {
-        match fight::enemy_attack(at.enemy(), player, enemies.rng()) {
+        let (enemy, rng) = match enemies.get_with_rng(at.enemy()) {
+            Some(e) => e,
+            None => continue,
+        };
+        match fight::enemy_attack(enemy, player, rng) {
             Some(hp) => {
-                let name = at.enemy().name();
-                res.push(Reaction::Notify(GameMsg::HitFrom(name.to_owned())));
+                let name = enemy.name().to_owned();
+                res.push(Reaction::Notify(GameMsg::HitFrom(name.clone())));
                 did_hit = true;
-                match player.get_damage(hp) {
-                    DamageReaction::Death => {
-                        let mordal = UiState::die(format!("Killed by {}", name));
-                        res.push(Reaction::UiTransition(mordal.clone()));
-                        return Ok(Some(mordal));
-                    }
-                    DamageReaction::None => {}
+                let reaction = match player.get_damage(hp) {
+                    DamageReaction::None => special_attack(&at, dungeon, enemies, player, res),
+                    death => death,
+                };
+                if let DamageReaction::Death = reaction {
+                    let mordal = UiState::die(format!("Killed by {}", name));
+                    res.push(Reaction::UiTransition(mordal.clone()));
+                    return Ok(Some(mordal));
                 }
             }
             None => {
-                res.push(Reaction::Notify(GameMsg::MissFrom(
-                    at.enemy().name().to_owned(),
-                )));
+                res.push(Reaction::Notify(GameMsg::MissFrom(enemy.name().to_owned())));
             }
         }
     }
@@ -118,6 +336,152 @@ fn move_active_enemies(
     Ok(None)
 }
 
+/// applies the on-hit effect of the enemy, as in rogue
+fn special_attack(
+    at: &Attack,
+    dungeon: &mut dyn Dungeon,
+    enemies: &mut EnemyHandler,
+    player: &mut Player,
+    res: &mut Vec<Reaction>,
+) -> DamageReaction {
+    let (enemy, rng) = match enemies.get_with_rng(at.enemy()) {
+        Some(e) => e,
+        None => return DamageReaction::None,
+    };
+    let mut notify = |msg| res.push(Reaction::Notify(msg));
+    let mut reaction = DamageReaction::None;
+    if enemy.can(EnemyAttr::RUSTS_ARMOR) {
+        match player.rust_armor() {
+            Some(true) => notify(GameMsg::ArmorRusted),
+            Some(false) => notify(GameMsg::RustVanished),
+            None => {}
+        }
+    }
+    if enemy.can(EnemyAttr::FREEZES) {
+        notify(GameMsg::Frozen(enemy.name().to_owned()));
+        reaction = player.freeze(rng.range(2..=3));
+    }
+    if enemy.can(EnemyAttr::REDUCE_STR) && !fight::save_poison(player.level(), rng) {
+        if player.reduce_strength() {
+            notify(GameMsg::StrengthDrained);
+        } else {
+            notify(GameMsg::BiteResisted);
+        }
+    }
+    // as in rogue, a wraith drains a level with a probability of 15%
+    if enemy.can(EnemyAttr::DRAINS_LEVEL) && rng.parcent(Parcent(15)) {
+        notify(GameMsg::LevelDrained);
+        reaction = player.drain_level();
+        if let DamageReaction::None = reaction {
+            reaction = player.drain_max_hp(HitPoint(rng.range(1..=10)));
+        }
+    }
+    // as in rogue, a vampire drains max HP with a probability of 30%
+    if enemy.can(EnemyAttr::DRAINS_HP) && rng.parcent(Parcent(30)) {
+        notify(GameMsg::MaxHpDrained);
+        reaction = player.drain_max_hp(HitPoint(rng.range(1..=3)));
+    }
+    if enemy.can(EnemyAttr::STEAL_GOLD) {
+        // as GOLDCALC in rogue, and more gold is stolen if the player fails to save
+        let level = i64::from(dungeon.level());
+        let times = if fight::save_throw(player.level(), rng) {
+            1
+        } else {
+            5
+        };
+        let amount = (0..times)
+            .map(|_| rng.range(0..50 + 10 * level) as u32 + 2)
+            .sum();
+        let stolen = player.lose_gold(amount);
+        if stolen > 0 {
+            notify(GameMsg::GoldStolen(stolen));
+        }
+        if let Some(to) = dungeon.select_cell(true) {
+            enemies.flee(at.place(), to);
+        }
+    }
+    reaction
+}
+
+/// springs the trap the player stepped on, as `be_trapped` in rogue
+fn spring_trap(
+    kind: TrapKind,
+    info: &GameInfo,
+    dungeon: &mut dyn Dungeon,
+    item: &mut ItemHandler,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<Option<UiState>> {
+    player.run(false);
+    res.push(Reaction::Redraw);
+    let rng = enemies.rng();
+    let (msg, damage, cause) = match kind {
+        TrapKind::TrapDoor => {
+            res.push(Reaction::Notify(GameMsg::FellIntoTrap));
+            new_level(info, dungeon, item, player, enemies, false)
+                .context("actions::spring_trap")?;
+            res.push(Reaction::StatusUpdated);
+            return Ok(None);
+        }
+        TrapKind::BearTrap => {
+            // as BEARTIME in rogue
+            player.get_stuck(spread(3, rng));
+            (GameMsg::CaughtInBearTrap, None, "")
+        }
+        TrapKind::SleepingGas => {
+            // as SLEEPTIME in rogue
+            player.fall_asleep(spread(5, rng));
+            (GameMsg::SleepingGas, None, "")
+        }
+        TrapKind::Arrow if fight::trap_hits(player.level() - Level(1), player, rng) => {
+            let damage = Dice::new(1, HitPoint(6)).random(rng);
+            (GameMsg::ArrowHit, Some(damage), "an arrow")
+        }
+        TrapKind::Arrow => {
+            res.push(Reaction::Notify(GameMsg::ArrowMiss));
+            if let Some(arrow) = item.gen_weapon("arrow") {
+                let pos = player.pos.clone();
+                fall(arrow, &pos, dungeon, res);
+            }
+            return Ok(None);
+        }
+        TrapKind::Teleport => {
+            player.pos = dungeon
+                .teleport(&player.pos, enemies)
+                .context("actions::spring_trap")?;
+            (GameMsg::TeleportTrap, None, "")
+        }
+        TrapKind::PoisonDart if fight::trap_hits(player.level() + Level(1), player, rng) => {
+            let damage = Dice::new(1, HitPoint(4)).random(rng);
+            if !fight::save_poison(player.level(), rng) {
+                player.reduce_strength();
+            }
+            (GameMsg::DartHit, Some(damage), "a poison dart")
+        }
+        TrapKind::PoisonDart => (GameMsg::DartMiss, None, ""),
+        TrapKind::Rust => {
+            res.push(Reaction::Notify(GameMsg::RustTrap));
+            match player.rust_armor() {
+                Some(true) => (GameMsg::ArmorRusted, None, ""),
+                Some(false) => (GameMsg::RustVanished, None, ""),
+                None => return Ok(None),
+            }
+        }
+        TrapKind::Mysterious => (GameMsg::MysteriousTrap, None, ""),
+    };
+    res.push(Reaction::Notify(msg));
+    if let Some(damage) = damage {
+        res.push(Reaction::StatusUpdated);
+        if let DamageReaction::Death = player.get_damage(damage) {
+            let mordal = UiState::die(format!("Killed by {}", cause));
+            res.push(Reaction::UiTransition(mordal.clone()));
+            return Ok(Some(mordal));
+        }
+    }
+    Ok(None)
+}
+
 pub(crate) fn new_level(
     info: &GameInfo,
     dungeon: &mut dyn Dungeon,
@@ -126,58 +490,110 @@ pub(crate) fn new_level(
     enemies: &mut EnemyHandler,
     is_init: bool,
 ) -> GameResult<()> {
-    if !is_init {
+    let arrival = if is_init {
+        dungeon.player_start()
+    } else {
         dungeon
             .new_level(info, item, enemies)
-            .context("action::new_level")?;
-    }
-    player.pos = dungeon.select_cell(true).ok_or(ErrorKind::MaybeBug(
-        "action::new_level No space for player!",
-    ))?;
+            .context("action::new_level")?
+    };
+    player.pos = match arrival {
+        Some(path) => path,
+        None => dungeon.select_cell(true).ok_or(ErrorKind::MaybeBug(
+            "action::new_level No space for player!",
+        ))?,
+    };
+    dungeon.enter_room(&player.pos, enemies)
+}
+
+fn prev_level(
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+) -> GameResult<()> {
+    player.pos = dungeon.prev_level(enemies).context("action::prev_level")?;
     dungeon.enter_room(&player.pos, enemies)
 }
 
 fn player_attack(
     player: &mut Player,
-    enemy: Rc<Enemy>,
+    enemy: EnemyId,
     place: DungeonPath,
+    dungeon: &mut dyn Dungeon,
     enemies: &mut EnemyHandler,
 ) -> GameResult<Vec<Reaction>> {
     let mut res = Vec::new();
     player.buttle();
     enemies.activate(place.clone());
-    if let Some(hp) = fight::player_attack(player, None, &*enemy, enemies.rng()) {
-        res.push(Reaction::Notify(GameMsg::HitTo(enemy.name().to_owned())));
-        match enemy.get_damage(hp) {
-            DamageReaction::Death => {
-                enemies.remove(place);
-                if player.level_up(enemy.exp(), enemies.rng()) {
-                    res.push(Reaction::StatusUpdated);
-                }
-                res.push(Reaction::Notify(GameMsg::Killed(enemy.name().to_owned())));
-                res.push(Reaction::Redraw);
-            }
-            DamageReaction::None => {}
-        }
+    let (enemy, rng) = enemies.get_with_rng(enemy).ok_or(ErrorKind::MaybeBug(
+        "actions::player_attack: No enemy for the id",
+    ))?;
+    let name = enemy.name().to_owned();
+    if let Some(hp) = fight::player_attack(player, None, enemy, rng) {
+        res.push(Reaction::Notify(GameMsg::HitTo(name)));
+        damage_enemy(player, place, hp, dungeon, enemies, &mut res)?;
     } else {
-        res.push(Reaction::Notify(GameMsg::MissTo(enemy.name().to_owned())));
+        res.push(Reaction::Notify(GameMsg::MissTo(name)));
     }
     Ok(res)
 }
 
+/// damages the enemy, and removes it if it's killed
+fn damage_enemy(
+    player: &mut Player,
+    place: DungeonPath,
+    hp: HitPoint,
+    dungeon: &mut dyn Dungeon,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<()> {
+    let id = enemies.get_id(&place).ok_or(ErrorKind::MaybeBug(
+        "actions::damage_enemy: No enemy in the place",
+    ))?;
+    let (enemy, _) = enemies.get_with_rng(id).ok_or(ErrorKind::MaybeBug(
+        "actions::damage_enemy: No enemy for the id",
+    ))?;
+    if let DamageReaction::Death = enemy.get_damage(hp) {
+        let (exp, name) = (enemy.exp(), enemy.name().to_owned());
+        // the item the enemy carried falls on the floor
+        if let Some(item) = enemies.remove(place.clone()) {
+            fall(item, &place, dungeon, res);
+        }
+        if player.level_up(exp, enemies.rng()) {
+            res.push(Reaction::StatusUpdated);
+        }
+        res.push(Reaction::Notify(GameMsg::Killed(name)));
+        res.push(Reaction::Redraw);
+    }
+    Ok(())
+}
+
 fn move_player(
     direction: Direction,
+    info: &mut GameInfo,
     dungeon: &mut dyn Dungeon,
     player: &mut Player,
     enemies: &mut EnemyHandler,
 ) -> GameResult<(Vec<Reaction>, bool)> {
+    if player.is_stuck() {
+        return Ok((vec![Reaction::Notify(GameMsg::StuckInBearTrap)], true));
+    }
+    // as in rogue, a confused player moves randomly with a probability of 4/5
+    let direction = if player.has_effect(Effect::Confused) && !enemies.rng().does_happen(5) {
+        let directions: Vec<_> = Direction::into_enum_iter()
+            .filter(|&d| d != Direction::Stay)
+            .collect();
+        directions[enemies.rng().range(0..directions.len())]
+    } else {
+        direction
+    };
     let new_pos = if let Some(next) = dungeon.can_move_player(&player.pos, direction) {
         next
     } else {
         return Ok((vec![Reaction::Notify(GameMsg::CantMove(direction))], true));
     };
-    if let Some(enemy) = enemies.get_cloned(&new_pos) {
-        return player_attack(player, enemy, new_pos, enemies).map(|r| (r, true));
+    if let Some(enemy) = enemies.get_id(&new_pos) {
+        return player_attack(player, enemy, new_pos, dungeon, enemies).map(|r| (r, true));
     }
     let new_pos = dungeon
         .move_player(&player.pos, direction, enemies)
@@ -186,7 +602,7 @@ fn move_player(
     player.run(true);
     let mut done = false;
     let mut res = vec![Reaction::Redraw];
-    if let Some(msg) = get_item(dungeon, player).context("in actions::move_player")? {
+    if let Some(msg) = get_item(info, dungeon, player).context("in actions::move_player")? {
         res.push(Reaction::Notify(msg));
         res.push(Reaction::StatusUpdated);
         done = true;
@@ -194,6 +610,485 @@ fn move_player(
     Ok((res, done))
 }
 
+fn eat(player: &mut Player, slot: usize, rng: &mut RngHandle) -> GameMsg {
+    let food = match player.itembox.get_slot(slot).map(|token| &token.kind) {
+        Some(ItemKind::Food(food)) => food.clone(),
+        Some(_) => return GameMsg::CantEat,
+        None => return GameMsg::InvalidItem,
+    };
+    player.itembox.take_one(slot);
+    player.eat(&food, rng);
+    GameMsg::Ate(food)
+}
+
+fn quaff(player: &mut Player, slot: usize, rng: &mut RngHandle) -> GameMsg {
+    let kind = match player.itembox.get_slot(slot).map(|token| &token.kind) {
+        Some(ItemKind::Potion(potion)) => potion.kind,
+        Some(_) => return GameMsg::CantQuaff,
+        None => return GameMsg::InvalidItem,
+    };
+    player.itembox.take_one(slot);
+    player.quaff(kind, rng);
+    GameMsg::Quaffed(kind)
+}
+
+fn read(
+    player: &mut Player,
+    slot: usize,
+    dungeon: &mut dyn Dungeon,
+    enemies: &mut EnemyHandler,
+) -> GameResult<GameMsg> {
+    let kind = match player.itembox.get_slot(slot).map(|token| &token.kind) {
+        Some(ItemKind::Scroll(scroll)) => scroll.kind,
+        Some(_) => return Ok(GameMsg::CantRead),
+        None => return Ok(GameMsg::InvalidItem),
+    };
+    player.itembox.take_one(slot);
+    let worked = match kind {
+        // the item to identify is selected after reading
+        ScrollKind::Identify | ScrollKind::ScareMonster => true,
+        ScrollKind::EnchantArmor => {
+            let rng = enemies.rng();
+            player.armor_mut().is_some_and(|armor| armor.enchant(rng))
+        }
+        ScrollKind::EnchantWeapon => {
+            let rng = enemies.rng();
+            player
+                .weapon_mut()
+                .is_some_and(|weapon| weapon.enchant(rng))
+        }
+        ScrollKind::RemoveCurse => {
+            player.remove_curse();
+            true
+        }
+        ScrollKind::Teleportation => {
+            player.pos = dungeon
+                .teleport(&player.pos, enemies)
+                .context("actions::read")?;
+            true
+        }
+        ScrollKind::MagicMapping => {
+            dungeon.magic_map();
+            true
+        }
+        ScrollKind::HoldMonster => {
+            let cd = dungeon.path_to_cd(&player.pos);
+            let held = enemies.hold(|path| {
+                dungeon.is_current_level(path) && dungeon.path_to_cd(path).move_dist(cd) <= 2
+            });
+            held > 0
+        }
+        ScrollKind::Sleep => {
+            // as in rogue, rnd(SLEEPTIME) + 4
+            player.fall_asleep(enemies.rng().range(4..9));
+            true
+        }
+        ScrollKind::CreateMonster => dungeon.create_enemy(&player.pos, enemies).is_some(),
+        ScrollKind::FoodDetection => {
+            player.add_effect(Effect::DetectFood, HUH_DURATION);
+            dungeon
+                .items()
+                .iter()
+                .any(|(_, item)| matches!(item.kind, ItemKind::Food(_)))
+        }
+        ScrollKind::AggravateMonsters => {
+            enemies.aggravate(|path| dungeon.is_current_level(path));
+            true
+        }
+        ScrollKind::ProtectArmor => match player.armor_mut() {
+            Some(armor) => {
+                armor.attr.or(ItemAttr::IS_PROTECTED);
+                true
+            }
+            None => false,
+        },
+    };
+    if worked {
+        Ok(GameMsg::Read(kind))
+    } else {
+        Ok(GameMsg::ScrollFailed(kind))
+    }
+}
+
+fn put_on(player: &mut Player, slot: usize) -> GameMsg {
+    let (id, kind) = match player.itembox.get_slot(slot) {
+        Some(token) if token.is_equipped() => return GameMsg::AlreadyWearing,
+        Some(token) => match &token.kind {
+            ItemKind::Ring(ring) => (token.id(), ring.kind),
+            _ => return GameMsg::CantPutOn,
+        },
+        None => return GameMsg::InvalidItem,
+    };
+    if player.put_on_ring(id) {
+        GameMsg::PutOnRing(kind)
+    } else {
+        GameMsg::NoFreeHand
+    }
+}
+
+fn remove(player: &mut Player, slot: usize) -> GameMsg {
+    let (id, kind, is_cursed) = match player.itembox.get_slot(slot) {
+        Some(token) => match &token.kind {
+            ItemKind::Ring(ring) if token.is_equipped() => {
+                (token.id(), ring.kind, token.is_cursed())
+            }
+            _ => return GameMsg::NotWearing,
+        },
+        None => return GameMsg::InvalidItem,
+    };
+    if is_cursed {
+        return GameMsg::Cursed;
+    }
+    player.remove_ring(id);
+    GameMsg::RemovedRing(kind)
+}
+
+fn kind_in_slot(player: &Player, slot: usize) -> Option<ItemKind> {
+    player
+        .itembox
+        .get_slot(slot)
+        .map(|token| token.kind.clone())
+}
+
+/// identifies the kind of the item by using it
+fn identify(items: &mut ItemHandler, kind: Option<ItemKind>) {
+    if let Some(kind) = kind {
+        items.knowledge_mut().identify(&kind);
+    }
+}
+
+fn wield(player: &mut Player, slot: usize) -> GameMsg {
+    let (id, kind) = match player.itembox.get_slot(slot) {
+        Some(token) => match &token.kind {
+            ItemKind::Weapon(_) if token.is_equipped() => return GameMsg::AlreadyWielding,
+            ItemKind::Weapon(_) => (token.id(), token.kind.clone()),
+            _ => return GameMsg::CantWield,
+        },
+        None => return GameMsg::InvalidItem,
+    };
+    if player.weapon().is_some_and(|weapon| weapon.is_cursed()) {
+        return GameMsg::Cursed;
+    }
+    player.wield(id);
+    GameMsg::Wielded(kind)
+}
+
+fn wear(player: &mut Player, slot: usize) -> GameMsg {
+    let (id, kind) = match player.itembox.get_slot(slot) {
+        Some(token) => match &token.kind {
+            ItemKind::Armor(_) if token.is_equipped() => return GameMsg::AlreadyWearing,
+            ItemKind::Armor(_) => (token.id(), token.kind.clone()),
+            _ => return GameMsg::CantWear,
+        },
+        None => return GameMsg::InvalidItem,
+    };
+    if player.armor().is_some() {
+        return GameMsg::TakeOffFirst;
+    }
+    player.wear(id);
+    GameMsg::Wore(kind)
+}
+
+fn take_off(player: &mut Player, slot: usize) -> GameMsg {
+    let (kind, is_cursed) = match player.itembox.get_slot(slot) {
+        Some(token) => match &token.kind {
+            ItemKind::Armor(_) if token.is_equipped() => (token.kind.clone(), token.is_cursed()),
+            _ => return GameMsg::NotWearing,
+        },
+        None => return GameMsg::InvalidItem,
+    };
+    if is_cursed {
+        return GameMsg::Cursed;
+    }
+    player.take_off();
+    GameMsg::TookOff(kind)
+}
+
+fn drop(
+    player: &mut Player,
+    slot: usize,
+    dungeon: &mut dyn Dungeon,
+    items: &mut ItemHandler,
+) -> GameMsg {
+    let token = match player.itembox.get_slot(slot) {
+        Some(token) => token,
+        None => return GameMsg::InvalidItem,
+    };
+    if token.is_equipped() && token.is_cursed() {
+        return GameMsg::Cursed;
+    }
+    // as in rogue, only one of stacked items is dropped, except weapons
+    let drops_one = token.is_many() && !matches!(token.kind, ItemKind::Weapon(_) | ItemKind::Gold);
+    let (id, kind) = (token.id(), token.kind.clone());
+    let dropped = if drops_one {
+        let mut item = token.get_cloned();
+        item.how_many = 1.into();
+        items.gen_item(item)
+    } else {
+        token.clone()
+    };
+    if dungeon.put_item(&player.pos, dropped).is_err() {
+        return GameMsg::SomethingThere;
+    }
+    if drops_one {
+        player.itembox.take_one(slot);
+    } else {
+        player.unequip(id);
+        player.itembox.remove(slot);
+    }
+    GameMsg::Dropped(kind)
+}
+
+/// takes the item to throw out of the pack, splitting one off if it's stacked
+fn take_projectile(
+    player: &mut Player,
+    slot: usize,
+    items: &mut ItemHandler,
+) -> Result<ItemToken, GameMsg> {
+    let token = player.itembox.get_slot(slot).ok_or(GameMsg::InvalidItem)?;
+    if token.kind == ItemKind::Gold {
+        return Err(GameMsg::CantThrow);
+    }
+    if token.is_equipped() {
+        return Err(GameMsg::InUse);
+    }
+    if token.how_many.0 <= 1 {
+        return player.itembox.remove(slot).ok_or(GameMsg::InvalidItem);
+    }
+    let item = player.itembox.take_one(slot).ok_or(GameMsg::InvalidItem)?;
+    Ok(items.gen_item(item))
+}
+
+/// flies the projectile until it hits an enemy or a wall
+fn throw(
+    token: ItemToken,
+    direction: Direction,
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<()> {
+    let item_name = match token.name() {
+        Some(name) => SmallStr::from_str(name),
+        None => SmallStr::from_string(token.kind.to_string()),
+    };
+    let mut landing = player.pos.clone();
+    for path in dungeon.ray(&player.pos, direction, usize::MAX, false) {
+        landing = path.clone();
+        let id = match enemies.get_id(&path) {
+            Some(id) => id,
+            None => continue,
+        };
+        player.buttle();
+        enemies.activate(path.clone());
+        let (enemy, rng) = enemies
+            .get_with_rng(id)
+            .ok_or(ErrorKind::MaybeBug("actions::throw: No enemy for the id"))?;
+        let name = enemy.name().to_owned();
+        if let Some(hp) = fight::player_attack(player, Some(&token), enemy, rng) {
+            // as in rogue, the projectile is gone when it hits
+            res.push(Reaction::Notify(GameMsg::ThrowHit(item_name, name)));
+            return damage_enemy(player, path, hp, dungeon, enemies, res);
+        }
+        res.push(Reaction::Notify(GameMsg::ThrowMiss(item_name, name)));
+        break;
+    }
+    fall(token, &landing, dungeon, res);
+    Ok(())
+}
+
+/// drops the missed projectile on the floor, or next to it if the cell is occupied
+fn fall(token: ItemToken, path: &DungeonPath, dungeon: &mut dyn Dungeon, res: &mut Vec<Reaction>) {
+    let kind = token.kind.clone();
+    let mut token = match dungeon.put_item(path, token) {
+        Ok(()) => return,
+        Err(token) => token,
+    };
+    let neighbors: Vec<_> = Direction::into_enum_iter()
+        .filter_map(|d| dungeon.ray(path, d, 1, false).pop())
+        .collect();
+    for next in neighbors {
+        token = match dungeon.put_item(&next, token) {
+            Ok(()) => return,
+            Err(token) => token,
+        };
+    }
+    res.push(Reaction::Notify(GameMsg::Vanished(kind)));
+}
+
+fn use_wand(player: &mut Player, slot: usize) -> Result<WandKind, GameMsg> {
+    let wand = match player
+        .itembox
+        .get_slot_mut(slot)
+        .map(|token| &mut token.kind)
+    {
+        Some(ItemKind::Wand(wand)) => wand,
+        Some(_) => return Err(GameMsg::CantZap),
+        None => return Err(GameMsg::InvalidItem),
+    };
+    if wand.use_charge() {
+        Ok(wand.kind)
+    } else {
+        Err(GameMsg::NoCharges)
+    }
+}
+
+/// as in rogue, a bolt goes 6 steps
+const BOLT_LENGTH: usize = 6;
+
+fn zap(
+    kind: WandKind,
+    direction: Direction,
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<Option<UiState>> {
+    let mut msg = GameMsg::Zapped(kind);
+    match kind {
+        WandKind::Light => {
+            if !dungeon.light(&player.pos).context("actions::zap")? {
+                msg = GameMsg::ZapFailed(kind);
+            }
+        }
+        WandKind::Lightning | WandKind::Fire | WandKind::Cold => {
+            res.push(Reaction::Notify(msg));
+            return fire_bolt(kind, direction, dungeon, player, enemies, res);
+        }
+        WandKind::DrainLife => msg = drain_life(dungeon, player, enemies, res)?,
+        WandKind::Nothing => {}
+        _ => {
+            let ray = dungeon.ray(&player.pos, direction, usize::MAX, false);
+            if let Some((place, id)) = enemies.target(&ray) {
+                zap_enemy(kind, place, id, dungeon, player, enemies, res)?;
+            }
+        }
+    }
+    res.push(Reaction::Notify(msg));
+    Ok(None)
+}
+
+/// applies the effect of the wand to the targeted enemy
+fn zap_enemy(
+    kind: WandKind,
+    place: DungeonPath,
+    id: EnemyId,
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<()> {
+    match kind {
+        WandKind::Striking => {
+            enemies.activate(place.clone());
+            let (enemy, rng) = enemies.get_with_rng(id).ok_or(ErrorKind::MaybeBug(
+                "actions::zap_enemy: No enemy for the id",
+            ))?;
+            let name = enemy.name().to_owned();
+            if let Some(hp) = fight::wand_attack(player, enemy, rng) {
+                res.push(Reaction::Notify(GameMsg::BoltHitTo(kind, name)));
+                damage_enemy(player, place, hp, dungeon, enemies, res)?;
+            } else {
+                res.push(Reaction::Notify(GameMsg::BoltMissTo(kind, name)));
+            }
+        }
+        WandKind::Sleep => {
+            enemies.hold(|path| *path == place);
+        }
+        WandKind::SlowMonster => enemies.change_speed(&place, false),
+        WandKind::HasteMonster => enemies.change_speed(&place, true),
+        WandKind::Polymorph => {
+            enemies.polymorph(&place);
+            res.push(Reaction::Redraw);
+        }
+        WandKind::TeleportAway => {
+            if let Some(to) = dungeon.select_cell(true) {
+                enemies.teleport(&place, to);
+                res.push(Reaction::Redraw);
+            }
+        }
+        WandKind::Cancellation => enemies.cancel(&place),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// shoots a bolt, which hits the first enemy failing the saving throw
+fn fire_bolt(
+    kind: WandKind,
+    direction: Direction,
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<Option<UiState>> {
+    let damage = Dice::new(6, HitPoint(6));
+    for path in dungeon.ray(&player.pos, direction, BOLT_LENGTH, true) {
+        // the bolt bounced and came back to the player
+        if path == player.pos {
+            if fight::save_throw(player.level(), enemies.rng()) {
+                res.push(Reaction::Notify(GameMsg::BoltMissFrom(kind)));
+                continue;
+            }
+            res.push(Reaction::Notify(GameMsg::BoltHitFrom(kind)));
+            res.push(Reaction::StatusUpdated);
+            if let DamageReaction::Death = player.get_damage(damage.random(enemies.rng())) {
+                let mordal = UiState::die(format!("Killed by a bolt of {}", kind));
+                res.push(Reaction::UiTransition(mordal.clone()));
+                return Ok(Some(mordal));
+            }
+            break;
+        }
+        let id = match enemies.get_id(&path) {
+            Some(id) => id,
+            None => continue,
+        };
+        enemies.activate(path.clone());
+        let (enemy, rng) = enemies.get_with_rng(id).ok_or(ErrorKind::MaybeBug(
+            "actions::fire_bolt: No enemy for the id",
+        ))?;
+        let name = enemy.name().to_owned();
+        if fight::save_throw(enemy.level(), rng) {
+            res.push(Reaction::Notify(GameMsg::BoltMissTo(kind, name)));
+        } else {
+            let hp = damage.random(rng);
+            res.push(Reaction::Notify(GameMsg::BoltHitTo(kind, name)));
+            damage_enemy(player, path, hp, dungeon, enemies, res)?;
+            break;
+        }
+    }
+    Ok(None)
+}
+
+/// as in rogue, the player loses half of hp and enemies around share the damage
+fn drain_life(
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+    enemies: &mut EnemyHandler,
+    res: &mut Vec<Reaction>,
+) -> GameResult<GameMsg> {
+    let current = player.hp().current;
+    if current < HitPoint(2) {
+        return Ok(GameMsg::TooWeak);
+    }
+    let targets: Vec<_> = enemies
+        .placed()
+        .filter(|(path, _)| dungeon.draw_enemy(&player.pos, path))
+        .map(|(path, _)| path.clone())
+        .collect();
+    if targets.is_empty() {
+        return Ok(GameMsg::ZapFailed(WandKind::DrainLife));
+    }
+    let hp = HitPoint(current.0 / 2);
+    player.get_damage(hp);
+    let damage = HitPoint(hp.0 / targets.len() as i64);
+    for place in targets {
+        enemies.activate(place.clone());
+        damage_enemy(player, place, damage, dungeon, enemies, res)?;
+    }
+    Ok(GameMsg::Zapped(WandKind::DrainLife))
+}
+
 fn search(dungeon: &mut dyn Dungeon, player: &mut Player) -> GameResult<Vec<Reaction>> {
     dungeon.search(&player.pos).map(|v| {
         v.into_iter()
@@ -203,7 +1098,11 @@ fn search(dungeon: &mut dyn Dungeon, player: &mut Player) -> GameResult<Vec<Reac
     })
 }
 
-fn get_item(dungeon: &mut dyn Dungeon, player: &mut Player) -> GameResult<Option<GameMsg>> {
+fn get_item(
+    info: &mut GameInfo,
+    dungeon: &mut dyn Dungeon,
+    player: &mut Player,
+) -> GameResult<Option<GameMsg>> {
     macro_rules! try_or_ok {
         ($res: expr) => {
             match $res {
@@ -224,8 +1123,304 @@ fn get_item(dungeon: &mut dyn Dungeon, player: &mut Player) -> GameResult<Option
         warn!("[actions::get_item] couldn't remove object!!!")
     }
     //dungeon.remove_from_place(&player.pos);
+    if got_item.kind == ItemKind::Amulet {
+        info.is_cleared = true;
+    }
     Ok(Some(GameMsg::GotItem {
         kind: got_item.kind.clone(),
         num: got_item.how_many.0,
     }))
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::character::{
+        enemies::{Config as EnemyConfig, Preset},
+        player::Effect,
+        EnemyHandler, Player,
+    };
+    use crate::dungeon::{Direction, Dungeon, DungeonPath, Positioned};
+    use crate::input::Key;
+    use crate::item::{scroll::ScrollKind, ItemAttr, ItemKind, ItemNum};
+    use crate::ui::{ItemAction, MordalKind, UiState};
+    use crate::{tile::Tile, GameConfig, GameMsg, Reaction, RunTime};
+    use enum_iterator::IntoEnumIterator;
+    use std::iter;
+    /// a game every test starts from
+    pub(crate) fn runtime() -> RunTime {
+        let config = GameConfig {
+            seed: Some(1),
+            ..Default::default()
+        };
+        config.build().unwrap()
+    }
+    /// adds a floor item which satisfies the query to the pack, and returns its slot
+    pub(crate) fn give_item(runtime: &mut RunTime, query: impl Fn(&ItemKind) -> bool) -> usize {
+        let item = (0..1000)
+            .map(|_| runtime.item.gen_floor_item())
+            .find(|item| query(&item.kind))
+            .unwrap();
+        let id = item.id();
+        assert!(runtime.player.itembox.add(item));
+        runtime
+            .player
+            .itembox
+            .slots()
+            .find(|(_, token)| token.id() == id)
+            .map(|(slot, _)| slot)
+            .unwrap()
+    }
+    /// creates an enemy next to the player
+    pub(crate) fn enemy_nearby(
+        dungeon: &mut dyn Dungeon,
+        player: &Player,
+        enemies: &mut EnemyHandler,
+    ) -> (DungeonPath, Direction) {
+        let place = dungeon.create_enemy(&player.pos, enemies).unwrap();
+        let diff = dungeon.path_to_cd(&place) - dungeon.path_to_cd(&player.pos);
+        let direction = Direction::into_enum_iter()
+            .find(|d| d.to_cd() == diff)
+            .unwrap();
+        (place, direction)
+    }
+    /// the key to select an item in `slot`
+    pub(crate) fn slot_key(slot: usize) -> Key {
+        Key::Char((b'a' + slot as u8) as char)
+    }
+    pub(crate) fn direction_key(direction: Direction) -> Key {
+        Key::Char(match direction {
+            Direction::Left => 'h',
+            Direction::Down => 'j',
+            Direction::Up => 'k',
+            Direction::Right => 'l',
+            Direction::LeftUp => 'y',
+            Direction::RightUp => 'u',
+            Direction::LeftDown => 'b',
+            Direction::RightDown => 'n',
+            Direction::Stay => unreachable!(),
+        })
+    }
+    #[test]
+    fn equipment() {
+        let mut runtime = runtime();
+        let find = |runtime: &crate::RunTime, f: fn(&ItemKind) -> bool| {
+            runtime
+                .itembox()
+                .items()
+                .enumerate()
+                .find(|(_, item)| f(&item.kind))
+                .map(|(i, _)| i)
+                .unwrap()
+        };
+        let armor = find(&runtime, |kind| matches!(kind, ItemKind::Armor(_)));
+        // food isn't armor
+        runtime.react_to_key(Key::Char('W')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantWear)));
+        runtime.react_to_key(Key::Char('W')).unwrap();
+        let res = runtime.react_to_key(slot_key(armor)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::AlreadyWearing)));
+        runtime.react_to_key(Key::Char('T')).unwrap();
+        let res = runtime.react_to_key(slot_key(armor)).unwrap();
+        assert!(res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::TookOff(_)))));
+        assert!(runtime.player.armor().is_none());
+        runtime.react_to_key(Key::Char('W')).unwrap();
+        let res = runtime.react_to_key(slot_key(armor)).unwrap();
+        assert!(res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::Wore(_)))));
+        assert!(runtime.player.armor().is_some());
+        // the initial weapon is already wielded
+        let weapon = find(&runtime, |kind| matches!(kind, ItemKind::Weapon(_)));
+        runtime.react_to_key(Key::Char('w')).unwrap();
+        let res = runtime.react_to_key(slot_key(weapon)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::AlreadyWielding)));
+        runtime.react_to_key(Key::Char('d')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::Dropped(ItemKind::Food(_))))));
+        assert!(runtime.dungeon.get_item(&runtime.player.pos).is_some());
+        runtime.react_to_key(Key::Char('d')).unwrap();
+        let res = runtime.react_to_key(slot_key(weapon)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::SomethingThere)));
+    }
+    #[test]
+    fn throw() {
+        let mut runtime = runtime();
+        let (slot, arrows) = runtime
+            .player
+            .itembox
+            .slots()
+            .find(|(_, token)| token.launcher().is_some())
+            .map(|(slot, token)| (slot, token.how_many))
+            .unwrap();
+        let weapon = runtime
+            .player
+            .itembox
+            .slots()
+            .find(|(_, token)| token.is_equipped() && token.at_weild().is_some())
+            .map(|(slot, _)| slot)
+            .unwrap();
+        // the wielded weapon can't be thrown
+        runtime.react_to_key(Key::Char('t')).unwrap();
+        runtime.react_to_key(slot_key(weapon)).unwrap();
+        let res = runtime.react_to_key(Key::Char('h')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::InUse)));
+        let RunTime {
+            dungeon,
+            enemies,
+            player,
+            ..
+        } = &mut runtime;
+        let (place, direction) = enemy_nearby(&mut **dungeon, player, enemies);
+        runtime.react_to_key(Key::Char('t')).unwrap();
+        let res = runtime.react_to_key(slot_key(slot)).unwrap();
+        let select = UiState::Mordal(MordalKind::SelectDirection(ItemAction::Throw, slot));
+        assert_eq!(res, vec![Reaction::UiTransition(select)]);
+        let res = runtime.react_to_key(direction_key(direction)).unwrap();
+        // only one arrow is split off from the stack
+        let left = runtime.player.itembox.get_slot(slot).unwrap().how_many;
+        assert_eq!(left.0 + 1, arrows.0);
+        let missed = res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::ThrowMiss(..))));
+        let hit = res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::ThrowHit(..))));
+        assert!(missed != hit);
+        if missed {
+            // the missed arrow falls around the enemy
+            let dropped = runtime.dungeon.items().into_iter().any(|(path, token)| {
+                let (cd, target) = (
+                    runtime.dungeon.path_to_cd(&path),
+                    runtime.dungeon.path_to_cd(&place),
+                );
+                token.launcher().is_some() && cd.is_adjacent(target)
+            });
+            assert!(dropped);
+        }
+    }
+    #[test]
+    fn curse() {
+        let mut runtime = runtime();
+        let mace = runtime
+            .itembox()
+            .slots()
+            .find(|(_, token)| token.is_equipped() && token.at_weild().is_some())
+            .map(|(slot, _)| slot)
+            .unwrap();
+        let sword = give_item(&mut runtime, |kind| match kind {
+            ItemKind::Weapon(w) => w.name() == "long-sword",
+            _ => false,
+        });
+        let token = runtime.player.itembox.get_slot_mut(sword).unwrap();
+        token.attr.or(ItemAttr::IS_CURSED);
+        let name = |runtime: &RunTime, slot| {
+            let item = runtime.itembox().get_slot(slot).unwrap().get();
+            runtime.knowledge().item_name(item).to_string()
+        };
+        // the enchantment isn't shown until identified
+        assert_eq!(name(&runtime, sword), "A long-sword");
+        runtime.react_to_key(Key::Char('w')).unwrap();
+        let res = runtime.react_to_key(slot_key(sword)).unwrap();
+        assert!(res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::Wielded(_)))));
+        runtime.react_to_key(Key::Char('w')).unwrap();
+        let res = runtime.react_to_key(slot_key(mace)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Cursed)));
+        runtime.react_to_key(Key::Char('d')).unwrap();
+        let res = runtime.react_to_key(slot_key(sword)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Cursed)));
+        let scroll = give_item(&mut runtime, |kind| match kind {
+            ItemKind::Scroll(s) => s.kind == ScrollKind::EnchantWeapon,
+            _ => false,
+        });
+        runtime.react_to_key(Key::Char('r')).unwrap();
+        runtime.react_to_key(slot_key(scroll)).unwrap();
+        // enchanting removes the curse
+        runtime.react_to_key(Key::Char('w')).unwrap();
+        let res = runtime.react_to_key(slot_key(mace)).unwrap();
+        assert!(res
+            .iter()
+            .any(|r| matches!(r, Reaction::Notify(GameMsg::Wielded(_)))));
+        let token = runtime.player.itembox.get_slot_mut(sword).unwrap();
+        token.attr.or(ItemAttr::IS_KNOWN);
+        assert!(name(&runtime, sword).contains(','));
+    }
+    #[test]
+    fn enemy_behaviors() {
+        let config_with = |enemy: usize| GameConfig {
+            seed: Some(1),
+            enemies: EnemyConfig {
+                enemies: vec![Preset::Builtin(enemy)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // a phantom is invisible without the effect of see invisible
+        let mut runtime = config_with(15).build().unwrap();
+        let RunTime {
+            dungeon,
+            enemies,
+            player,
+            ..
+        } = &mut runtime;
+        let place = iter::repeat_with(|| dungeon.create_enemy(&player.pos, enemies))
+            .find_map(|place| place)
+            .unwrap();
+        let cd = dungeon.path_to_cd(&place);
+        let drawn = |runtime: &RunTime| {
+            let mut tile = None;
+            runtime
+                .draw_screen(|Positioned(c, t)| {
+                    if c == cd {
+                        tile = Some(t);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            tile
+        };
+        assert_ne!(drawn(&runtime), Some(Tile(b'P')));
+        runtime.player.add_effect(Effect::SeeInvisible, 10);
+        assert_eq!(drawn(&runtime), Some(Tile(b'P')));
+        // an orc grabs gold in its room, and drops it when killed
+        let mut runtime = config_with(14).build().unwrap();
+        let RunTime {
+            dungeon,
+            enemies,
+            player,
+            item,
+            ..
+        } = &mut runtime;
+        let place = iter::repeat_with(|| dungeon.create_enemy(&player.pos, enemies))
+            .find_map(|place| place)
+            .unwrap();
+        let next = Direction::into_enum_iter()
+            .filter_map(|d| dungeon.ray(&place, d, 1, false).pop())
+            .find(|p| {
+                *p != player.pos && enemies.get_id(p).is_none() && dungeon.get_item(p).is_none()
+            })
+            .unwrap();
+        let gold = item.gen_item(ItemKind::Gold.numbered(ItemNum(10)).unwrap().many());
+        dungeon.put_item(&next, gold).unwrap();
+        let gold = dungeon.gold_in_room(&place).unwrap();
+        let id = enemies.get_id(&place).unwrap();
+        enemies.activate(place);
+        for _ in 0..50 {
+            enemies.move_actives(&player.pos, &mut **dungeon);
+        }
+        assert!(dungeon.get_item(&gold).is_none());
+        let place = enemies
+            .placed()
+            .find(|(_, enemy)| enemy.id() == id)
+            .map(|(path, _)| path.clone())
+            .unwrap();
+        let carried = enemies.remove(place).unwrap();
+        assert_eq!(carried.kind, ItemKind::Gold);
+    }
+}
@@ -1,16 +1,14 @@
-use super::{DamageReaction, Defense, Dice, Exp, HitPoint, Level, Strength};
+use super::{DamageReaction, Defense, Dice, Energy, Exp, HitPoint, Level, Speed, Strength};
 use crate::dungeon::{Dungeon, DungeonPath, MoveResult};
 use crate::{
-    item::ItemNum,
+    item::{ItemNum, ItemToken},
     rng::{Parcent, RngHandle},
     tile::Tile,
     Drawable, SmallStr,
 };
 use smallvec::SmallVec;
-use std::cell::Cell;
 use std::collections::BTreeMap;
 use std::ops::Range;
-use std::rc::{Rc, Weak};
 
 pub type DiceVec<T> = SmallVec<[Dice<T>; 4]>;
 
@@ -46,7 +44,7 @@ impl Config {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct ConfigInner {
     appear_rate_gold: Parcent,
     appear_rate_nogold: Parcent,
@@ -120,28 +118,46 @@ pub struct Status {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, BitOr)]
-pub struct EnemyAttr(u16);
+pub struct EnemyAttr(u32);
 
 #[rustfmt::skip]
 impl EnemyAttr {
-    pub const MEAN: EnemyAttr        = EnemyAttr(0b000_000_000_001);
-    pub const FLYING: EnemyAttr      = EnemyAttr(0b000_000_000_010);
-    pub const REGENERATE: EnemyAttr  = EnemyAttr(0b000_000_000_100);
-    pub const GREEDY: EnemyAttr      = EnemyAttr(0b000_000_001_000);
-    pub const INVISIBLE: EnemyAttr   = EnemyAttr(0b000_000_010_000);
-    pub const RUSTS_ARMOR: EnemyAttr = EnemyAttr(0b000_000_100_000);
-    pub const STEAL_GOLD: EnemyAttr  = EnemyAttr(0b000_001_000_000);
-    pub const REDUCE_STR: EnemyAttr  = EnemyAttr(0b000_010_000_000);
-    pub const FREEZES: EnemyAttr     = EnemyAttr(0b000_100_000_000);
-    pub const RANDOM: EnemyAttr      = EnemyAttr(0b001_000_000_000);
-    pub const CONFUSED: EnemyAttr    = EnemyAttr(0b010_000_000_000);
-    pub const NONE: EnemyAttr        = EnemyAttr(0b000_000_000_000);
+    pub const MEAN: EnemyAttr         = EnemyAttr(1 << 0);
+    pub const FLYING: EnemyAttr       = EnemyAttr(1 << 1);
+    pub const REGENERATE: EnemyAttr   = EnemyAttr(1 << 2);
+    pub const GREEDY: EnemyAttr       = EnemyAttr(1 << 3);
+    pub const INVISIBLE: EnemyAttr    = EnemyAttr(1 << 4);
+    pub const RUSTS_ARMOR: EnemyAttr  = EnemyAttr(1 << 5);
+    pub const STEAL_GOLD: EnemyAttr   = EnemyAttr(1 << 6);
+    pub const REDUCE_STR: EnemyAttr   = EnemyAttr(1 << 7);
+    pub const FREEZES: EnemyAttr      = EnemyAttr(1 << 8);
+    pub const RANDOM: EnemyAttr       = EnemyAttr(1 << 9);
+    pub const CONFUSED: EnemyAttr     = EnemyAttr(1 << 10);
+    /// drains a level of the player, as wraiths in rogue
+    pub const DRAINS_LEVEL: EnemyAttr = EnemyAttr(1 << 11);
+    /// held by the scroll of hold monster
+    pub const HELD: EnemyAttr         = EnemyAttr(1 << 12);
+    /// slowed by the wand of slow monster
+    pub const SLOWED: EnemyAttr       = EnemyAttr(1 << 13);
+    /// hasted by the wand of haste monster
+    pub const HASTED: EnemyAttr       = EnemyAttr(1 << 14);
+    /// lost its special abilities by the wand of cancellation
+    pub const CANCELED: EnemyAttr     = EnemyAttr(1 << 15);
+    /// drains the max HP of the player, as vampires in rogue
+    pub const DRAINS_HP: EnemyAttr    = EnemyAttr(1 << 16);
+    pub const NONE: EnemyAttr         = EnemyAttr(0);
 }
 
 impl EnemyAttr {
     pub fn contains(self, r: Self) -> bool {
         (self.0 & r.0) != 0
     }
+    fn insert(&mut self, r: Self) {
+        self.0 |= r.0;
+    }
+    fn remove(&mut self, r: Self) {
+        self.0 &= !r.0;
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -158,34 +174,56 @@ impl EnemyId {
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Enemy {
     attack: DiceVec<HitPoint>,
-    attr: Cell<EnemyAttr>,
+    attr: EnemyAttr,
     defense: Defense,
     exp: Exp,
-    hp: Cell<HitPoint>,
+    hp: HitPoint,
     id: EnemyId,
     level: Level,
     max_hp: HitPoint,
     name: SmallStr,
-    running: Cell<bool>,
+    running: bool,
+    /// energy for moving, which is gained according to the speed
+    #[serde(default)]
+    energy: Energy,
     tile: Tile,
 }
 
 impl Enemy {
     pub(crate) const STRENGTH: Strength = Strength(10);
     pub fn is_mean(&self) -> bool {
-        self.attr.get().contains(EnemyAttr::MEAN)
+        self.attr.contains(EnemyAttr::MEAN)
     }
     pub fn is_greedy(&self) -> bool {
-        self.attr.get().contains(EnemyAttr::GREEDY)
+        self.attr.contains(EnemyAttr::GREEDY)
+    }
+    pub fn is_flying(&self) -> bool {
+        self.attr.contains(EnemyAttr::FLYING)
+    }
+    pub fn is_invisible(&self) -> bool {
+        self.attr.contains(EnemyAttr::INVISIBLE)
     }
     pub fn is_random(&self) -> bool {
-        self.attr.get().contains(EnemyAttr::RANDOM)
+        self.attr.contains(EnemyAttr::RANDOM)
     }
     pub fn is_confused(&self) -> bool {
-        self.attr.get().contains(EnemyAttr::CONFUSED)
+        self.attr.contains(EnemyAttr::CONFUSED)
+    }
+    pub fn is_held(&self) -> bool {
+        self.attr.contains(EnemyAttr::HELD)
+    }
+    pub fn is_canceled(&self) -> bool {
+        self.attr.contains(EnemyAttr::CANCELED)
     }
     pub fn is_running(&self) -> bool {
-        self.running.get()
+        self.running
+    }
+    /// checks if the enemy has the special attack, which the wand of cancellation disables
+    pub fn can(&self, attack: EnemyAttr) -> bool {
+        !self.is_canceled() && self.attr.contains(attack)
+    }
+    pub fn id(&self) -> EnemyId {
+        self.id
     }
     pub fn level(&self) -> Level {
         self.level
@@ -202,17 +240,65 @@ impl Enemy {
     pub fn name(&self) -> &SmallStr {
         &self.name
     }
-    pub fn get_damage(&self, damage: HitPoint) -> DamageReaction {
-        let cur = self.hp.get();
+    pub fn is_regenerating(&self) -> bool {
+        self.attr.contains(EnemyAttr::REGENERATE)
+    }
+    pub fn hp(&self) -> HitPoint {
+        self.hp
+    }
+    pub fn speed(&self) -> Speed {
+        if self.attr.contains(EnemyAttr::HASTED) {
+            Speed::Fast
+        } else if self.attr.contains(EnemyAttr::SLOWED) {
+            Speed::Slow
+        } else {
+            Speed::Normal
+        }
+    }
+    pub fn get_damage(&mut self, damage: HitPoint) -> DamageReaction {
+        let cur = self.hp;
         if cur <= damage {
             DamageReaction::Death
         } else {
-            self.hp.replace(damage - cur);
+            self.hp = cur - damage;
             DamageReaction::None
         }
     }
-    fn run(&self) {
-        self.running.replace(true);
+    /// heals 1 HP per turn, as trolls in rogue
+    fn regenerate(&mut self) {
+        if self.hp < self.max_hp {
+            self.hp += HitPoint(1);
+        }
+    }
+    fn run(&mut self) {
+        self.attr.remove(EnemyAttr::HELD);
+        self.running = true;
+    }
+    fn hold(&mut self) {
+        self.attr.insert(EnemyAttr::HELD);
+        self.running = false;
+    }
+    /// as in rogue, slowing a hasted enemy makes it normal, and vice versa
+    fn change_speed(&mut self, faster: bool) {
+        let (from, to) = if faster {
+            (EnemyAttr::SLOWED, EnemyAttr::HASTED)
+        } else {
+            (EnemyAttr::HASTED, EnemyAttr::SLOWED)
+        };
+        if self.attr.contains(from) {
+            self.attr.remove(from);
+        } else {
+            self.attr.insert(to);
+        }
+    }
+    /// how many times the enemy moves in this turn
+    fn moves_in_turn(&mut self) -> usize {
+        let speed = self.speed();
+        self.energy.actions_in_turn(speed)
+    }
+    fn cancel(&mut self) {
+        self.attr.insert(EnemyAttr::CANCELED);
+        self.attr.remove(EnemyAttr::INVISIBLE);
     }
 }
 
@@ -228,22 +314,36 @@ impl Drawable for Enemy {
     }
 }
 
-pub(crate) struct Attack(Rc<Enemy>);
+/// An attack by the enemy, which is detected when moving enemies
+pub(crate) struct Attack(EnemyId, DungeonPath);
 
 impl Attack {
-    pub fn enemy(&self) -> &Enemy {
-        self.0.as_ref()
+    pub fn enemy(&self) -> EnemyId {
+        self.0
+    }
+    pub fn place(&self) -> &DungeonPath {
+        &self.1
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EnemyHandler {
     enemy_stats: Vec<Status>,
-    enemies: Vec<Weak<Enemy>>,
-    placed_enemies: BTreeMap<DungeonPath, Rc<Enemy>>,
-    active_enemies: BTreeMap<DungeonPath, Rc<Enemy>>,
+    /// all enemies, including ones left in other levels
+    enemies: BTreeMap<EnemyId, Enemy>,
+    #[serde(with = "path_map")]
+    placed_enemies: BTreeMap<DungeonPath, EnemyId>,
+    #[serde(with = "path_map")]
+    active_enemies: BTreeMap<DungeonPath, EnemyId>,
     rng: RngHandle,
     config: ConfigInner,
     next_id: EnemyId,
+    /// the player moves silently and doesn't wake up enemies
+    #[serde(default)]
+    sneaking: bool,
+    /// items carried by enemies, as gold grabbed by greedy enemies
+    #[serde(default)]
+    carried: BTreeMap<EnemyId, ItemToken>,
 }
 
 impl EnemyHandler {
@@ -251,12 +351,14 @@ impl EnemyHandler {
         stats.sort_by_key(|stat| stat.rarelity);
         EnemyHandler {
             enemy_stats: stats,
-            enemies: Vec::new(),
+            enemies: BTreeMap::new(),
             placed_enemies: Default::default(),
             active_enemies: Default::default(),
             rng,
             config,
             next_id: EnemyId(0),
+            sneaking: false,
+            carried: BTreeMap::new(),
         }
     }
     pub fn is_no_enemy(&self) -> bool {
@@ -283,12 +385,13 @@ impl EnemyHandler {
             Exp(base as u32 * 4)
         }
     }
+    /// generates an enemy and registers it
     pub fn gen_enemy(
         &mut self,
         range: Range<u32>,
         lev_add: i64,
         has_gold: bool,
-    ) -> Option<Rc<Enemy>> {
+    ) -> Option<EnemyId> {
         let appear_parcent = if has_gold {
             self.config.appear_rate_gold
         } else {
@@ -297,56 +400,116 @@ impl EnemyHandler {
         if !self.rng.parcent(appear_parcent) {
             return None;
         }
+        self.new_enemy(range, lev_add)
+    }
+    /// generates an enemy, places it and wakes it up if it's mean
+    pub(crate) fn create_enemy(
+        &mut self,
+        path: DungeonPath,
+        range: Range<u32>,
+        lev_add: i64,
+    ) -> Option<EnemyId> {
+        let id = self.new_enemy(range, lev_add)?;
+        self.place(path.clone(), id);
+        if self.enemies[&id].is_mean() {
+            self.activate(path);
+        }
+        Some(id)
+    }
+    /// generates an enemy by the preset, as ones placed in custom levels
+    pub(crate) fn gen_enemy_by(&mut self, preset: Preset) -> EnemyId {
+        self.enemy_from_stat(&preset.build(), 0)
+    }
+    fn new_enemy(&mut self, range: Range<u32>, lev_add: i64) -> Option<EnemyId> {
         let idx = self.select(range);
-        let stat = self.enemy_stats.get(idx)?;
+        let stat = self.enemy_stats.get(idx)?.clone();
+        Some(self.enemy_from_stat(&stat, lev_add))
+    }
+    fn enemy_from_stat(&mut self, stat: &Status, lev_add: i64) -> EnemyId {
         let level = stat.level + lev_add.into();
         let hp = Dice::new(8, level).exec::<i64>(&mut self.rng).0.into();
+        let id = self.next_id.increment();
         let enem = Enemy {
-            attr: Cell::new(stat.attr),
+            attr: stat.attr,
             attack: stat.attack.clone(),
             defense: stat.defense - (lev_add as i32).into(),
             exp: stat.exp + Exp::from((lev_add * 10) as u32) + self.exp_add(level, hp),
-            hp: Cell::new(hp),
-            id: self.next_id.increment(),
+            hp,
+            id,
             level,
             name: stat.name.clone(),
             max_hp: hp,
-            running: Cell::default(),
+            running: false,
+            energy: Energy::default(),
             tile: stat.tile,
         };
-        let enem = Rc::new(enem);
-        self.enemies.push(Rc::downgrade(&enem));
-        Some(enem)
+        self.enemies.insert(id, enem);
+        id
     }
-    pub fn place(&mut self, path: DungeonPath, enemy: Rc<Enemy>) {
+    pub fn place(&mut self, path: DungeonPath, enemy: EnemyId) {
         if let Some(enem) = self.placed_enemies.insert(path, enemy) {
             debug!("EnemyHandler::place path is already used by {:?}", enem);
         }
     }
-    pub fn remove(&mut self, path: DungeonPath) {
-        self.placed_enemies.remove(&path);
-        self.active_enemies.remove(&path);
+    /// removes the enemy and returns the item it carried
+    pub fn remove(&mut self, path: DungeonPath) -> Option<ItemToken> {
+        let id = self
+            .placed_enemies
+            .remove(&path)
+            .or_else(|| self.active_enemies.remove(&path))?;
+        self.enemies.remove(&id);
+        self.carried.remove(&id)
     }
-    pub fn get_enemy(&self, path: &DungeonPath) -> Option<&Enemy> {
+    pub fn get(&self, id: EnemyId) -> Option<&Enemy> {
+        self.enemies.get(&id)
+    }
+    /// returns the enemy and the rng, for fighting with the enemy
+    pub(crate) fn get_with_rng(&mut self, id: EnemyId) -> Option<(&mut Enemy, &mut RngHandle)> {
+        let EnemyHandler { enemies, rng, .. } = self;
+        enemies.get_mut(&id).map(|enemy| (enemy, rng))
+    }
+    pub fn get_id(&self, path: &DungeonPath) -> Option<EnemyId> {
         self.placed_enemies
-            .get(&path)
-            .or_else(|| self.active_enemies.get(&path))
-            .map(AsRef::as_ref)
+            .get(path)
+            .or_else(|| self.active_enemies.get(path))
+            .cloned()
+    }
+    pub fn get_enemy(&self, path: &DungeonPath) -> Option<&Enemy> {
+        self.get_id(path).and_then(|id| self.get(id))
+    }
+    fn get_enemy_mut(&mut self, path: &DungeonPath) -> Option<&mut Enemy> {
+        let id = self.get_id(path)?;
+        self.enemies.get_mut(&id)
     }
-    pub fn get_cloned(&self, path: &DungeonPath) -> Option<Rc<Enemy>> {
+    /// the first enemy on the ray
+    pub(crate) fn target<'a>(
+        &self,
+        ray: impl IntoIterator<Item = &'a DungeonPath>,
+    ) -> Option<(DungeonPath, EnemyId)> {
+        ray.into_iter()
+            .find_map(|path| Some((path.clone(), self.get_id(path)?)))
+    }
+    /// all enemies with their places, including ones in other levels
+    pub fn placed(&self) -> impl Iterator<Item = (&DungeonPath, &Enemy)> {
         self.placed_enemies
-            .get(&path)
-            .or_else(|| self.active_enemies.get(&path))
-            .map(Rc::clone)
+            .iter()
+            .chain(self.active_enemies.iter())
+            .filter_map(move |(path, id)| Some((path, self.enemies.get(id)?)))
     }
-    pub fn activate_area<'a, F>(&mut self, is_in_activation_area: F)
+    pub fn activate_area<F>(&mut self, is_in_activation_area: F)
     where
         F: Fn(&DungeonPath) -> bool,
     {
+        if self.sneaking {
+            return;
+        }
         let removes: Vec<_> = self
             .placed_enemies
             .iter()
-            .filter(|(p, e)| is_in_activation_area(p) && e.is_mean())
+            .filter(|(p, id)| {
+                let enemy = &self.enemies[id];
+                is_in_activation_area(p) && enemy.is_mean() && !enemy.is_held()
+            })
             .map(|(p, _)| p.to_owned())
             .collect();
         for path in removes {
@@ -354,19 +517,114 @@ impl EnemyHandler {
         }
     }
     pub(crate) fn activate(&mut self, place: DungeonPath) -> Option<()> {
-        let enem = self.placed_enemies.remove(&place)?;
-        enem.run();
-        self.active_enemies.insert(place, enem);
+        let id = self.placed_enemies.remove(&place)?;
+        self.enemies.get_mut(&id)?.run();
+        self.active_enemies.insert(place, id);
+        Some(())
+    }
+    /// slows down or speeds up the enemy and wakes it up
+    pub(crate) fn change_speed(&mut self, path: &DungeonPath, faster: bool) {
+        if let Some(enemy) = self.get_enemy_mut(path) {
+            enemy.change_speed(faster);
+        }
+        self.activate(path.clone());
+    }
+    /// cancels special abilities of the enemy
+    pub(crate) fn cancel(&mut self, path: &DungeonPath) {
+        if let Some(enemy) = self.get_enemy_mut(path) {
+            enemy.cancel();
+        }
+    }
+    /// turns the enemy into a random one
+    pub(crate) fn polymorph(&mut self, path: &DungeonPath) -> Option<EnemyId> {
+        let old = self.get_id(path)?;
+        let range = 0..self.enemy_stats.len() as u32;
+        let id = self.new_enemy(range, 0)?;
+        let is_running = self.enemies.remove(&old).is_some_and(|e| e.is_running());
+        if let Some(item) = self.carried.remove(&old) {
+            self.carried.insert(id, item);
+        }
+        if is_running {
+            self.active_enemies.insert(path.clone(), id);
+            self.enemies.get_mut(&id)?.run();
+        } else {
+            self.placed_enemies.insert(path.clone(), id);
+        }
+        Some(id)
+    }
+    /// moves the enemy to another place and wakes it up
+    pub(crate) fn teleport(&mut self, from: &DungeonPath, to: DungeonPath) -> Option<()> {
+        self.flee(from, to.clone())?;
+        self.activate(to)
+    }
+    /// moves the enemy to another place and stops it, as a leprechaun running away with gold
+    pub(crate) fn flee(&mut self, from: &DungeonPath, to: DungeonPath) -> Option<()> {
+        let id = self
+            .placed_enemies
+            .remove(from)
+            .or_else(|| self.active_enemies.remove(from))?;
+        self.enemies.get_mut(&id)?.running = false;
+        self.placed_enemies.insert(to, id);
         Some(())
     }
-    pub(crate) fn remove_enemies(&mut self) {
-        self.active_enemies = BTreeMap::new();
-        self.placed_enemies = BTreeMap::new();
+    /// regenerating enemies heal for a turn
+    pub(crate) fn regenerate(&mut self) {
+        self.enemies
+            .values_mut()
+            .filter(|enemy| enemy.is_regenerating())
+            .for_each(Enemy::regenerate);
+    }
+    pub(crate) fn sneak(&mut self, sneaking: bool) {
+        self.sneaking = sneaking;
+    }
+    /// holds enemies in the area and returns how many enemies are held
+    pub(crate) fn hold<F>(&mut self, is_in_area: F) -> usize
+    where
+        F: Fn(&DungeonPath) -> bool,
+    {
+        let actives: Vec<_> = self
+            .active_enemies
+            .keys()
+            .filter(|p| is_in_area(p))
+            .cloned()
+            .collect();
+        for path in actives {
+            if let Some(id) = self.active_enemies.remove(&path) {
+                self.placed_enemies.insert(path, id);
+            }
+        }
+        let mut count = 0;
+        for (_, id) in self.placed_enemies.iter().filter(|(p, _)| is_in_area(p)) {
+            if let Some(enemy) = self.enemies.get_mut(id) {
+                enemy.hold();
+                count += 1;
+            }
+        }
+        count
+    }
+    /// wakes up all enemies in the area
+    pub(crate) fn aggravate<F>(&mut self, is_in_area: F)
+    where
+        F: Fn(&DungeonPath) -> bool,
+    {
+        let placed: Vec<_> = self
+            .placed_enemies
+            .keys()
+            .filter(|p| is_in_area(p))
+            .cloned()
+            .collect();
+        for path in placed {
+            self.activate(path);
+        }
+    }
+    /// stops all active enemies, to leave them in the level the player is leaving
+    pub(crate) fn deactivate_all(&mut self) {
+        let actives = ::std::mem::take(&mut self.active_enemies);
+        self.placed_enemies.extend(actives);
     }
     pub(crate) fn move_actives(
         &mut self,
         player_pos: &DungeonPath,
-        gold_pos: Option<&DungeonPath>,
         dungeon: &mut dyn Dungeon,
     ) -> Vec<Attack> {
         let mut out = Vec::new();
@@ -379,42 +637,18 @@ impl EnemyHandler {
             ::std::mem::swap(&mut tmp, &mut self.active_enemies);
             tmp
         };
-        for (path, enemy) in active_enemies {
-            let next = (|| {
-                let EnemyHandler {
-                    ref mut rng,
-                    ref active_enemies,
-                    ref placed_enemies,
-                    ..
-                } = self;
-                let skip: &dyn Fn(&DungeonPath) -> bool =
-                    &|p| active_enemies.contains_key(p) || placed_enemies.contains_key(p);
-                if let Some(gold_pos) = gold_pos {
-                    if enemy.is_greedy() {
-                        match dungeon.move_enemy(&path, gold_pos, skip) {
-                            MoveResult::Reach => return path,
-                            MoveResult::CanMove(path) => return path,
-                            MoveResult::CantMove => {}
-                        }
-                    }
-                }
-                let res = if (rng.does_happen(2) && enemy.is_random())
-                    || (!rng.does_happen(5) && enemy.is_confused())
-                {
-                    dungeon.move_enemy_randomly(&path, player_pos, skip)
-                } else {
-                    dungeon.move_enemy(&path, player_pos, skip)
-                };
-                match res {
-                    MoveResult::Reach => {
-                        out.push(Attack(Rc::clone(&enemy)));
-                        path
-                    }
-                    MoveResult::CanMove(p) => p,
-                    MoveResult::CantMove => path,
+        for (mut path, id) in active_enemies {
+            let moves = self.enemies.get_mut(&id).map_or(0, Enemy::moves_in_turn);
+            let is_flying = self.enemies.get(&id).is_some_and(Enemy::is_flying);
+            for _ in 0..moves {
+                path = self.move_enemy(path, id, player_pos, dungeon, &mut out);
+                // as in rogue, flying enemies move twice unless they are next to the player
+                let player_cd = dungeon.path_to_cd(player_pos);
+                if is_flying && !dungeon.path_to_cd(&path).is_adjacent(player_cd) {
+                    path = self.move_enemy(path, id, player_pos, dungeon, &mut out);
                 }
-            })();
-            self.active_enemies.insert(next, enemy);
+            }
+            self.active_enemies.insert(path, id);
         }
         debug!(
             "[EnemyHandler::move_actives] after: {:?}",
@@ -422,11 +656,96 @@ impl EnemyHandler {
         );
         out
     }
+    fn move_enemy(
+        &mut self,
+        path: DungeonPath,
+        id: EnemyId,
+        player_pos: &DungeonPath,
+        dungeon: &mut dyn Dungeon,
+        out: &mut Vec<Attack>,
+    ) -> DungeonPath {
+        let EnemyHandler {
+            ref mut rng,
+            ref enemies,
+            ref active_enemies,
+            ref placed_enemies,
+            ref mut carried,
+            ..
+        } = self;
+        let enemy = &enemies[&id];
+        let skip: &dyn Fn(&DungeonPath) -> bool =
+            &|p| active_enemies.contains_key(p) || placed_enemies.contains_key(p);
+        // as in rogue, greedy enemies head for gold in their room and grab it
+        let gold = dungeon
+            .gold_in_room(&path)
+            .filter(|gold| enemy.is_greedy() && !carried.contains_key(&id) && gold != player_pos);
+        if let Some(gold) = gold {
+            match dungeon.move_enemy(&path, &gold, skip) {
+                MoveResult::Reach => {
+                    if let Some(item) = dungeon.remove_item(&gold) {
+                        carried.insert(id, item);
+                    }
+                    return gold;
+                }
+                MoveResult::CanMove(path) => return path,
+                MoveResult::CantMove => {}
+            }
+        }
+        // as in rogue, confused enemies stumble with a probability of 4/5,
+        // bats flutter with 1/2 and invisible enemies with 1/5
+        let res = if (enemy.is_confused() && !rng.does_happen(5))
+            || (enemy.is_random() && rng.does_happen(2))
+            || (enemy.is_invisible() && rng.does_happen(5))
+        {
+            dungeon.move_enemy_randomly(&path, player_pos, skip)
+        } else {
+            dungeon.move_enemy(&path, player_pos, skip)
+        };
+        match res {
+            MoveResult::Reach => {
+                out.push(Attack(id, path.clone()));
+                path
+            }
+            MoveResult::CanMove(p) => p,
+            MoveResult::CantMove => path,
+        }
+    }
+    /// random number generator for all actions in a turn, including the player's ones,
+    /// e.g., traps, eating, quaffing and zapping.
+    /// The player has no generator of its own, so actions share this one, as fights always did,
+    /// and a game is still reproduced from its seed and inputs.
     pub(crate) fn rng(&mut self) -> &mut RngHandle {
         &mut self.rng
     }
 }
 
+/// (de)serializes maps keyed by DungeonPath as sequences,
+/// because json doesn't allow DungeonPath as a key
+mod path_map {
+    use super::EnemyId;
+    use crate::dungeon::DungeonPath;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+    pub(super) fn serialize<S>(
+        map: &BTreeMap<DungeonPath, EnemyId>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+    pub(super) fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<BTreeMap<DungeonPath, EnemyId>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let seq = Vec::<(DungeonPath, EnemyId)>::deserialize(deserializer)?;
+        Ok(seq.into_iter().collect())
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StaticStatus {
     attack: &'static [Dice<HitPoint>],
@@ -705,7 +1024,7 @@ pub const BUILTIN_ENEMIES: [StaticStatus; 26] = [
     },
     StaticStatus {
         attack: &[hp_dice!(1, 19)],
-        attr: enem_attr!(MEAN, REGENERATE,),
+        attr: enem_attr!(MEAN, REGENERATE, DRAINS_HP,),
         defense: Defense(1),
         exp: Exp(350),
         gold: ItemNum(20),
@@ -716,7 +1035,7 @@ pub const BUILTIN_ENEMIES: [StaticStatus; 26] = [
     },
     StaticStatus {
         attack: &[hp_dice!(1, 6)],
-        attr: enem_attr!(),
+        attr: enem_attr!(DRAINS_LEVEL,),
         defense: Defense(4),
         exp: Exp(55),
         gold: ItemNum(0),
@@ -759,3 +1078,56 @@ pub const BUILTIN_ENEMIES: [StaticStatus; 26] = [
         tile: b'Z',
     },
 ];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn run_keeps_special_attacks() {
+        let wraith = BUILTIN_ENEMIES
+            .iter()
+            .position(|stat| stat.name == "wraith")
+            .unwrap();
+        let config = Config {
+            enemies: vec![Preset::Builtin(wraith)],
+            ..Default::default()
+        };
+        let mut handler = config.build(1);
+        let id = handler.new_enemy(0..1, 0).unwrap();
+        assert!(!handler.enemies[&id].is_held());
+        let path = DungeonPath::from([1, 1, 1]);
+        handler.place(path.clone(), id);
+        handler.activate(path);
+        let enemy = &handler.enemies[&id];
+        assert!(enemy.is_running());
+        assert!(!enemy.is_held());
+        assert!(enemy.can(EnemyAttr::DRAINS_LEVEL));
+    }
+    #[test]
+    fn attrs_are_distinct_bits() {
+        let attrs = [
+            EnemyAttr::MEAN,
+            EnemyAttr::FLYING,
+            EnemyAttr::REGENERATE,
+            EnemyAttr::GREEDY,
+            EnemyAttr::INVISIBLE,
+            EnemyAttr::RUSTS_ARMOR,
+            EnemyAttr::STEAL_GOLD,
+            EnemyAttr::REDUCE_STR,
+            EnemyAttr::FREEZES,
+            EnemyAttr::RANDOM,
+            EnemyAttr::CONFUSED,
+            EnemyAttr::DRAINS_LEVEL,
+            EnemyAttr::HELD,
+            EnemyAttr::SLOWED,
+            EnemyAttr::HASTED,
+            EnemyAttr::CANCELED,
+            EnemyAttr::DRAINS_HP,
+        ];
+        for (i, attr) in attrs.iter().enumerate() {
+            assert_eq!(attr.0.count_ones(), 1, "{:?}", attr);
+            assert!(attrs[i + 1..].iter().all(|other| attr.0 & other.0 == 0));
+        }
+        assert_eq!(EnemyAttr::NONE.0, 0);
+    }
+}
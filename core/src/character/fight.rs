@@ -1,15 +1,15 @@
 use super::{Damage, Defense, Dice, Enemy, HitPoint, Level, Player, Strength};
-use crate::item::ItemToken;
+use crate::item::{ring::RingKind, ItemToken};
 use crate::rng::{Parcent, RngHandle};
 use std::iter;
 
 pub fn player_attack(
     player: &Player,
-    throw_weapon: Option<ItemToken>,
+    throw_weapon: Option<&ItemToken>,
     enemy: &Enemy,
     rng: &mut RngHandle,
 ) -> Option<HitPoint> {
-    let (attack_rate, dam_plus) = if let Some(ref item) = throw_weapon {
+    let (attack_rate, dam_plus) = if let Some(item) = throw_weapon {
         let (mut hit_plus, mut dam_plus) = (item.hit_plus(), item.dam_plus());
         let name = player.weapon().and_then(|w| w.name());
         if name.is_some() && name == item.launcher() {
@@ -24,20 +24,50 @@ pub fn player_attack(
         let dam_plus = player.weapon().map(|w| w.dam_plus()).unwrap_or(HitPoint(0));
         (attack_rate, dam_plus)
     };
-    let dice = if let Some(ref item) = throw_weapon {
+    let dice = if let Some(item) = throw_weapon {
         item.at_throw()
     } else {
         player.weapon().and_then(|w| w.at_weild())
     }
     .unwrap_or(Dice::new(1, HitPoint(4)));
+    let ring_plus = HitPoint(player.ring_plus(RingKind::IncreaseDamage));
     roll(
         iter::once(&dice),
         attack_rate,
-        dam_plus + damage_plus(player.strength().current),
+        dam_plus + ring_plus + damage_plus(player.strength().current),
         rng,
     )
 }
 
+/// an attack by the wand of striking, which rarely misses
+pub fn wand_attack(player: &Player, enemy: &Enemy, rng: &mut RngHandle) -> Option<HitPoint> {
+    // as in rogue, 2d8 or 3d8 + 9 with a probability of 1/20
+    let (dice, dam_plus) = if rng.does_happen(20) {
+        (Dice::new(3, HitPoint(8)), HitPoint(9))
+    } else {
+        (Dice::new(2, HitPoint(8)), HitPoint(1))
+    };
+    let attack_rate = attack_rate_player(player, enemy, Level(100));
+    roll(iter::once(&dice), attack_rate, dam_plus, rng)
+}
+
+/// saving throw against magic, as `save_throw(VS_MAGIC, ..)` in rogue
+pub fn save_throw(level: Level, rng: &mut RngHandle) -> bool {
+    let need = 17 - level.0 / 2;
+    rng.range(1..=20) >= need
+}
+
+/// saving throw against poison, as `save_throw(VS_POISON, ..)` in rogue
+pub fn save_poison(level: Level, rng: &mut RngHandle) -> bool {
+    let need = 14 - level.0 / 2;
+    rng.range(1..=20) >= need
+}
+
+/// checks if the arrow or the dart of a trap hits the player, as `swing` in rogue
+pub fn trap_hits(level: Level, player: &Player, rng: &mut RngHandle) -> bool {
+    rng.parcent(attack_rate(level, player.arm(), Level(1)))
+}
+
 pub fn enemy_attack(enemy: &Enemy, player: &Player, rng: &mut RngHandle) -> Option<HitPoint> {
     let attack_rate = attack_rate_enemy(player, enemy);
     let dam_plus = damage_plus(Enemy::STRENGTH);
@@ -73,7 +103,9 @@ fn roll<'a>(
 
 fn attack_rate_player(player: &Player, enemy: &Enemy, hit_plus: Level) -> Parcent {
     let st = player.strength().current;
-    let str_p = hit_prob_plus(st) + if enemy.is_running() { 0 } else { 4 }.into() + hit_plus;
+    let ring_plus = Level(player.ring_plus(RingKind::Dexterity));
+    let str_p =
+        hit_prob_plus(st) + if enemy.is_running() { 0 } else { 4 }.into() + hit_plus + ring_plus;
     attack_rate(player.level(), enemy.defense(), str_p)
 }
 
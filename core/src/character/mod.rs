@@ -259,12 +259,63 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DamageReaction {
     Death,
     None,
 }
 
+/// speed of a character, which decides how much energy it gains per turn
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Speed {
+    /// acts every other turn
+    Slow,
+    /// acts once per turn
+    Normal,
+    /// acts twice per turn
+    Fast,
+}
+
+impl Speed {
+    fn energy_per_turn(self) -> u32 {
+        match self {
+            Speed::Slow => Energy::ACTION_COST / 2,
+            Speed::Normal => Energy::ACTION_COST,
+            Speed::Fast => Energy::ACTION_COST * 2,
+        }
+    }
+}
+
+/// energy accumulated by a character, which is spent by actions
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Energy(u32);
+
+impl Energy {
+    /// energy needed for an action
+    const ACTION_COST: u32 = 12;
+    /// gains energy for a turn
+    pub(crate) fn charge(&mut self, speed: Speed) {
+        self.0 += speed.energy_per_turn();
+    }
+    /// spends energy for an action, and returns if there was enough energy
+    pub(crate) fn spend(&mut self) -> bool {
+        if self.0 < Self::ACTION_COST {
+            return false;
+        }
+        self.0 -= Self::ACTION_COST;
+        true
+    }
+    /// gains energy for a turn and spends all of it, and returns how many actions are taken
+    pub(crate) fn actions_in_turn(&mut self, speed: Speed) -> usize {
+        self.charge(speed);
+        let mut actions = 0;
+        while self.spend() {
+            actions += 1;
+        }
+        actions
+    }
+}
+
 pub(crate) fn clamp<T: Ord>(value: T, min: T, max: T) -> T {
     use std::cmp;
     cmp::max(cmp::min(value, max), min)
@@ -282,4 +333,16 @@ mod test {
             assert!(2 <= hp.0 && hp.0 <= 8);
         }
     }
+    #[test]
+    fn energy() {
+        let actions = |speed| {
+            let mut energy = Energy::default();
+            (0..6)
+                .map(|_| energy.actions_in_turn(speed))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(actions(Speed::Slow), vec![0, 1, 0, 1, 0, 1]);
+        assert_eq!(actions(Speed::Normal), vec![1; 6]);
+        assert_eq!(actions(Speed::Fast), vec![2; 6]);
+    }
 }
@@ -1,14 +1,22 @@
-use super::{clamp, DamageReaction, Defense, Dice, Exp, HitPoint, Level, Maxed, Strength};
+use super::{
+    clamp, DamageReaction, Defense, Dice, Energy, Exp, HitPoint, Level, Maxed, Speed, Strength,
+};
 use crate::dungeon::{Direction, DungeonPath};
 use crate::error::GameResult;
 use crate::item::{
-    armor, food::Food, itembox::ItemBox, weapon, InitItem, Item, ItemHandler, ItemKind, ItemToken,
+    armor,
+    food::Food,
+    itembox::ItemBox,
+    potion::PotionKind,
+    ring::{Ring, RingKind},
+    weapon, InitItem, Item, ItemAttr, ItemHandler, ItemId, ItemKind, ItemToken,
 };
 use crate::{
     rng::RngHandle,
     smallstr::SmallStr,
     tile::{Drawable, Tile},
 };
+use std::collections::BTreeMap;
 use std::{cmp, fmt};
 use tuple_map::TupleMap2;
 
@@ -19,6 +27,9 @@ pub struct Config {
     pub level: Leveling,
     #[serde(default = "default_hunger_time")]
     pub hunger_time: u32,
+    /// the player starves to death after this turns of fainting
+    #[serde(default = "default_starve_time")]
+    pub starve_time: u32,
     #[serde(default = "default_init_hp")]
     pub init_hp: HitPoint,
     #[serde(default = "default_init_str")]
@@ -36,6 +47,7 @@ impl Default for Config {
         Config {
             level: Leveling::default(),
             hunger_time: default_hunger_time(),
+            starve_time: default_starve_time(),
             init_hp: default_init_hp(),
             init_str: default_init_str(),
             max_items: default_max_items(),
@@ -49,6 +61,10 @@ const fn default_hunger_time() -> u32 {
     1300
 }
 
+const fn default_starve_time() -> u32 {
+    850
+}
+
 const fn default_init_hp() -> HitPoint {
     HitPoint(12)
 }
@@ -84,19 +100,23 @@ impl Config {
             config: self,
             armor: None,
             weapon: None,
+            rings: [None; 2],
         }
     }
 }
 
 /// Representation of player
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Player {
     /// player position
     pub pos: DungeonPath,
     /// item box
     pub itembox: ItemBox,
-    armor: Option<ItemToken>,
-    weapon: Option<ItemToken>,
+    armor: Option<ItemId>,
+    weapon: Option<ItemId>,
+    /// rings on the left and right hands
+    #[serde(default)]
+    rings: [Option<ItemId>; 2],
     /// player status(for drawing)
     status: StatusInner,
     /// configuration
@@ -106,21 +126,150 @@ pub struct Player {
 impl Player {
     pub fn fill_status(&self, status: &mut Status) {
         status.hp = self.status.hp;
-        status.strength = self.status.strength;
+        status.strength = self.strength();
         status.exp = self.status.exp;
         status.player_level = self.status.level.0 as u32;
-        let hunger = self.config.hunger_time / 10;
-        status.hunger_level = match self.status.food_left {
+        status.hunger_level = self.hunger();
+    }
+    pub fn hunger(&self) -> Hunger {
+        let hunger = i64::from(self.config.hunger_time / 10);
+        match self.status.food_left {
+            x if x <= 0 => Hunger::Fainting,
             x if x <= hunger => Hunger::Weak,
             x if x <= hunger * 2 => Hunger::Hungry,
             _ => Hunger::Normal,
-        };
+        }
+    }
+    /// eats the food and restores food_left
+    pub(crate) fn eat(&mut self, food: &Food, rng: &mut RngHandle) {
+        let hunger_time = self.config.hunger_time;
+        // as in rogue, STOMACHSIZE(2000) / HUNGERTIME(1300)
+        let stomach_size = i64::from(hunger_time) * 20 / 13;
+        let food_left = cmp::max(self.status.food_left, 0);
+        self.status.food_left = cmp::min(
+            food_left + i64::from(food.nutrition(hunger_time, rng)),
+            stomach_size,
+        );
+    }
+    /// drinks the potion and gets its effect
+    pub(crate) fn quaff(&mut self, kind: PotionKind, rng: &mut RngHandle) {
+        let sustain_str = self.wears_ring(RingKind::SustainStrength);
+        let status = &mut self.status;
+        match kind {
+            PotionKind::Confusion => self.add_effect(Effect::Confused, spread(HUH_DURATION, rng)),
+            PotionKind::Poison if sustain_str => {}
+            PotionKind::Poison => {
+                let str_down = Strength(rng.range(1..=3));
+                status.strength.current = cmp::max(status.strength.current - str_down, Strength(3));
+            }
+            PotionKind::GainStrength => {
+                status.strength.current += Strength(1);
+                status.strength.max = cmp::max(status.strength.max, status.strength.current);
+            }
+            PotionKind::SeeInvisible => {
+                self.cure(Effect::Blind);
+                self.add_effect(Effect::SeeInvisible, spread(SEE_DURATION, rng));
+            }
+            PotionKind::Healing => {
+                let level = status.level.0 as usize;
+                status.hp.current += Dice::new(level, HitPoint(4)).exec::<i64>(rng);
+                if status.hp.current > status.hp.max {
+                    status.hp.max += HitPoint(1);
+                    status.hp.current = status.hp.max;
+                }
+                self.cure(Effect::Blind);
+            }
+            PotionKind::ExtraHealing => {
+                let level = status.level.0 as usize;
+                status.hp.current += Dice::new(level, HitPoint(8)).exec::<i64>(rng);
+                if status.hp.current > status.hp.max {
+                    if status.hp.current > status.hp.max + HitPoint(level as i64 + 1) {
+                        status.hp.max += HitPoint(1);
+                    }
+                    status.hp.max += HitPoint(1);
+                    status.hp.current = status.hp.max;
+                }
+                self.cure(Effect::Blind);
+            }
+            PotionKind::DetectMonsters => {
+                self.add_effect(Effect::DetectMonsters, HUH_DURATION);
+            }
+            PotionKind::DetectMagic => self.add_effect(Effect::DetectMagic, HUH_DURATION),
+            PotionKind::RaiseLevel => {
+                let level = status.level.0 as usize;
+                if let Some(&exp) = self.config.level.exps.get(level - 1) {
+                    status.exp = exp;
+                    self.level_up(Exp(0), rng);
+                }
+            }
+            PotionKind::Haste => self.add_effect(Effect::Hasted, rng.range(4..8)),
+            PotionKind::RestoreStrength => {
+                status.strength.current = cmp::max(status.strength.current, status.strength.max);
+            }
+            PotionKind::Blindness => self.add_effect(Effect::Blind, spread(SEE_DURATION, rng)),
+        }
+    }
+    /// checks if the player is under the effect or not
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        if effect == Effect::SeeInvisible && self.wears_ring(RingKind::SeeInvisible) {
+            return true;
+        }
+        self.status.effects.contains_key(&effect)
+    }
+    /// adds the effect, or lengthens it if the player is already under it
+    pub(crate) fn add_effect(&mut self, effect: Effect, turns: u32) {
+        *self.status.effects.entry(effect).or_insert(0) += turns;
+    }
+    /// removes the effect
+    pub(crate) fn cure(&mut self, effect: Effect) -> bool {
+        self.status.effects.remove(&effect).is_some()
+    }
+    /// the player falls asleep and can't move for the turns
+    pub(crate) fn fall_asleep(&mut self, turns: u32) {
+        self.status.fainted += turns;
+    }
+    /// removes curses of all equipped items
+    pub(crate) fn remove_curse(&mut self) {
+        self.itembox
+            .tokens_mut()
+            .filter(|token| token.attr.contains(ItemAttr::IS_EQUIPPED))
+            .for_each(|token| token.attr.remove(ItemAttr::IS_CURSED));
+    }
+    pub fn speed(&self) -> Speed {
+        if self.has_effect(Effect::Hasted) {
+            Speed::Fast
+        } else {
+            Speed::Normal
+        }
+    }
+    /// spends energy for the next action, and returns if the player can act without a turn passing
+    pub(crate) fn spend_energy(&mut self) -> bool {
+        !self.is_fainted() && self.status.energy.spend()
+    }
+    /// gains energy for a turn, unless the player is fainting
+    pub(crate) fn charge_energy(&mut self) {
+        if !self.is_fainted() {
+            let speed = self.speed();
+            self.status.energy.charge(speed);
+        }
+    }
+    /// the player can't move while fainting
+    pub fn is_fainted(&self) -> bool {
+        self.status.fainted > 0
+    }
+    /// the player is held by a bear trap and can't move for the turns
+    pub(crate) fn get_stuck(&mut self, turns: u32) {
+        self.status.stuck += turns;
+    }
+    /// the player can't move while stuck in a bear trap
+    pub fn is_stuck(&self) -> bool {
+        self.status.stuck > 0
     }
     pub fn run(&mut self, b: bool) {
         self.status.running = b;
     }
     pub fn armor(&self) -> Option<&ItemToken> {
-        self.armor.as_ref()
+        self.armor.and_then(|id| self.itembox.get(id))
     }
     pub fn arm(&self) -> Defense {
         self.armor()
@@ -129,9 +278,101 @@ impl Player {
                 _ => return None,
             })
             .unwrap_or(Defense(0))
+            + Defense(self.ring_plus(RingKind::Protection) as i32)
+    }
+    /// rings the player is wearing
+    pub fn rings(&self) -> impl Iterator<Item = &Ring> {
+        self.rings
+            .iter()
+            .filter_map(move |id| match &self.itembox.get((*id)?)?.kind {
+                ItemKind::Ring(ring) => Some(ring),
+                _ => None,
+            })
+    }
+    pub fn wears_ring(&self, kind: RingKind) -> bool {
+        self.rings().any(|ring| ring.kind == kind)
+    }
+    /// sum of pluses of the rings of the kind
+    pub fn ring_plus(&self, kind: RingKind) -> i64 {
+        self.rings()
+            .filter(|ring| ring.kind == kind)
+            .map(Ring::plus)
+            .sum()
+    }
+    /// puts on the ring if the player has a free hand
+    pub(crate) fn put_on_ring(&mut self, id: ItemId) -> bool {
+        let hand = match self.rings.iter_mut().find(|hand| hand.is_none()) {
+            Some(hand) => hand,
+            None => return false,
+        };
+        *hand = Some(id);
+        self.set_equipped(id, true);
+        true
+    }
+    /// removes the ring from the hand
+    pub(crate) fn remove_ring(&mut self, id: ItemId) -> bool {
+        let hand = match self.rings.iter_mut().find(|hand| **hand == Some(id)) {
+            Some(hand) => hand,
+            None => return false,
+        };
+        *hand = None;
+        self.set_equipped(id, false);
+        true
+    }
+    /// wields the weapon instead of the current one
+    pub(crate) fn wield(&mut self, id: ItemId) {
+        if let Some(old) = self.weapon.replace(id) {
+            self.set_equipped(old, false);
+        }
+        self.set_equipped(id, true);
+    }
+    /// wears the armor, assuming that the player wears nothing
+    pub(crate) fn wear(&mut self, id: ItemId) {
+        self.armor = Some(id);
+        self.set_equipped(id, true);
+        // as in rogue, the player knows the enchantment of worn armor
+        if let Some(token) = self.itembox.get_mut(id) {
+            token.attr.or(ItemAttr::IS_KNOWN);
+        }
+    }
+    pub(crate) fn take_off(&mut self) -> Option<ItemId> {
+        let id = self.armor.take()?;
+        self.set_equipped(id, false);
+        Some(id)
+    }
+    /// stops using the item, before dropping it
+    pub(crate) fn unequip(&mut self, id: ItemId) {
+        if self.weapon == Some(id) {
+            self.weapon = None;
+        }
+        if self.armor == Some(id) {
+            self.armor = None;
+        }
+        self.rings
+            .iter_mut()
+            .filter(|hand| **hand == Some(id))
+            .for_each(|hand| *hand = None);
+        self.set_equipped(id, false);
+    }
+    fn set_equipped(&mut self, id: ItemId, equipped: bool) {
+        if let Some(token) = self.itembox.get_mut(id) {
+            if equipped {
+                token.attr.equip();
+            } else {
+                token.attr.remove(ItemAttr::IS_EQUIPPED);
+            }
+        }
+    }
+    pub(crate) fn armor_mut(&mut self) -> Option<&mut ItemToken> {
+        let id = self.armor?;
+        self.itembox.get_mut(id)
     }
     pub fn weapon(&self) -> Option<&ItemToken> {
-        self.weapon.as_ref()
+        self.weapon.and_then(|id| self.itembox.get(id))
+    }
+    pub(crate) fn weapon_mut(&mut self) -> Option<&mut ItemToken> {
+        let id = self.weapon?;
+        self.itembox.get_mut(id)
     }
     pub fn init_items(&mut self, items: &mut ItemHandler) -> GameResult<()> {
         items.init_player_items(&mut self.itembox, &self.config.init_items)?;
@@ -151,8 +392,13 @@ impl Player {
         }
         Ok(())
     }
+    pub fn hp(&self) -> Maxed<HitPoint> {
+        self.status.hp
+    }
     pub fn strength(&self) -> Maxed<Strength> {
-        self.status.strength
+        let mut strength = self.status.strength;
+        strength.current += Strength(self.ring_plus(RingKind::AddStrength));
+        strength
     }
     pub fn level(&self) -> Level {
         self.status.level
@@ -162,16 +408,48 @@ impl Player {
     }
     pub(crate) fn turn_passed(&mut self, rng: &mut RngHandle) -> Vec<PlayerEvent> {
         let mut res = vec![];
-        self.status.food_left -= 1;
-        if self.status.food_left == 0 {
+        if self.status.fainted > 0 {
+            self.status.fainted -= 1;
+        }
+        if self.status.stuck > 0 {
+            self.status.stuck -= 1;
+        }
+        let effects = &mut self.status.effects;
+        effects.values_mut().for_each(|turns| *turns -= 1);
+        let ended: Vec<_> = effects
+            .iter()
+            .filter(|(_, &t)| t == 0)
+            .map(|(&e, _)| e)
+            .collect();
+        for effect in ended {
+            effects.remove(&effect);
+            res.push(PlayerEvent::EffectEnded(effect));
+        }
+        let before = self.hunger();
+        let ring_eat: i64 = self.rings().map(|ring| ring.eat(rng)).sum();
+        self.status.food_left -= 1 + ring_eat;
+        if self.status.food_left < -i64::from(self.config.starve_time) {
             return vec![PlayerEvent::Dead];
         }
-        if self.notify_hungry() {
-            res.push(PlayerEvent::Hungry);
+        let after = self.hunger();
+        if before != after {
+            res.push(PlayerEvent::Hunger(after));
+        }
+        // as in rogue, a starving player faints with a probability of 1/5
+        if self.status.food_left <= 0 && !self.is_fainted() && rng.does_happen(5) {
+            self.status.fainted = rng.range(4..12);
+            res.push(PlayerEvent::Fainted);
         }
         if self.heal(rng) {
             res.push(PlayerEvent::Healed);
         }
+        if self.wears_ring(RingKind::Searching) {
+            res.push(PlayerEvent::Search);
+        }
+        // as in rogue, the ring of teleportation teleports the player with a probability of 1/50
+        if self.wears_ring(RingKind::Teleportation) && rng.does_happen(50) {
+            res.push(PlayerEvent::Teleport);
+        }
         res
     }
     pub(crate) fn get_damage(&mut self, damage: HitPoint) -> DamageReaction {
@@ -182,6 +460,74 @@ impl Player {
             DamageReaction::None
         }
     }
+    /// rusts the armor and returns if it rusted, or None if the player has no armor to rust
+    pub(crate) fn rust_armor(&mut self) -> Option<bool> {
+        let maintained = self.wears_ring(RingKind::MaintainArmor);
+        let armor = self.armor_mut()?.get_mut();
+        if armor.attr.contains(ItemAttr::IS_PROTECTED) {
+            return None;
+        }
+        Some(!maintained && armor.rust())
+    }
+    /// loses gold and returns how much gold was lost
+    pub(crate) fn lose_gold(&mut self, amount: u32) -> u32 {
+        let gold = self
+            .itembox
+            .tokens_mut()
+            .find(|token| token.get().kind == ItemKind::Gold);
+        match gold {
+            Some(token) => {
+                let num = &mut token.get_mut().how_many.0;
+                let lost = cmp::min(*num, amount);
+                *num -= lost;
+                lost
+            }
+            None => 0,
+        }
+    }
+    /// reduces the strength by 1 and returns if it was reduced
+    pub(crate) fn reduce_strength(&mut self) -> bool {
+        if self.wears_ring(RingKind::SustainStrength) {
+            return false;
+        }
+        let strength = &mut self.status.strength.current;
+        *strength = cmp::max(*strength - Strength(1), Strength(3));
+        true
+    }
+    /// the player is frozen and can't move for the turns, and dies of hypothermia if frozen too long
+    pub(crate) fn freeze(&mut self, turns: u32) -> DamageReaction {
+        self.fall_asleep(turns);
+        if self.status.fainted > MAX_FROZEN {
+            DamageReaction::Death
+        } else {
+            DamageReaction::None
+        }
+    }
+    /// loses a level, or dies if the player has no exp
+    pub(crate) fn drain_level(&mut self) -> DamageReaction {
+        let status = &mut self.status;
+        if status.exp == Exp(0) {
+            return DamageReaction::Death;
+        }
+        if status.level <= Level(1) {
+            status.exp = Exp(0);
+        } else {
+            status.level -= Level(1);
+            status.exp = self.config.level.exp_for(status.level);
+        }
+        DamageReaction::None
+    }
+    /// reduces the max HP, and dies if it gets to 0
+    pub(crate) fn drain_max_hp(&mut self, hp: HitPoint) -> DamageReaction {
+        let status = &mut self.status;
+        status.hp.max -= hp;
+        status.hp.current = cmp::max(status.hp.current - hp, HitPoint(1));
+        if status.hp.max <= HitPoint(0) {
+            DamageReaction::Death
+        } else {
+            DamageReaction::None
+        }
+    }
     pub(crate) fn level_up(&mut self, exp: Exp, rng: &mut RngHandle) -> bool {
         self.status.exp += exp;
         let diff = self
@@ -211,12 +557,10 @@ impl Player {
             None
         })
     }
-    fn equip_from_box(&self, query: impl FnMut(&Item) -> bool) -> Option<ItemToken> {
-        self.itembox.find_by(query).map(|item| {
-            let mut item = item.clone();
-            item.get_mut().attr.equip();
-            item
-        })
+    fn equip_from_box(&mut self, query: impl FnMut(&Item) -> bool) -> Option<ItemId> {
+        let id = self.itembox.find_by(query)?.id();
+        self.itembox.get_mut(id)?.attr.equip();
+        Some(id)
     }
     fn heal(&mut self, rng: &mut RngHandle) -> bool {
         self.status.quiet += 1;
@@ -229,6 +573,11 @@ impl Player {
         } else {
             0
         };
+        let regenerate = self
+            .rings()
+            .filter(|ring| ring.kind == RingKind::Regeneration)
+            .count() as i64;
+        let heal = if heal > 0 { heal + regenerate } else { 0 };
         if heal > 0 {
             self.status.hp.current += HitPoint(heal);
             self.status.hp.verify();
@@ -238,16 +587,57 @@ impl Player {
             false
         }
     }
-    fn notify_hungry(&mut self) -> bool {
-        let hunger = self.config.hunger_time / 10;
-        self.status.food_left == hunger || self.status.food_left == hunger * 2
-    }
 }
 
 pub(crate) enum PlayerEvent {
     Dead,
+    Fainted,
     Healed,
-    Hungry,
+    Hunger(Hunger),
+    EffectEnded(Effect),
+    /// the ring of searching finds hidden things
+    Search,
+    /// the ring of teleportation moves the player
+    Teleport,
+}
+
+/// duration of confusion and detection
+pub(crate) const HUH_DURATION: u32 = 20;
+/// duration of blindness and see invisible
+const SEE_DURATION: u32 = 850;
+/// as BORE_LEVEL in rogue, a player frozen longer than this dies of hypothermia
+const MAX_FROZEN: u32 = 50;
+
+/// randomizes the duration, as `spread` in rogue
+pub(crate) fn spread(n: u32, rng: &mut RngHandle) -> u32 {
+    n - n / 20 + rng.range(0..n / 10)
+}
+
+/// temporary effects on the player
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Effect {
+    Confused,
+    Blind,
+    Hasted,
+    SeeInvisible,
+    DetectMonsters,
+    DetectMagic,
+    DetectFood,
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Effect::Confused => "confused",
+            Effect::Blind => "blind",
+            Effect::Hasted => "hasted",
+            Effect::SeeInvisible => "see invisible",
+            Effect::DetectMonsters => "detect monsters",
+            Effect::DetectMagic => "detect magic",
+            Effect::DetectFood => "detect food",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl Drawable for Player {
@@ -273,8 +663,19 @@ struct StatusInner {
     exp: Exp,
     /// level
     level: Level,
-    /// count down to death
-    food_left: u32,
+    /// count down to fainting, and to death if it's negative
+    food_left: i64,
+    /// turns left to wake up from fainting or sleeping
+    fainted: u32,
+    /// turns left to get out of a bear trap
+    #[serde(default)]
+    stuck: u32,
+    /// temporary effects and their remaining turns
+    #[serde(default)]
+    effects: BTreeMap<Effect, u32>,
+    /// energy for actions, which is gained according to the speed
+    #[serde(default)]
+    energy: Energy,
     running: bool,
     quiet: u32,
 }
@@ -286,7 +687,11 @@ impl StatusInner {
             strength: Maxed::max(Strength(16)),
             exp: Exp(0),
             level: Level(1),
-            food_left: config.hunger_time,
+            food_left: i64::from(config.hunger_time),
+            fainted: 0,
+            stuck: 0,
+            effects: BTreeMap::new(),
+            energy: Energy::default(),
             running: false,
             quiet: 0,
         }
@@ -302,6 +707,30 @@ pub enum Action {
     DownStair,
     Search,
     NoOp,
+    /// eat the item in the slot
+    Eat(usize),
+    /// quaff the potion in the slot
+    Quaff(usize),
+    /// read the scroll in the slot
+    Read(usize),
+    /// identify the item in the slot, after reading the scroll of identify
+    Identify(usize),
+    /// put on the ring in the slot
+    PutOn(usize),
+    /// remove the ring in the slot
+    Remove(usize),
+    /// zap the wand in the slot in the direction
+    Zap(usize, Direction),
+    /// wield the weapon in the slot
+    Wield(usize),
+    /// wear the armor in the slot
+    Wear(usize),
+    /// take off the armor in the slot
+    TakeOff(usize),
+    /// drop the item in the slot
+    Drop(usize),
+    /// throw the item in the slot in the direction
+    Throw(usize, Direction),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -343,6 +772,13 @@ impl Default for Leveling {
 }
 
 impl Leveling {
+    /// necessary exp for the level
+    fn exp_for(&self, level: Level) -> Exp {
+        match level.0 {
+            x if x <= 1 => Exp(0),
+            x => self.exps.get(x as usize - 2).cloned().unwrap_or(Exp(0)),
+        }
+    }
     fn check_level(&self, cur: Level, exp: Exp) -> usize {
         let cur = (cur.0 - 1) as usize;
         if cur >= self.exps.len() {
@@ -353,11 +789,12 @@ impl Leveling {
 }
 
 /// Hunger level
-#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Hunger {
     Normal,
     Hungry,
     Weak,
+    Fainting,
 }
 
 impl Hunger {
@@ -366,6 +803,7 @@ impl Hunger {
             Hunger::Normal => 0,
             Hunger::Hungry => 1,
             Hunger::Weak => 2,
+            Hunger::Fainting => 3,
         }
     }
 }
@@ -381,6 +819,7 @@ impl fmt::Display for Hunger {
         match self {
             Hunger::Hungry => write!(formatter, "hungry"),
             Hunger::Weak => write!(formatter, "weak"),
+            Hunger::Fainting => write!(formatter, "faint"),
             Hunger::Normal => Ok(()),
         }
     }
@@ -448,3 +887,101 @@ impl fmt::Display for Status {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn hunger_cycle() {
+        let config = Config {
+            hunger_time: 20,
+            starve_time: 10,
+            ..Default::default()
+        };
+        let mut player = config.build();
+        let mut rng = RngHandle::from_seed(1);
+        let mut hungers = vec![];
+        let mut fainted = 0;
+        let mut turns = 0;
+        loop {
+            turns += 1;
+            let events = player.turn_passed(&mut rng);
+            if events.iter().any(|e| matches!(e, PlayerEvent::Dead)) {
+                break;
+            }
+            for event in events {
+                match event {
+                    PlayerEvent::Hunger(h) => hungers.push(h),
+                    PlayerEvent::Fainted => fainted += 1,
+                    _ => {}
+                }
+            }
+        }
+        assert_eq!(
+            hungers,
+            vec![Hunger::Hungry, Hunger::Weak, Hunger::Fainting]
+        );
+        assert_eq!(player.hunger(), Hunger::Fainting);
+        assert!(fainted > 0);
+        assert_eq!(turns, 31);
+    }
+    #[test]
+    fn quaff_potions() {
+        let mut player = Config::default().build();
+        let mut rng = RngHandle::from_seed(1);
+        player.get_damage(HitPoint(5));
+        player.quaff(PotionKind::Healing, &mut rng);
+        assert!(player.status.hp.current > HitPoint(7));
+        player.quaff(PotionKind::Poison, &mut rng);
+        assert!(player.strength().current < Strength(16));
+        player.quaff(PotionKind::RestoreStrength, &mut rng);
+        assert_eq!(player.strength().current, Strength(16));
+        player.quaff(PotionKind::GainStrength, &mut rng);
+        assert_eq!(player.strength(), Maxed::max(Strength(17)));
+        player.quaff(PotionKind::RaiseLevel, &mut rng);
+        assert_eq!(player.level(), Level(2));
+        player.quaff(PotionKind::Blindness, &mut rng);
+        assert!(player.has_effect(Effect::Blind));
+        player.quaff(PotionKind::ExtraHealing, &mut rng);
+        assert!(!player.has_effect(Effect::Blind));
+        player.quaff(PotionKind::Confusion, &mut rng);
+        let mut turns = 0;
+        while player.has_effect(Effect::Confused) {
+            turns += 1;
+            let events = player.turn_passed(&mut rng);
+            if !player.has_effect(Effect::Confused) {
+                assert!(events
+                    .iter()
+                    .any(|e| matches!(e, PlayerEvent::EffectEnded(Effect::Confused))));
+            }
+        }
+        assert!((19..=21).contains(&turns));
+    }
+    #[test]
+    fn drained() {
+        let mut player = Config::default().build();
+        let mut rng = RngHandle::from_seed(1);
+        assert!(player.reduce_strength());
+        assert_eq!(
+            player.strength(),
+            Maxed {
+                max: Strength(16),
+                current: Strength(15)
+            }
+        );
+        // a player without exp dies by the level drain
+        assert_eq!(player.drain_level(), DamageReaction::Death);
+        player.level_up(Exp(25), &mut rng);
+        assert_eq!(player.level(), Level(3));
+        assert_eq!(player.drain_level(), DamageReaction::None);
+        assert_eq!((player.level(), player.status.exp), (Level(2), Exp(10)));
+        let max = player.hp().max;
+        assert_eq!(player.drain_max_hp(HitPoint(3)), DamageReaction::None);
+        assert_eq!(player.hp().max, max - HitPoint(3));
+        assert_eq!(player.drain_max_hp(max), DamageReaction::Death);
+        // frozen too long, the player dies of hypothermia
+        assert_eq!(player.freeze(3), DamageReaction::None);
+        assert!(player.is_fainted());
+        assert_eq!(player.freeze(MAX_FROZEN), DamageReaction::Death);
+    }
+}
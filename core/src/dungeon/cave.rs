@@ -0,0 +1,358 @@
+//! cave dungeon generated by cellular automata
+use super::rogue::{self, floor::Floor, FloorGen, Surface};
+use crate::dungeon::{Cell, CellAttr, Coord, Direction, DungeonSnapshot, Field, X, Y};
+use crate::{error::*, rng::Parcent, rng::RngHandle};
+use anyhow::{bail, Context};
+use enum_iterator::IntoEnumIterator;
+use ndarray::Array2;
+use rect_iter::{Get2D, GetMut2D, RectRange};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use tuple_map::TupleMap2;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    /// probability that a cell is rock before smoothing
+    #[serde(default = "default_fill_rate")]
+    pub fill_rate: Parcent,
+    /// how many times the cellular automaton smooths the cave
+    #[serde(default = "default_smoothing")]
+    pub smoothing: u32,
+    /// open regions smaller than this are filled with rock
+    #[serde(default = "default_min_region_size")]
+    pub min_region_size: usize,
+    /// settings shared with rogue, e.g., traps and the amulet level
+    #[serde(flatten)]
+    pub rogue: rogue::Config,
+}
+
+const fn default_fill_rate() -> Parcent {
+    Parcent(45)
+}
+
+const fn default_smoothing() -> u32 {
+    4
+}
+
+const fn default_min_region_size() -> usize {
+    16
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            fill_rate: default_fill_rate(),
+            smoothing: default_smoothing(),
+            min_region_size: default_min_region_size(),
+            rogue: rogue::Config::default(),
+        }
+    }
+}
+
+/// a cave with fewer open cells than this is generated again
+const MIN_OPEN_CELLS: usize = 40;
+
+/// how many times we try to generate a cave
+const MAX_TRIES: usize = 100;
+
+/// digs a cave by cellular automata and returns which cells are open
+fn dig_cave(config: &Config, width: X, height: Y, rng: &mut RngHandle) -> GameResult<Array2<bool>> {
+    // leave the message line, the status line and outer walls as rock
+    let inner = RectRange::from_ranges(1..width.0 - 1, 2..height.0 - 2)
+        .ok_or(ErrorKind::MaybeBug("[cave::dig_cave] too small dungeon"))?;
+    for _ in 0..MAX_TRIES {
+        let mut open = Array2::from_elem([height.0 as usize, width.0 as usize], false);
+        for cd in inner.iter() {
+            *open.get_mut_p(cd) = !rng.parcent(config.fill_rate);
+        }
+        for _ in 0..config.smoothing {
+            open = smooth(&open, &inner);
+        }
+        let mut regions = open_regions(&open, &inner);
+        regions.retain(|region| {
+            if region.len() >= config.min_region_size {
+                return true;
+            }
+            region.iter().for_each(|&cd| *open.get_mut_p(cd) = false);
+            false
+        });
+        if regions.is_empty() {
+            continue;
+        }
+        connect_regions(&mut open, regions, rng);
+        let regions = open_regions(&open, &inner);
+        if regions.len() == 1 && regions[0].len() >= MIN_OPEN_CELLS {
+            return Ok(open);
+        }
+    }
+    bail!(ErrorKind::MaybeBug(
+        "[cave::dig_cave] failed to generate a connected cave"
+    ))
+}
+
+/// a step of the cellular automaton, where a cell becomes rock
+/// if 5 or more neighbors are rock, and stays rock if 4 or more are
+fn smooth(open: &Array2<bool>, inner: &RectRange<i32>) -> Array2<bool> {
+    let mut res = open.clone();
+    for cd in inner.iter() {
+        let cd = Coord::from(cd);
+        let rocks = Direction::into_enum_iter()
+            .take(8)
+            .filter(|d| {
+                !open
+                    .try_get_p(cd + d.to_cd())
+                    .ok()
+                    .cloned()
+                    .unwrap_or(false)
+            })
+            .count();
+        let is_rock = rocks >= 5 || (!*open.get_p(cd) && rocks >= 4);
+        *res.get_mut_p(cd) = !is_rock;
+    }
+    res
+}
+
+/// open regions connected in 4 directions, which the player can walk through
+fn open_regions(open: &Array2<bool>, inner: &RectRange<i32>) -> Vec<Vec<Coord>> {
+    let mut used = Array2::from_elem(open.dim(), false);
+    let mut regions = Vec::new();
+    for start in inner.iter().map(Coord::from) {
+        if !*open.get_p(start) || *used.get_p(start) {
+            continue;
+        }
+        let mut region = vec![start];
+        let mut queue = VecDeque::new();
+        *used.get_mut_p(start) = true;
+        queue.push_back(start);
+        while let Some(cur) = queue.pop_front() {
+            for d in Direction::into_enum_iter().filter(|d| !d.is_diag() && *d != Direction::Stay) {
+                let next = cur + d.to_cd();
+                let is_open = open.try_get_p(next).ok().cloned().unwrap_or(false);
+                if !is_open || *used.get_p(next) {
+                    continue;
+                }
+                *used.get_mut_p(next) = true;
+                region.push(next);
+                queue.push_back(next);
+            }
+        }
+        regions.push(region);
+    }
+    regions
+}
+
+/// digs tunnels from smaller regions to the nearest cell of the largest one
+fn connect_regions(open: &mut Array2<bool>, mut regions: Vec<Vec<Coord>>, rng: &mut RngHandle) {
+    regions.sort_by_key(|region| Reverse(region.len()));
+    let mut connected = regions.remove(0);
+    for region in regions {
+        let start = region[rng.range(0..region.len())];
+        let goal = *connected
+            .iter()
+            .min_by_key(|&&cd| {
+                let diff = start - cd;
+                (diff.x.0, diff.y.0).map(i32::abs).sum()
+            })
+            .expect("[cave::connect_regions] no connected cell");
+        // dig horizontally first, then vertically
+        let mut cur = start;
+        while cur != goal {
+            if cur.x != goal.x {
+                cur.x += if cur.x < goal.x { X(1) } else { X(-1) };
+            } else {
+                cur.y += if cur.y < goal.y { Y(1) } else { Y(-1) };
+            }
+            if !*open.get_p(cur) {
+                *open.get_mut_p(cur) = true;
+                connected.push(cur);
+            }
+        }
+        connected.extend(region);
+    }
+}
+
+impl Config {
+    /// rejects settings which never make a cave, instead of retrying to generate it
+    fn check(&self, width: X, height: Y) -> GameResult<()> {
+        if self.fill_rate.0 >= 100 {
+            bail!(ErrorKind::InvalidSetting(
+                "fill_rate must be smaller than 100".into()
+            ));
+        }
+        let inner = ((width.0 - 2).max(0) * (height.0 - 4).max(0)) as usize;
+        if self.min_region_size > inner {
+            bail!(ErrorKind::InvalidSetting(
+                format!("min_region_size must be {} or smaller", inner).into()
+            ));
+        }
+        Ok(())
+    }
+
+    /// generates the structure of a cave, where rock next to open cells becomes walls
+    fn gen_field(&self, width: X, height: Y, rng: &mut RngHandle) -> GameResult<Field<Surface>> {
+        let open = dig_cave(self, width, height, rng)?;
+        let is_open = |cd: Coord| open.try_get_p(cd).ok().cloned().unwrap_or(false);
+        let mut field = Field::new(width, height, Cell::with_default_attr(Surface::None));
+        for cd in field.size().into_iter().map(Coord::from) {
+            let cell = field.get_mut_p(cd);
+            if is_open(cd) {
+                cell.surface = Surface::Floor;
+                // as passages in rogue, caves are dark
+                cell.attr = CellAttr::IS_DARK;
+            } else if Direction::into_enum_iter().any(|d| is_open(cd + d.to_cd())) {
+                let (left, right) = (Direction::Left, Direction::Right).map(|d| cd + d.to_cd());
+                cell.surface = if is_open(left) || is_open(right) {
+                    Surface::WallY
+                } else {
+                    Surface::WallX
+                };
+            }
+        }
+        Ok(field)
+    }
+}
+
+/// cave dungeon
+pub type Dungeon = rogue::Dungeon<Config>;
+
+impl FloorGen for Config {
+    fn base(&self) -> &rogue::Config {
+        &self.rogue
+    }
+    fn gen_floor(
+        &self,
+        _level: u32,
+        width: X,
+        height: Y,
+        rng: &mut RngHandle,
+    ) -> GameResult<Floor> {
+        const ERR_STR: &str = "in cave::Config::gen_floor";
+        self.check(width, height).context(ERR_STR)?;
+        let field = self.gen_field(width, height, rng).context(ERR_STR)?;
+        Ok(Floor::open(field))
+    }
+    fn snapshot(dungeon: &Dungeon) -> DungeonSnapshot {
+        DungeonSnapshot::Cave(dungeon.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::character::enemies;
+    use crate::dungeon::{rogue::Address, Dungeon as DungeonTrait, DungeonPath};
+    use crate::input::Key;
+    use crate::item::{self, ItemHandler, ItemKind, ItemNum};
+    use crate::{GameConfig, GameInfo, GlobalConfig, RunTime};
+    #[test]
+    #[ignore]
+    fn print_cave() {
+        let mut rng = RngHandle::new();
+        let field = Config::default().gen_field(X(80), Y(24), &mut rng).unwrap();
+        println!("{}", field);
+    }
+    #[test]
+    fn connected() {
+        let mut rng = RngHandle::new();
+        let config = Config::default();
+        for _ in 0..20 {
+            let field = config.gen_field(X(80), Y(24), &mut rng).unwrap();
+            let floor = Floor::open(field);
+            let start = floor.select_cell(&mut rng, false).unwrap();
+            let dist = floor.make_dist_map(start, false);
+            for cd in floor.field.size() {
+                let cell = floor.field.get_p(cd);
+                // all open cells are reachable, surrounded by walls
+                if cell.surface == Surface::Floor {
+                    assert_ne!(*dist.get_p(cd), u32::MAX);
+                    assert!((1..79).contains(&cd.0) && (2..22).contains(&cd.1));
+                }
+            }
+        }
+    }
+    #[test]
+    fn nearest_gold() {
+        let global = GlobalConfig {
+            width: X(80),
+            height: Y(24),
+            seed: 1,
+            hide_dungeon: true,
+        };
+        let mut item = ItemHandler::new(item::Config::default(), 1);
+        let mut enemies = enemies::Config::default().build(1);
+        let mut dungeon = Dungeon::new(
+            Config::default(),
+            &global,
+            &GameInfo::new(),
+            &mut item,
+            &mut enemies,
+            1,
+        )
+        .unwrap();
+        let floor = &mut dungeon.current_floor;
+        floor.items.clear();
+        let start = floor.select_cell(&mut dungeon.rng, true).unwrap();
+        let open_cell = |dist: &dyn Fn(i32) -> bool| {
+            floor
+                .field
+                .size()
+                .into_iter()
+                .map(Coord::from)
+                .find(|&cd| floor.field.get_p(cd).surface.is_open() && dist(start.move_dist(cd)))
+                .unwrap()
+        };
+        let (near, far) = (open_cell(&|d| (1..=8).contains(&d)), open_cell(&|d| d > 8));
+        let path = DungeonPath::from(Address::new(1, start));
+        for (cd, expected) in [(far, None), (near, Some(near))] {
+            let gold = item.gen_item(ItemKind::Gold.numbered(ItemNum(10)).unwrap().many());
+            dungeon.current_floor.put_item(cd, gold).unwrap();
+            // gold out of the distance limit is ignored
+            let gold = dungeon.gold_in_room(&path);
+            assert_eq!(gold.map(|p| Address::from_path(&p).cd), expected);
+        }
+    }
+    #[test]
+    fn invalid_config() {
+        let check = |config: Config| config.check(X(80), Y(24));
+        assert!(check(Config::default()).is_ok());
+        let full = Config {
+            fill_rate: Parcent(100),
+            ..Default::default()
+        };
+        assert!(check(full).is_err());
+        let huge = Config {
+            min_region_size: 78 * 20 + 1,
+            ..Default::default()
+        };
+        assert!(check(huge).is_err());
+    }
+    #[test]
+    fn play_cave() {
+        const CONFIG: &str = r#"
+{
+    "seed": 1,
+    "dungeon": {
+        "style": "cave",
+        "fill_rate": 40,
+        "smoothing": 3
+    }
+}
+"#;
+        let mut runtime = GameConfig::from_json(CONFIG).unwrap().build().unwrap();
+        for c in "hjklyubn".chars() {
+            runtime.react_to_key(Key::Char(c)).unwrap();
+        }
+        let stair = (0..80)
+            .flat_map(|x| (0..24).map(move |y| Address::new(1, Coord::new(x, y))))
+            .map(DungeonPath::from)
+            .find(|path| runtime.dungeon.is_downstair(path))
+            .unwrap();
+        runtime.player.pos = stair;
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        assert!(runtime.history(&runtime.player_status()).is_some());
+        let saved = runtime.save().unwrap();
+        let loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), runtime.state_hash());
+    }
+}
@@ -0,0 +1,417 @@
+//! hand-authored levels loaded from ASCII files
+use super::rogue::{self, floor::Floor, Address, FloorEnv, FloorGen, Surface};
+use crate::character::enemies::Preset;
+use crate::dungeon::{Cell, Coord, DungeonSnapshot, Field, TrapKind, X, Y};
+use crate::item::InitItem;
+use crate::rng::RngHandle;
+use crate::tile::{Drawable, Tile};
+use crate::{error::*, GlobalConfig};
+use anyhow::{bail, Context};
+use enum_iterator::IntoEnumIterator;
+use rect_iter::{Get2D, GetMut2D};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    /// paths to the map files, one file per level from the top.
+    /// A map is drawn with the same characters as the dungeon on the screen(`-|.#+%^`),
+    /// and its first line is placed just below the message line.
+    /// A JSON file with the same stem(e.g. `level1.json` for `level1.txt`) places
+    /// the player, enemies, items and traps, if it exists.
+    pub levels: Vec<String>,
+}
+
+/// contents of a JSON sidecar file,
+/// where coordinates are columns and lines in the map file counted from 0
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Sidecar {
+    /// where the player starts, or a random place if not specified
+    #[serde(default)]
+    pub player: Option<Coord>,
+    #[serde(default)]
+    pub enemies: Vec<EnemyPlacement>,
+    #[serde(default)]
+    pub items: Vec<ItemPlacement>,
+    /// kinds of traps drawn as `^`, which are chosen randomly if not specified
+    #[serde(default)]
+    pub traps: Vec<TrapPlacement>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct EnemyPlacement {
+    pub place: Coord,
+    pub enemy: Preset,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ItemPlacement {
+    pub place: Coord,
+    pub item: InitItem,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TrapPlacement {
+    pub place: Coord,
+    pub kind: TrapKind,
+}
+
+/// surfaces which can be drawn in map files
+const SURFACES: [Surface; 8] = [
+    Surface::Passage,
+    Surface::Floor,
+    Surface::WallX,
+    Surface::WallY,
+    Surface::Stair,
+    Surface::Door,
+    Surface::Trap,
+    Surface::None,
+];
+
+/// a level loaded from files, where all coordinates are already on the screen
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Level {
+    field: Field<Surface>,
+    sidecar: Sidecar,
+}
+
+impl Level {
+    fn load(path: &str, config_global: &GlobalConfig, is_last: bool) -> GameResult<Self> {
+        let read = |path: &str| {
+            crate::read_file(path).map_err(|e| {
+                ErrorKind::InvalidSetting(format!("cannot read {}: {}", path, e).into())
+            })
+        };
+        let field = parse_map(&read(path)?, config_global.width, config_global.height)?;
+        let sidecar_path = Path::new(path).with_extension("json");
+        let mut sidecar: Sidecar = if sidecar_path.exists() {
+            let sidecar_path = sidecar_path.to_string_lossy();
+            serde_json::from_str(&read(&sidecar_path)?).map_err(|e| {
+                ErrorKind::InvalidSetting(format!("invalid sidecar {}: {}", sidecar_path, e).into())
+            })?
+        } else {
+            Sidecar::default()
+        };
+        // map files start from the line just below the message line
+        let to_screen = |cd: &mut Coord| *cd = cd.slide_y(1);
+        sidecar.player.iter_mut().for_each(to_screen);
+        sidecar
+            .enemies
+            .iter_mut()
+            .for_each(|e| to_screen(&mut e.place));
+        sidecar
+            .items
+            .iter_mut()
+            .for_each(|i| to_screen(&mut i.place));
+        sidecar
+            .traps
+            .iter_mut()
+            .for_each(|t| to_screen(&mut t.place));
+        let level = Level { field, sidecar };
+        level.validate(is_last)?;
+        Ok(level)
+    }
+
+    fn validate(&self, is_last: bool) -> GameResult<()> {
+        let surface = |cd: Coord| self.field.try_get_p(cd).ok().map(|cell| cell.surface);
+        let is_open = |cd: Coord| surface(cd).is_some_and(|s| s.is_open());
+        let invalid = |what: &str, cd: Coord| -> anyhow::Error {
+            ErrorKind::InvalidSetting(
+                format!("{} at ({}, {}) is not on a floor", what, cd.x.0, cd.y.0 - 1).into(),
+            )
+            .into()
+        };
+        let mut characters = HashSet::new();
+        if let Some(cd) = self.sidecar.player {
+            if !is_open(cd) {
+                return Err(invalid("the player", cd));
+            }
+            characters.insert(cd);
+        }
+        for enemy in &self.sidecar.enemies {
+            if !is_open(enemy.place) || !characters.insert(enemy.place) {
+                return Err(invalid("an enemy", enemy.place));
+            }
+        }
+        let mut items = HashSet::new();
+        for item in &self.sidecar.items {
+            if !is_open(item.place) || !items.insert(item.place) {
+                return Err(invalid("an item", item.place));
+            }
+        }
+        for trap in &self.sidecar.traps {
+            if surface(trap.place) != Some(Surface::Trap) {
+                return Err(invalid("a trap", trap.place));
+            }
+            if is_last && trap.kind == TrapKind::TrapDoor {
+                bail!(ErrorKind::InvalidSetting(
+                    "the last level can't have a trap door".into()
+                ));
+            }
+        }
+        let has_stair = self
+            .field
+            .size()
+            .into_iter()
+            .any(|cd| self.field.get_p(cd).surface == Surface::Stair);
+        if !is_last && !has_stair {
+            bail!(ErrorKind::InvalidSetting(
+                "levels except the last one need a stair".into()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// converts a map file into a field, leaving the message line and the status line empty
+fn parse_map(map: &str, width: X, height: Y) -> GameResult<Field<Surface>> {
+    let mut field = Field::new(width, height, Cell::with_default_attr(Surface::None));
+    let lines: Vec<_> = map.lines().collect();
+    if lines.len() as i32 > height.0 - 2 {
+        bail!(ErrorKind::InvalidSetting(
+            format!(
+                "map has {} lines but only {} can be shown",
+                lines.len(),
+                height.0 - 2
+            )
+            .into()
+        ));
+    }
+    for (y, line) in lines.into_iter().enumerate() {
+        if line.len() as i32 > width.0 {
+            bail!(ErrorKind::InvalidSetting(
+                format!("line {} of map is wider than {}", y, width.0).into()
+            ));
+        }
+        for (x, c) in line.bytes().enumerate() {
+            let surface = SURFACES
+                .iter()
+                .cloned()
+                .find(|s| s.tile() == Tile(c))
+                .ok_or_else(|| {
+                    ErrorKind::InvalidSetting(
+                        format!("unknown character {:?} in map", c as char).into(),
+                    )
+                })?;
+            let cd = Coord::new(x as i32, y as i32 + 1);
+            field.get_mut_p(cd).surface = surface;
+        }
+    }
+    Ok(field)
+}
+
+impl Config {
+    /// reads all map files, so that the dungeon doesn't depend on files after it's made
+    pub(super) fn load(&self, config_global: &GlobalConfig) -> GameResult<Levels> {
+        if self.levels.is_empty() {
+            bail!(ErrorKind::InvalidSetting("no custom levels".into()));
+        }
+        let last = self.levels.len() - 1;
+        let levels: Vec<_> = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                Level::load(path, config_global, i == last)
+                    .with_context(|| format!("custom::Config::load {}", path))
+            })
+            .collect::<GameResult<_>>()?;
+        let base = rogue::Config {
+            amulet_level: levels.len() as u32,
+            ..Default::default()
+        };
+        Ok(Levels { levels, base })
+    }
+}
+
+/// loaded levels
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Levels {
+    levels: Vec<Level>,
+    /// settings shared with rogue, where the amulet is on the last level
+    base: rogue::Config,
+}
+
+impl Levels {
+    fn get(&self, level: u32) -> GameResult<&Level> {
+        let idx = (level as usize).wrapping_sub(1);
+        self.levels
+            .get(idx)
+            .ok_or_else(|| ErrorKind::MaybeBug("[custom::Levels] no level").into())
+    }
+}
+
+/// custom dungeon
+pub type Dungeon = rogue::Dungeon<Levels>;
+
+impl FloorGen for Levels {
+    fn base(&self) -> &rogue::Config {
+        &self.base
+    }
+    /// builds a floor as it's written, with traps whose kinds aren't specified chosen randomly
+    fn gen_floor(
+        &self,
+        level: u32,
+        _width: X,
+        _height: Y,
+        rng: &mut RngHandle,
+    ) -> GameResult<Floor> {
+        let Level { field, sidecar } = self.get(level)?;
+        let mut floor = Floor::open(field.clone());
+        let is_last = !self.has_lower_level(level);
+        for cd in field.size().into_iter().map(Coord::from) {
+            if field.get_p(cd).surface != Surface::Trap {
+                continue;
+            }
+            let kind = match sidecar.traps.iter().find(|t| t.place == cd) {
+                Some(t) => t.kind,
+                None => {
+                    let kinds: Vec<_> = TrapKind::into_enum_iter()
+                        .filter(|&k| !is_last || k != TrapKind::TrapDoor)
+                        .collect();
+                    kinds[rng.range(0..kinds.len())]
+                }
+            };
+            floor.field.get_mut_p(cd).visible(true);
+            floor.traps.insert(cd, kind);
+        }
+        Ok(floor)
+    }
+    /// stairs are drawn in map files
+    fn num_stairs(&self) -> usize {
+        0
+    }
+    fn has_lower_level(&self, level: u32) -> bool {
+        (level as usize) < self.levels.len()
+    }
+    fn player_start(&self, level: u32) -> Option<Coord> {
+        self.get(level).ok()?.sidecar.player
+    }
+    /// places only what the sidecar specifies, so gold and the amulet appear only if it places them
+    fn setup_floor(&self, floor: &mut Floor, env: FloorEnv<'_>) -> GameResult<()> {
+        const ERR_STR: &str = "in custom::Levels::setup_floor";
+        let sidecar = &self.get(env.level)?.sidecar;
+        for e in &sidecar.enemies {
+            let id = env.enemies.gen_enemy_by(e.enemy.clone());
+            env.enemies
+                .place(Address::new(env.level, e.place).into(), id);
+            floor.set_obj(e.place, true);
+        }
+        for i in &sidecar.items {
+            let item = i.item.clone().initialize(env.item).context(ERR_STR)?;
+            if floor.put_item(i.place, item).is_err() {
+                bail!(ErrorKind::MaybeBug(
+                    "[custom::Levels::setup_floor] failed to put an item"
+                ));
+            }
+        }
+        Ok(())
+    }
+    fn snapshot(dungeon: &Dungeon) -> DungeonSnapshot {
+        DungeonSnapshot::Custom(dungeon.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dungeon::DungeonPath;
+    use crate::input::Key;
+    use crate::{GameConfig, RunTime};
+    use std::fs;
+    use std::path::PathBuf;
+
+    const LEVEL1: &str = "\
+----------
+|........|
+|...^....+###
+|........|  #
+----------  #
+       -----+-
+       |..%..|
+       -------";
+
+    const SIDECAR1: &str = r#"
+{
+    "player": {"x": 2, "y": 1},
+    "enemies": [{"place": {"x": 7, "y": 3}, "enemy": 1}],
+    "items": [{"place": {"x": 3, "y": 1}, "item": {"Weapon": {"name": "mace", "num_plus": 1, "hit_plus": 1, "dam_plus": 1}}}],
+    "traps": [{"place": {"x": 4, "y": 2}, "kind": "Teleport"}]
+}
+"#;
+
+    const LEVEL2: &str = "\
+-----
+|.%.|
+-----";
+
+    fn write_files(name: &str, files: &[(&str, &str)]) -> Vec<String> {
+        let dir: PathBuf = std::env::temp_dir().join(format!("rogue-gym-custom-{}", name));
+        fs::create_dir_all(&dir).unwrap();
+        files
+            .iter()
+            .map(|(file, content)| {
+                let path = dir.join(file);
+                fs::write(&path, content).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect()
+    }
+
+    fn config(levels: &[String]) -> String {
+        format!(
+            r#"{{"dungeon": {{"style": "custom", "levels": {}}}}}"#,
+            serde_json::to_string(levels).unwrap()
+        )
+    }
+
+    #[test]
+    fn play_custom() {
+        let paths = write_files(
+            "play",
+            &[("1.txt", LEVEL1), ("1.json", SIDECAR1), ("2.txt", LEVEL2)],
+        );
+        let levels = [paths[0].clone(), paths[2].clone()];
+        let mut runtime = GameConfig::from_json(&config(&levels))
+            .unwrap()
+            .build()
+            .unwrap();
+        let path = |level, x, y| DungeonPath::from(Address::new(level, Coord::new(x, y)));
+        assert_eq!(runtime.player.pos, path(1, 2, 2));
+        assert!(runtime.dungeon.get_item(&path(1, 3, 2)).is_some());
+        assert!(runtime.enemies.get_enemy(&path(1, 7, 4)).is_some());
+        assert_eq!(
+            runtime.dungeon.step_on_trap(&path(1, 4, 3)),
+            Some(TrapKind::Teleport)
+        );
+        assert!(runtime.dungeon.is_downstair(&path(1, 10, 7)));
+        runtime.player.pos = path(1, 10, 7);
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        // the last level has no lower level
+        assert!(!runtime.dungeon.is_downstair(&path(2, 2, 2)));
+        assert!(runtime.dungeon.is_upstair(&path(2, 2, 2)));
+        let saved = runtime.save().unwrap();
+        let loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), runtime.state_hash());
+    }
+
+    #[test]
+    fn invalid_maps() {
+        let paths = write_files(
+            "invalid",
+            &[
+                ("glyph.txt", "|.@.|"),
+                ("nostair.txt", "|...|"),
+                ("wall.txt", "|...|"),
+                ("wall.json", r#"{"player": {"x": 0, "y": 0}}"#),
+            ],
+        );
+        let build = |levels: &[String]| GameConfig::from_json(&config(levels)).unwrap().build();
+        assert!(build(&[paths[0].clone()]).is_err());
+        assert!(build(&[paths[1].clone(), paths[1].clone()]).is_err());
+        assert!(build(&[paths[2].clone()]).is_err());
+        assert!(build(&[paths[1].clone()]).is_ok());
+        assert!(build(&["no-such-level.txt".to_owned()]).is_err());
+    }
+}
@@ -1,10 +1,15 @@
 //! module for making and managing dungeon
+mod cave;
 mod coord;
+mod custom;
 mod field;
+mod nethack;
 mod rogue;
+mod trap;
 
 pub use self::coord::{Coord, Direction, Positioned, X, Y};
 pub use self::field::{Cell, CellAttr, Field};
+pub use self::trap::TrapKind;
 use crate::character::{player::Status as PlayerStatus, EnemyHandler};
 use crate::item::{ItemHandler, ItemToken};
 use crate::{error::*, tile::Tile, GameInfo, GameMsg, GlobalConfig};
@@ -12,6 +17,7 @@ use anyhow::Context;
 use ndarray::Array2;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::hash::Hasher;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(tag = "style")]
@@ -19,12 +25,14 @@ use smallvec::SmallVec;
 pub enum DungeonStyle {
     /// rogue 5.4.4 like dungeon
     Rogue(rogue::Config),
-    /// not implemented now
-    NetHack,
+    /// caves generated by cellular automata
+    Cave(cave::Config),
+    /// NetHack-like dungeon with irregularly placed rooms
+    NetHack(nethack::Config),
     /// not implemented now
     Cataclysm,
-    /// not implemented now
-    Custom,
+    /// hand-authored levels loaded from ASCII files
+    Custom(custom::Config),
 }
 
 impl Default for DungeonStyle {
@@ -55,6 +63,43 @@ impl DungeonStyle {
                 .context("DungeonStyle::build")?;
                 Ok(Box::new(dungeon))
             }
+            DungeonStyle::Cave(config) => {
+                let dungeon = cave::Dungeon::new(
+                    config,
+                    config_global,
+                    game_info,
+                    item_handle,
+                    enemies,
+                    seed,
+                )
+                .context("DungeonStyle::build")?;
+                Ok(Box::new(dungeon))
+            }
+            DungeonStyle::NetHack(config) => {
+                let dungeon = nethack::Dungeon::new(
+                    config,
+                    config_global,
+                    game_info,
+                    item_handle,
+                    enemies,
+                    seed,
+                )
+                .context("DungeonStyle::build")?;
+                Ok(Box::new(dungeon))
+            }
+            DungeonStyle::Custom(config) => {
+                let levels = config.load(config_global).context("DungeonStyle::build")?;
+                let dungeon = custom::Dungeon::new(
+                    levels,
+                    config_global,
+                    game_info,
+                    item_handle,
+                    enemies,
+                    seed,
+                )
+                .context("DungeonStyle::build")?;
+                Ok(Box::new(dungeon))
+            }
             _ => unimplemented!(),
         }
     }
@@ -67,15 +112,25 @@ pub enum MoveResult {
     Reach,
 }
 
-pub trait Dungeon {
+pub trait Dungeon: Send + Sync {
     fn is_downstair(&self, path: &DungeonPath) -> bool;
+    fn is_upstair(&self, path: &DungeonPath) -> bool;
     fn level(&self) -> u32;
+    /// goes down to the next level.
+    /// Returns where the player arrives if the level was visited before,
+    /// or None if the player can be placed anywhere.
     fn new_level(
         &mut self,
         game_info: &GameInfo,
         item: &mut ItemHandler,
         enemies: &mut EnemyHandler,
-    ) -> GameResult<()>;
+    ) -> GameResult<Option<DungeonPath>>;
+    /// goes back to the upper level and returns where the player arrives
+    fn prev_level(&mut self, enemies: &mut EnemyHandler) -> GameResult<DungeonPath>;
+    /// where the player starts in the current level, if it's fixed
+    fn player_start(&self) -> Option<DungeonPath> {
+        None
+    }
     fn can_move_player(&self, path: &DungeonPath, direction: Direction) -> Option<DungeonPath>;
     fn move_player(
         &mut self,
@@ -85,6 +140,33 @@ pub trait Dungeon {
     ) -> GameResult<DungeonPath>;
     fn draw_enemy(&self, player: &DungeonPath, enemy: &DungeonPath) -> bool;
     fn search(&mut self, path: &DungeonPath) -> GameResult<Vec<GameMsg>>;
+    /// springs the trap at the path if any, which is found by the player then
+    fn step_on_trap(&mut self, path: &DungeonPath) -> Option<TrapKind>;
+    /// reveals the structure of the current level
+    fn magic_map(&mut self);
+    /// moves the player to a random place in the current level
+    fn teleport(
+        &mut self,
+        path: &DungeonPath,
+        enemies: &mut EnemyHandler,
+    ) -> GameResult<DungeonPath>;
+    /// creates an enemy next to the player and returns where it is placed
+    fn create_enemy(
+        &mut self,
+        path: &DungeonPath,
+        enemies: &mut EnemyHandler,
+    ) -> Option<DungeonPath>;
+    /// cells a ray from `path` passes through in `length` steps.
+    /// If `bounce` is true the ray is reflected by walls, otherwise it stops there.
+    fn ray(
+        &self,
+        path: &DungeonPath,
+        direction: Direction,
+        length: usize,
+        bounce: bool,
+    ) -> Vec<DungeonPath>;
+    /// lights up the room the player is in, and returns if the player is in a room
+    fn light(&mut self, path: &DungeonPath) -> GameResult<bool>;
     fn select_cell(&mut self, is_character: bool) -> Option<DungeonPath>;
     fn enter_room(&mut self, path: &DungeonPath, enemies: &mut EnemyHandler) -> GameResult<()>;
     fn draw(&self, drawer: &mut dyn FnMut(Positioned<Tile>) -> GameResult<()>) -> GameResult<()>;
@@ -92,6 +174,14 @@ pub trait Dungeon {
     fn path_to_cd(&self, path: &DungeonPath) -> Coord;
     fn get_item(&self, path: &DungeonPath) -> Option<&ItemToken>;
     fn remove_item(&mut self, path: &DungeonPath) -> Option<ItemToken>;
+    /// puts the item on the floor, or gives it back if the item can't be put there
+    fn put_item(&mut self, path: &DungeonPath, item: ItemToken) -> Result<(), ItemToken>;
+    /// all items in the current level
+    fn items(&self) -> Vec<(DungeonPath, &ItemToken)>;
+    /// gold in the same room as the path, which greedy enemies head for
+    fn gold_in_room(&self, path: &DungeonPath) -> Option<DungeonPath>;
+    /// checks if the path is in the current level
+    fn is_current_level(&self, path: &DungeonPath) -> bool;
     fn tile(&mut self, path: &DungeonPath) -> Option<Tile>;
     fn get_history(&self, state: &PlayerStatus) -> Option<Array2<bool>>;
     fn move_enemy(
@@ -106,6 +196,31 @@ pub trait Dungeon {
         player_pos: &DungeonPath,
         skip: &dyn Fn(&DungeonPath) -> bool,
     ) -> MoveResult;
+    /// takes a serializable snapshot of the dungeon
+    fn snapshot(&self) -> DungeonSnapshot;
+    /// feeds the state of the dungeon to the hasher
+    fn hash_state(&self, hasher: &mut dyn Hasher);
+}
+
+/// Serializable representation of a dungeon, keyed by its style
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DungeonSnapshot {
+    Rogue(rogue::Dungeon),
+    Cave(cave::Dungeon),
+    Custom(custom::Dungeon),
+    NetHack(nethack::Dungeon),
+}
+
+impl DungeonSnapshot {
+    pub(crate) fn restore(self) -> Box<dyn Dungeon> {
+        match self {
+            DungeonSnapshot::Rogue(dungeon) => Box::new(dungeon),
+            DungeonSnapshot::Cave(dungeon) => Box::new(dungeon),
+            DungeonSnapshot::Custom(dungeon) => Box::new(dungeon),
+            DungeonSnapshot::NetHack(dungeon) => Box::new(dungeon),
+        }
+    }
 }
 
 type PathVec = SmallVec<[i32; 4]>;
@@ -0,0 +1,626 @@
+//! NetHack-like dungeon, where rooms are placed irregularly and joined by winding corridors
+use super::rogue::floor::{self, Floor};
+use super::rogue::{self, FloorGen, Room, Surface};
+use crate::dungeon::{CellAttr, Coord, Direction, DungeonSnapshot, Field, Positioned, TrapKind};
+use crate::dungeon::{X, Y};
+use crate::{error::*, rng::RngHandle};
+use anyhow::{bail, Context};
+use rect_iter::{Get2D, GetMut2D, RectRange};
+use std::collections::HashSet;
+use tuple_map::TupleMap2;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    /// maximum number of rooms, not including closets
+    #[serde(default = "default_max_rooms")]
+    pub max_rooms: usize,
+    /// maximum size of a room including walls, while the minimum is `min_room_size`
+    #[serde(default = "default_max_room_size")]
+    pub max_room_size: Coord,
+    /// maximum number of closets, which are rooms with only one floor cell
+    #[serde(default = "default_max_closets")]
+    pub max_closets: usize,
+    /// the number of stairs in a level
+    #[serde(default = "default_num_stairs")]
+    pub num_stairs: usize,
+    /// settings shared with rogue, e.g., `min_room_size`, `enable_trap` and `amulet_level`.
+    /// Settings about the layout of rooms are not used.
+    #[serde(flatten)]
+    pub rogue: rogue::Config,
+}
+
+const fn default_max_rooms() -> usize {
+    12
+}
+
+#[inline]
+fn default_max_room_size() -> Coord {
+    Coord::new(15, 7)
+}
+
+const fn default_max_closets() -> usize {
+    2
+}
+
+const fn default_num_stairs() -> usize {
+    2
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_rooms: default_max_rooms(),
+            max_room_size: default_max_room_size(),
+            max_closets: default_max_closets(),
+            num_stairs: default_num_stairs(),
+            rogue: rogue::Config::default(),
+        }
+    }
+}
+
+/// space kept around a room, so that corridors can run between rooms
+const ROOM_MARGIN: (i32, i32) = (2, 1);
+
+/// size of a closet including walls
+const CLOSET_SIZE: i32 = 3;
+
+/// how many times we try to generate a connected floor
+const MAX_TRIES: usize = 100;
+
+/// a corridor is given up after this many steps, as in NetHack
+const MAX_CORRIDOR_STEPS: usize = 500;
+
+/// places rooms and closets in free rectangles of the screen, as `makerooms` in NetHack
+fn place_rooms(config: &Config, width: X, height: Y, rng: &mut RngHandle) -> Vec<RectRange<i32>> {
+    let mut free: Vec<_> = RectRange::from_ranges(0..width.0, 1..height.0 - 1)
+        .into_iter()
+        .collect();
+    let mut rooms = Vec::new();
+    let (min, max) = (config.rogue.min_room_size, config.max_room_size);
+    while rooms.len() < config.max_rooms {
+        let candidates: Vec<_> = free
+            .iter()
+            .filter(|r| r.xlen() >= min.x.0 && r.ylen() >= min.y.0)
+            .collect();
+        if candidates.is_empty() {
+            break;
+        }
+        let rect = candidates[rng.range(0..candidates.len())].clone();
+        let size = (
+            (min.x.0, max.x.0, rect.xlen()),
+            (min.y.0, max.y.0, rect.ylen()),
+        )
+            .map(|(min, max, len)| rng.range(min..=max.min(len)));
+        let room = place_in(&rect, size, rng);
+        carve(&mut free, &room);
+        rooms.push(room);
+    }
+    // closets fill spaces too small for rooms
+    for _ in 0..rng.range(0..=config.max_closets) {
+        if free.is_empty() {
+            break;
+        }
+        let rect = free[rng.range(0..free.len())].clone();
+        let room = place_in(&rect, (CLOSET_SIZE, CLOSET_SIZE), rng);
+        carve(&mut free, &room);
+        rooms.push(room);
+    }
+    rooms
+}
+
+/// places a room of `size` at a random position in `rect`
+fn place_in(rect: &RectRange<i32>, size: (i32, i32), rng: &mut RngHandle) -> RectRange<i32> {
+    let x = rect.get_x().start + rng.range(0..=rect.xlen() - size.0);
+    let y = rect.get_y().start + rng.range(0..=rect.ylen() - size.1);
+    RectRange::from_ranges(x..x + size.0, y..y + size.1).unwrap()
+}
+
+/// removes a room and its margin from free rectangles, as `split_rects` in NetHack
+fn carve(free: &mut Vec<RectRange<i32>>, room: &RectRange<i32>) {
+    let (mx, my) = ROOM_MARGIN;
+    let used = RectRange::from_ranges(
+        room.get_x().start - mx..room.get_x().end + mx,
+        room.get_y().start - my..room.get_y().end + my,
+    )
+    .unwrap();
+    let mut res: Vec<RectRange<i32>> = Vec::new();
+    for rect in free.drain(..) {
+        if !rect.intersects(&used) {
+            res.push(rect);
+            continue;
+        }
+        let (x, y) = (rect.cloned_x(), rect.cloned_y());
+        let (ux, uy) = (used.cloned_x(), used.cloned_y());
+        let pieces = [
+            (x.start..ux.start, y.clone()),
+            (ux.end..x.end, y.clone()),
+            (x.clone(), y.start..uy.start),
+            (x, uy.end..y.end),
+        ];
+        res.extend(
+            pieces
+                .iter()
+                .filter_map(|(x, y)| RectRange::from_ranges(x.clone(), y.clone()))
+                .filter(|r| r.xlen() >= CLOSET_SIZE && r.ylen() >= CLOSET_SIZE),
+        );
+    }
+    // remove rectangles included by others
+    let includes = |r: &RectRange<i32>, s: &RectRange<i32>| r.intersection(s).as_ref() == Some(s);
+    for (i, rect) in res.iter().enumerate() {
+        let included = res
+            .iter()
+            .enumerate()
+            .any(|(j, other)| includes(other, rect) && (other != rect || j < i));
+        if !included {
+            free.push(rect.clone());
+        }
+    }
+}
+
+/// cells of a wall of `room` facing `direction`, except corners
+fn wall(room: &RectRange<i32>, direction: Direction) -> Vec<Coord> {
+    let (x, y) = (room.cloned_x(), room.cloned_y());
+    match direction {
+        Direction::Up => (x.start + 1..x.end - 1)
+            .map(|x| Coord::new(x, y.start))
+            .collect(),
+        Direction::Down => (x.start + 1..x.end - 1)
+            .map(|x| Coord::new(x, y.end - 1))
+            .collect(),
+        Direction::Left => (y.start + 1..y.end - 1)
+            .map(|y| Coord::new(x.start, y))
+            .collect(),
+        Direction::Right => (y.start + 1..y.end - 1)
+            .map(|y| Coord::new(x.end - 1, y))
+            .collect(),
+        _ => unreachable!("[nethack::wall] invalid direction"),
+    }
+}
+
+/// digs corridors between rooms already drawn on the field
+struct Digger<'a> {
+    field: &'a Field<Surface>,
+    rooms: &'a [RectRange<i32>],
+    /// range where corridors can be dug
+    inner: RectRange<i32>,
+    dug: HashSet<Coord>,
+    doors: HashSet<Coord>,
+    /// doors and corridors in the order they're made
+    passages: Vec<Positioned<Surface>>,
+    rng: &'a mut RngHandle,
+}
+
+impl<'a> Digger<'a> {
+    fn is_stone(&self, cd: Coord) -> bool {
+        self.inner.contains(cd)
+            && !self.dug.contains(&cd)
+            && self
+                .field
+                .try_get_p(cd)
+                .is_ok_and(|cell| cell.surface == Surface::None)
+    }
+
+    fn can_dig(&self, cd: Coord) -> bool {
+        self.is_stone(cd) || self.dug.contains(&cd)
+    }
+
+    fn dig(&mut self, cd: Coord) {
+        self.dug.insert(cd);
+        self.passages.push(Positioned(cd, Surface::Passage));
+    }
+
+    /// as `okdoor` in NetHack, a door must be on a wall and not be next to another door
+    fn ok_door(&self, cd: Coord) -> bool {
+        let on_wall = self
+            .field
+            .try_get_p(cd)
+            .is_ok_and(|cell| matches!(cell.surface, Surface::WallX | Surface::WallY));
+        let by_door = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .iter()
+        .any(|d| self.doors.contains(&(cd + d.to_cd())));
+        on_wall && !by_door && !self.doors.contains(&cd)
+    }
+
+    fn add_door(&mut self, cd: Coord) {
+        if self.doors.insert(cd) {
+            self.passages.push(Positioned(cd, Surface::Door));
+        }
+    }
+
+    /// selects a door position from candidates, as `finddpos` in NetHack
+    fn find_door(&mut self, candidates: &[Coord]) -> Coord {
+        let first = candidates[self.rng.range(0..candidates.len())];
+        if self.ok_door(first) {
+            return first;
+        }
+        candidates
+            .iter()
+            .cloned()
+            .find(|&cd| self.ok_door(cd))
+            .unwrap_or(first)
+    }
+
+    /// digs a corridor from `org` to `dest`, as `dig_corridor` in NetHack.
+    /// An extra corridor may stop halfway, leaving a dead end.
+    fn dig_corridor(&mut self, org: Coord, dest: Coord, extra: bool) -> bool {
+        if !self.inner.contains(org) || !self.inner.contains(dest) {
+            return false;
+        }
+        let toward = |from: i32, to: i32| if from > to { -1 } else { 1 };
+        let mut d = if dest.x > org.x {
+            Coord::new(1, 0)
+        } else if dest.y > org.y {
+            Coord::new(0, 1)
+        } else if dest.x < org.x {
+            Coord::new(-1, 0)
+        } else {
+            Coord::new(0, -1)
+        };
+        let mut cur = org - d;
+        for _ in 0..MAX_CORRIDOR_STEPS {
+            if cur == dest {
+                return true;
+            }
+            if extra && self.rng.does_happen(35) {
+                return false;
+            }
+            cur += d;
+            if self.is_stone(cur) {
+                self.dig(cur);
+            } else if !self.dug.contains(&cur) {
+                return false;
+            }
+            let (mut dix, mut diy) = ((cur.x.0 - dest.x.0).abs(), (cur.y.0 - dest.y.0).abs());
+            // sometimes turn earlier, to make corridors winding
+            if dix > diy && diy > 0 && self.rng.range(0..=dix - diy) == 0 {
+                dix = 0;
+            } else if diy > dix && dix > 0 && self.rng.range(0..=diy - dix) == 0 {
+                diy = 0;
+            }
+            if d.y.0 != 0 && dix > diy {
+                let nd = Coord::new(toward(cur.x.0, dest.x.0), 0);
+                if self.can_dig(cur + nd) {
+                    d = nd;
+                    continue;
+                }
+            } else if d.x.0 != 0 && diy > dix {
+                let nd = Coord::new(0, toward(cur.y.0, dest.y.0));
+                if self.can_dig(cur + nd) {
+                    d = nd;
+                    continue;
+                }
+            }
+            if self.can_dig(cur + d) {
+                continue;
+            }
+            // go around obstacles
+            d = if d.x.0 != 0 {
+                Coord::new(0, toward(cur.y.0, dest.y.0))
+            } else {
+                Coord::new(toward(cur.x.0, dest.x.0), 0)
+            };
+            if self.can_dig(cur + d) {
+                continue;
+            }
+            d = Coord::new(-d.x.0, -d.y.0);
+        }
+        cur == dest
+    }
+
+    /// connects 2 rooms by a corridor, as `join` in NetHack
+    fn join(&mut self, a: usize, b: usize, extra: bool) -> bool {
+        let (croom, troom) = (&self.rooms[a], &self.rooms[b]);
+        let direction = if troom.get_x().start >= croom.get_x().end {
+            Direction::Right
+        } else if troom.get_y().end <= croom.get_y().start {
+            Direction::Up
+        } else if troom.get_x().end <= croom.get_x().start {
+            Direction::Left
+        } else {
+            Direction::Down
+        };
+        let (cwall, twall) = (wall(croom, direction), wall(troom, direction.reverse()));
+        let start = self.find_door(&cwall);
+        let end = self.find_door(&twall);
+        let d = direction.to_cd();
+        let (org, dest) = (start + d, end - d);
+        if extra && !self.is_stone(org) {
+            return false;
+        }
+        if !extra || self.ok_door(start) {
+            self.add_door(start);
+        }
+        if !self.dig_corridor(org, dest, extra) {
+            return false;
+        }
+        if !extra || self.ok_door(end) {
+            self.add_door(end);
+        }
+        true
+    }
+
+    /// joins rooms until all rooms are connected, as `makecorridors` in NetHack.
+    /// Returns if all rooms are connected or not.
+    fn make_corridors(&mut self) -> bool {
+        let n = self.rooms.len();
+        // connected components of rooms
+        let mut smeq: Vec<_> = (0..n).collect();
+        let mut join = |digger: &mut Self, a: usize, b: usize| {
+            if smeq[a] == smeq[b] || !digger.join(a, b, false) {
+                return;
+            }
+            let (from, to) = (smeq[a].max(smeq[b]), smeq[a].min(smeq[b]));
+            smeq.iter_mut()
+                .filter(|s| **s == from)
+                .for_each(|s| *s = to);
+        };
+        for a in 0..n.saturating_sub(1) {
+            join(self, a, a + 1);
+            if self.rng.does_happen(50) {
+                break;
+            }
+        }
+        for a in 0..n.saturating_sub(2) {
+            join(self, a, a + 2);
+        }
+        for a in 0..n {
+            for b in 0..n {
+                join(self, a, b);
+            }
+        }
+        let connected = smeq.iter().all(|&s| s == 0);
+        // some extra corridors, which may be dead ends
+        if n > 2 {
+            for _ in 0..self.rng.range(0..n) + 4 {
+                let a = self.rng.range(0..n);
+                let mut b = self.rng.range(0..n - 2);
+                if b >= a {
+                    b += 2;
+                }
+                self.join(a, b, true);
+            }
+        }
+        connected
+    }
+
+    /// digs a niche behind the upper or lower wall of a room, as `makeniche` in NetHack.
+    /// Returns the niche and its door.
+    fn make_niche(&mut self) -> Option<(Coord, Coord)> {
+        for _ in 0..8 {
+            let room = &self.rooms[self.rng.range(0..self.rooms.len())];
+            let direction = if self.rng.does_happen(2) {
+                Direction::Up
+            } else {
+                Direction::Down
+            };
+            let door = self.find_door(&wall(room, direction));
+            let niche = door + direction.to_cd();
+            if !self.ok_door(door) || !self.is_stone(niche) {
+                continue;
+            }
+            self.dig(niche);
+            self.add_door(door);
+            return Some((niche, door));
+        }
+        None
+    }
+
+    /// digs niches, some of which have traps, as `make_niches` in NetHack
+    fn make_niches(
+        &mut self,
+        level: u32,
+        enable_trap: bool,
+    ) -> Vec<(Coord, Coord, Option<TrapKind>)> {
+        let count = self.rng.range(1..=self.rooms.len() / 2 + 1);
+        // teleport traps stand in for level teleporters of NetHack
+        let mut teleport = enable_trap && level > 15;
+        let mut trapdoor = enable_trap && 5 < level && level < 25;
+        let mut res = Vec::new();
+        for _ in 0..count {
+            let trap = if teleport && self.rng.does_happen(6) {
+                teleport = false;
+                Some(TrapKind::Teleport)
+            } else if trapdoor && self.rng.does_happen(6) {
+                trapdoor = false;
+                Some(TrapKind::TrapDoor)
+            } else {
+                None
+            };
+            if let Some((niche, door)) = self.make_niche() {
+                res.push((niche, door, trap));
+            }
+        }
+        res
+    }
+}
+
+impl Config {
+    fn check(&self) -> GameResult<()> {
+        let (min, max) = (self.rogue.min_room_size, self.max_room_size);
+        if min.x.0 < CLOSET_SIZE || min.y.0 < CLOSET_SIZE {
+            bail!(ErrorKind::InvalidSetting(
+                "min_room_size must be 3x3 or larger".into()
+            ));
+        }
+        if max.x < min.x || max.y < min.y {
+            bail!(ErrorKind::InvalidSetting(
+                "max_room_size is smaller than min_room_size".into()
+            ));
+        }
+        if self.num_stairs == 0 {
+            bail!(ErrorKind::InvalidSetting(
+                "num_stairs must be positive".into()
+            ));
+        }
+        Ok(())
+    }
+
+    /// generates a floor, or returns None if rooms aren't connected
+    fn try_gen_floor(
+        &self,
+        level: u32,
+        width: X,
+        height: Y,
+        rng: &mut RngHandle,
+    ) -> GameResult<Option<Floor>> {
+        let mut ranges = place_rooms(self, width, height, rng);
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        // as `sort_rooms` in NetHack, neighbors in the list are joined first
+        ranges.sort_by_key(|range| range.get_x().start);
+        let rooms: Vec<_> = ranges
+            .iter()
+            .enumerate()
+            .map(|(id, range)| {
+                let is_dark = rng.range(0..self.rogue.dark_level) < level;
+                Room::normal(range.clone(), is_dark, id)
+            })
+            .collect();
+        let mut field = floor::draw_rooms(&rooms, level, &self.rogue, width, height, rng)?;
+        let inner = RectRange::from_ranges(1..width.0 - 1, 2..height.0 - 2)
+            .ok_or(ErrorKind::MaybeBug("[nethack::Config] too small dungeon"))?;
+        let (passages, niches) = {
+            let mut digger = Digger {
+                field: &field,
+                rooms: &ranges,
+                inner,
+                dug: HashSet::new(),
+                doors: HashSet::new(),
+                passages: Vec::new(),
+                rng,
+            };
+            if !digger.make_corridors() {
+                return Ok(None);
+            }
+            let niches = digger.make_niches(level, self.rogue.enable_trap);
+            (digger.passages, niches)
+        };
+        let doors = floor::draw_passages(&mut field, passages, level, &self.rogue, rng)?;
+        // as in NetHack, most niches are behind secret doors
+        for &(_, door, _) in &niches {
+            if !rng.does_happen(5) {
+                let cell = field.get_mut_p(door);
+                cell.attr |= CellAttr::IS_LOCKED;
+                cell.surface = Surface::WallX;
+            }
+        }
+        let mut floor = Floor::new(rooms, doors, field);
+        let traps = niches
+            .into_iter()
+            .filter_map(|(niche, _, trap)| Some((niche, trap?)));
+        floor.traps.extend(traps);
+        Ok(Some(floor))
+    }
+}
+
+/// NetHack-like dungeon
+pub type Dungeon = rogue::Dungeon<Config>;
+
+impl FloorGen for Config {
+    fn base(&self) -> &rogue::Config {
+        &self.rogue
+    }
+    fn gen_floor(&self, level: u32, width: X, height: Y, rng: &mut RngHandle) -> GameResult<Floor> {
+        const ERR_STR: &str = "in nethack::Config::gen_floor";
+        self.check().context(ERR_STR)?;
+        for _ in 0..MAX_TRIES {
+            if let Some(floor) = self
+                .try_gen_floor(level, width, height, rng)
+                .context(ERR_STR)?
+            {
+                return Ok(floor);
+            }
+        }
+        bail!(ErrorKind::MaybeBug(
+            "[nethack::Config::gen_floor] failed to connect rooms"
+        ))
+    }
+    fn num_stairs(&self) -> usize {
+        self.num_stairs
+    }
+    fn snapshot(dungeon: &Dungeon) -> DungeonSnapshot {
+        DungeonSnapshot::NetHack(dungeon.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dungeon::{rogue::Address, DungeonPath};
+    use crate::input::Key;
+    use crate::{GameConfig, RunTime};
+    #[test]
+    #[ignore]
+    fn print_nethack() {
+        let mut rng = RngHandle::new();
+        let floor = Config::default()
+            .gen_floor(10, X(80), Y(24), &mut rng)
+            .unwrap();
+        println!("{}", floor.field);
+    }
+    #[test]
+    fn connected() {
+        let mut rng = RngHandle::new();
+        let config = Config::default();
+        for level in 1..=20 {
+            let mut floor = config.gen_floor(level, X(80), Y(24), &mut rng).unwrap();
+            for (i, a) in floor.rooms.iter().enumerate() {
+                for b in &floor.rooms[i + 1..] {
+                    assert!(!a.assigned_area.intersects(&b.assigned_area));
+                }
+            }
+            // reveal secret doors and passages as searching does
+            for cd in floor.field.size() {
+                let cell = floor.field.get_mut_p(cd);
+                if cell.is_hidden() {
+                    cell.surface = Surface::Passage;
+                } else if cell.is_locked() {
+                    cell.surface = Surface::Door;
+                }
+            }
+            // all floor cells are reachable
+            let start = floor.rooms[0].select_cell(&mut rng, false).unwrap();
+            let dist = floor.make_dist_map(start, true);
+            for cd in floor.field.size() {
+                if floor.field.get_p(cd).surface == Surface::Floor {
+                    assert_ne!(*dist.get_p(cd), u32::MAX);
+                }
+            }
+        }
+    }
+    #[test]
+    fn play_nethack() {
+        const CONFIG: &str = r#"
+{
+    "seed": 1,
+    "dungeon": {
+        "style": "nethack",
+        "num_stairs": 3,
+        "enable_trap": false
+    }
+}
+"#;
+        let mut runtime = GameConfig::from_json(CONFIG).unwrap().build().unwrap();
+        for c in "hjklyubn".chars() {
+            runtime.react_to_key(Key::Char(c)).unwrap();
+        }
+        let stairs: Vec<_> = (0..80)
+            .flat_map(|x| (0..24).map(move |y| Address::new(1, Coord::new(x, y))))
+            .map(DungeonPath::from)
+            .filter(|path| runtime.dungeon.is_downstair(path))
+            .collect();
+        assert_eq!(stairs.len(), 3);
+        runtime.player.pos = stairs[0].clone();
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        let saved = runtime.save().unwrap();
+        let loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), runtime.state_hash());
+    }
+}
@@ -0,0 +1,96 @@
+//! binary space partitioning of a floor into areas assigned to rooms
+use crate::dungeon::Coord;
+use crate::rng::RngHandle;
+use rect_iter::RectRange;
+use tuple_map::TupleMap2;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    /// minimum number of rooms, which can't be satisfied if the floor is too small
+    #[serde(default = "default_min_rooms")]
+    pub min_rooms: usize,
+    /// maximum number of rooms
+    #[serde(default = "default_max_rooms")]
+    pub max_rooms: usize,
+    /// maximum size of a room including walls, while the minimum is `min_room_size`
+    #[serde(default = "default_max_room_size")]
+    pub max_room_size: Coord,
+}
+
+const fn default_min_rooms() -> usize {
+    5
+}
+
+const fn default_max_rooms() -> usize {
+    10
+}
+
+#[inline]
+fn default_max_room_size() -> Coord {
+    Coord::new(20, 10)
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            min_rooms: default_min_rooms(),
+            max_rooms: default_max_rooms(),
+            max_room_size: default_max_room_size(),
+        }
+    }
+}
+
+/// splits `area` recursively into `num` areas or fewer, none of which is smaller than `min_size`
+pub(super) fn partition(
+    area: RectRange<i32>,
+    num: usize,
+    min_size: Coord,
+    rng: &mut RngHandle,
+) -> Vec<RectRange<i32>> {
+    if num <= 1 {
+        return vec![area];
+    }
+    let (xlen, ylen) = (area.xlen(), area.ylen());
+    let (xmin, ymin) = (min_size.x.0, min_size.y.0);
+    let split_x = match (xlen >= xmin * 2, ylen >= ymin * 2) {
+        (false, false) => return vec![area],
+        (true, false) => true,
+        (false, true) => false,
+        // the longer side relative to the minimum size is likely to be split
+        (true, true) => rng.range(0..xlen * ymin + ylen * xmin) < xlen * ymin,
+    };
+    let (x, y) = (area.cloned_x(), area.cloned_y());
+    let (a, b) = if split_x {
+        let mid = x.start + rng.range(xmin..=xlen - xmin);
+        ((x.start..mid, y.clone()), (mid..x.end, y))
+    } else {
+        let mid = y.start + rng.range(ymin..=ylen - ymin);
+        ((x.clone(), y.start..mid), (x, mid..y.end))
+    };
+    let (a, b) = (a, b).map(|(x, y)| RectRange::from_ranges(x, y).unwrap());
+    // divide the number of rooms in proportion to areas
+    let num_a = (num as i32 * a.area() + area.area() / 2) / area.area();
+    let num_a = (num_a.max(1) as usize).min(num - 1);
+    let mut res = partition(a, num_a, min_size, rng);
+    res.extend(partition(b, num - num_a, min_size, rng));
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn partition_areas() {
+        let mut rng = RngHandle::new();
+        let area = RectRange::from_ranges(0..80, 1..23).unwrap();
+        for num in 1..20 {
+            let areas = partition(area.clone(), num, Coord::new(5, 5), &mut rng);
+            assert!(areas.len() <= num);
+            assert_eq!(areas.iter().map(|a| a.area()).sum::<i32>(), area.area());
+            for (i, a) in areas.iter().enumerate() {
+                assert!(a.xlen() >= 5 && a.ylen() >= 5);
+                assert!(areas[i + 1..].iter().all(|b| !a.intersects(b)));
+            }
+        }
+    }
+}
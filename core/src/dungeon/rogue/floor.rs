@@ -1,32 +1,87 @@
 //! rogue floor
 use super::{passages, rooms, Address, Config, Room, Surface};
-use crate::dungeon::{Cell, CellAttr, Coord, Direction, Field, Positioned, X, Y};
+use crate::dungeon::{Cell, CellAttr, Coord, Direction, Field, Positioned, TrapKind, X, Y};
 use crate::enemies::EnemyHandler;
-use crate::item::{ItemHandler, ItemToken};
-use crate::{error::*, fenwick::FenwickSet, rng::RngHandle, GameMsg};
+use crate::item::{ItemHandler, ItemKind, ItemToken};
+use crate::rng::{Parcent, RngHandle};
+use crate::{error::*, fenwick::FenwickSet, GameMsg};
 use anyhow::{bail, Context};
 use enum_iterator::IntoEnumIterator;
 use ndarray::Array2;
-use rect_iter::{Get2D, GetMut2D};
+use rect_iter::{Get2D, GetMut2D, RectRange};
+use serde::Serializer;
 use std::collections::{HashMap, HashSet, VecDeque};
 
+/// maximum number of items put on a floor
+const MAX_OBJECTS: usize = 9;
+
+/// maximum number of traps put on a floor
+const MAX_TRAPS: u32 = 10;
+
+/// a floor without rooms is split into these numbers of areas in X and Y axis,
+/// where gold and enemies are put as in rooms
+const OPEN_AREAS: (i32, i32) = (3, 2);
+
+/// greedy enemies in a floor without rooms head for gold within this distance
+const OPEN_GOLD_DIST: i32 = 8;
+
 /// representation of 'floor'
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Floor {
     /// rooms
     pub rooms: Vec<Room>,
     /// Coordinates of doors
+    #[serde(serialize_with = "serialize_doors")]
     pub doors: HashSet<Coord>,
     /// field (level map)
     pub field: Field<Surface>,
     /// ids of rooms which are not empty
     pub non_empty_rooms: FenwickSet,
     /// items
+    #[serde(with = "coord_map")]
     pub items: HashMap<Coord, ItemToken>,
+    /// traps, which are drawn only after found
+    #[serde(default, with = "coord_map")]
+    pub traps: HashMap<Coord, TrapKind>,
+}
+
+/// serializes doors in sorted order, to make the output deterministic
+fn serialize_doors<S>(doors: &HashSet<Coord>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut doors: Vec<_> = doors.iter().collect();
+    doors.sort();
+    serializer.collect_seq(doors)
+}
+
+/// (de)serializes maps keyed by Coord as sorted sequences, because json doesn't allow Coord as a key
+pub(crate) mod coord_map {
+    use crate::dungeon::Coord;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    pub(crate) fn serialize<S, T>(map: &HashMap<Coord, T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let mut items: Vec<_> = map.iter().collect();
+        items.sort_by_key(|&(cd, _)| cd);
+        serializer.collect_seq(items)
+    }
+    pub(crate) fn deserialize<'de, D, T>(deserializer: D) -> Result<HashMap<Coord, T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let seq = Vec::<(Coord, T)>::deserialize(deserializer)?;
+        Ok(seq.into_iter().collect())
+    }
 }
 
 impl Floor {
-    fn new(rooms: Vec<Room>, doors: HashSet<Coord>, field: Field<Surface>) -> Self {
+    /// makes a floor from rooms and passages already drawn on the field
+    pub(crate) fn new(rooms: Vec<Room>, doors: HashSet<Coord>, field: Field<Surface>) -> Self {
         let non_empty_rooms =
             rooms
                 .iter()
@@ -42,11 +97,30 @@ impl Floor {
             field,
             non_empty_rooms,
             items: Default::default(),
+            traps: Default::default(),
         }
     }
 
-    /// generate a new floor without items
-    // TODO: trap
+    /// makes a floor without rooms, as caves, from a field already drawn
+    pub(crate) fn open(field: Field<Surface>) -> Self {
+        let (width, height) = (field.width().0, field.height().0);
+        let (nx, ny) = OPEN_AREAS;
+        // leave the message line and the status line, as rooms do
+        let x = |i: i32| width * i / nx;
+        let y = |i: i32| 1 + (height - 2) * i / ny;
+        let rooms = RectRange::zero_start(nx, ny)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(id, (i, j))| {
+                let area = RectRange::from_ranges(x(i)..x(i + 1), y(j)..y(j + 1)).unwrap();
+                Room::open(area, id, |cd| field.get_p(cd).surface.is_open())
+            })
+            .collect();
+        Floor::new(rooms, HashSet::new(), field)
+    }
+
+    /// generate a new floor without items and traps
     pub fn gen_floor(
         level: u32,
         config: &Config,
@@ -54,52 +128,16 @@ impl Floor {
         height: Y,
         rng: &mut RngHandle,
     ) -> GameResult<Self> {
-        let rooms =
+        let (rooms, graph) =
             rooms::gen_rooms(level, config, width, height, rng).context("Error in gen_floor")?;
-        let mut field = Field::new(width, height, Cell::with_default_attr(Surface::None));
-        // in this phase, we can draw surfaces 'as is'
-        rooms.iter().try_for_each(|room| {
-            room.draw(|Positioned(cd, surface)| {
-                field
-                    .try_get_mut_p(cd)
-                    .map(|mut_cell| {
-                        mut_cell.surface = surface;
-                        mut_cell.attr = gen_attr(surface, room.is_dark, rng, level, config);
-                    })
-                    .context("Error in gen_floor")
-            })
-        })?;
+        let mut field = draw_rooms(&rooms, level, config, width, height, rng)?;
         // sometimes door is hidden randomly so first we store positions to avoid borrow restriction
         let mut passages = Vec::new();
-        passages::dig_passges(
-            &rooms,
-            config.room_num_x,
-            config.room_num_y,
-            rng,
-            config.max_extra_edges,
-            |p| {
-                passages.push(p);
-                Ok(())
-            },
-        )?;
-        let mut doors = HashSet::new();
-        passages
-            .into_iter()
-            .try_for_each(|Positioned(cd, surface)| {
-                if surface == Surface::Door {
-                    doors.insert(cd);
-                }
-                field
-                    .try_get_mut_p(cd)
-                    .map(|cell| {
-                        cell.attr = gen_attr(surface, false, rng, level, config);
-                        // if the passage is not hiddden, let's draw
-                        if !cell.is_hidden() && !cell.is_locked() {
-                            cell.surface = surface;
-                        }
-                    })
-                    .context("Floor::new dig_passges returned invalid index")
-            })?;
+        passages::dig_passges(&rooms, graph, rng, config.max_extra_edges, |p| {
+            passages.push(p);
+            Ok(())
+        })?;
+        let doors = draw_passages(&mut field, passages, level, config, rng)?;
         Ok(Floor::new(rooms, doors, field))
     }
     /// place enemies
@@ -134,6 +172,7 @@ impl Floor {
         level: u32,
         item_handle: &mut ItemHandler,
         set_gold: bool,
+        set_amulet: bool,
         rng: &mut RngHandle,
     ) {
         // setup gold
@@ -150,6 +189,58 @@ impl Floor {
                 }
             }
         }
+        // as in rogue, try MAXOBJ(9) times to put an item with a probability of 36%
+        for _ in 0..MAX_OBJECTS {
+            if !rng.parcent(Parcent(36)) {
+                continue;
+            }
+            if let Some(cd) = self.select_cell(rng, false) {
+                self.set_obj(cd, false);
+                self.items.insert(cd, item_handle.gen_floor_item());
+            }
+        }
+        // setup the Amulet of Yendor
+        if set_amulet {
+            if let Some(cd) = self.select_cell(rng, false) {
+                self.set_obj(cd, false);
+                self.items.insert(cd, item_handle.gen_amulet());
+            }
+        }
+    }
+
+    /// setup hidden traps for a floor
+    pub fn setup_traps(&mut self, level: u32, rng: &mut RngHandle) {
+        // as in rogue, traps appear with a probability of level / 10
+        if rng.range(0..10) >= level {
+            return;
+        }
+        let num = (rng.range(0..(level / 4).max(1)) + 1).min(MAX_TRAPS);
+        for _ in 0..num {
+            // try some times to find an empty floor, as `find_floor` in rogue
+            let cd = (0..100).find_map(|_| {
+                let cd = self.select_cell(rng, false)?;
+                let is_floor = self.field.try_get_p(cd).ok()?.surface == Surface::Floor;
+                if is_floor && !self.items.contains_key(&cd) && !self.traps.contains_key(&cd) {
+                    Some(cd)
+                } else {
+                    None
+                }
+            });
+            if let Some(cd) = cd {
+                let idx = rng.range(0..TrapKind::VARIANT_COUNT);
+                let kind = TrapKind::into_enum_iter().nth(idx).unwrap();
+                self.traps.insert(cd, kind);
+            }
+        }
+    }
+
+    /// returns the trap on the cell and marks it as found
+    pub(super) fn find_trap(&mut self, cd: Coord) -> Option<TrapKind> {
+        let kind = *self.traps.get(&cd)?;
+        let cell = self.field.try_get_mut_p(cd).ok()?;
+        cell.surface = Surface::Trap;
+        cell.visible(true);
+        Some(kind)
     }
 
     /// set stair
@@ -166,6 +257,15 @@ impl Floor {
         Ok(())
     }
 
+    /// returns the coordinate of the stair
+    pub(super) fn stair(&self) -> Option<Coord> {
+        self.field
+            .size()
+            .into_iter()
+            .map(Coord::from)
+            .find(|&cd| self.field.get_p(cd).surface == Surface::Stair)
+    }
+
     fn can_move_impl(&self, cd: Coord, direction: Direction, is_enemy: bool) -> Option<bool> {
         let cell = |cd: Coord| self.field.try_get_p(cd).ok();
         let nxt = cell(cd + direction.to_cd())?;
@@ -263,18 +363,25 @@ impl Floor {
     /// player walks in the cell
     pub(super) fn player_in(
         &mut self,
-        cd: Coord,
+        address: Address,
         init: bool,
         enemies: &mut EnemyHandler,
     ) -> GameResult<()> {
+        let cd = address.cd;
         debug!("[Floor::player_in] cd: {:?}", cd);
-        if init || self.doors.contains(&cd) {
+        if self.is_in_open_area(cd) {
+            // as in dark places of rogue, enemies wake up when the player comes next to them
+            enemies.activate_area(|p| {
+                let addr = Address::from_path(p);
+                addr.level == address.level && addr.cd.is_adjacent(cd)
+            });
+        } else if init || self.doors.contains(&cd) {
             self.enters_room(cd).context("Floor::player_in")?;
             if let Some(room_id) = self.cd_to_room_id(cd) {
                 let room = &self.rooms[room_id];
                 enemies.activate_area(|p| {
-                    let cd = Address::from_path(p).cd;
-                    room.assigned_area.contains(cd)
+                    let addr = Address::from_path(p);
+                    addr.level == address.level && room.assigned_area.contains(addr.cd)
                 });
             }
         }
@@ -312,7 +419,7 @@ impl Floor {
     }
 
     /// register an object to cell
-    pub(super) fn set_obj(&mut self, cd: Coord, is_character: bool) -> bool {
+    pub(crate) fn set_obj(&mut self, cd: Coord, is_character: bool) -> bool {
         let mut impl_ = || {
             let room = self.rooms.iter_mut().find(|room| room.contains(cd))?;
             Some(room.fill_cell(cd, is_character))
@@ -329,8 +436,23 @@ impl Floor {
         impl_() == Some(true)
     }
 
+    /// puts an item on the cell, as `drop` in rogue
+    pub(crate) fn put_item(&mut self, cd: Coord, item: ItemToken) -> Result<(), ItemToken> {
+        let can_put = self
+            .field
+            .try_get_p(cd)
+            .ok()
+            .is_some_and(|cell| cell.surface.is_open());
+        if !can_put || self.items.contains_key(&cd) {
+            return Err(item);
+        }
+        self.set_obj(cd, false);
+        self.items.insert(cd, item);
+        Ok(())
+    }
+
     /// select an empty cell from rooms randomly
-    pub(super) fn select_cell(&self, rng: &mut RngHandle, is_character: bool) -> Option<Coord> {
+    pub(crate) fn select_cell(&self, rng: &mut RngHandle, is_character: bool) -> Option<Coord> {
         let mut candidates = self.non_empty_rooms.clone();
         while candidates.len() > 0 {
             let room_idx = candidates
@@ -365,10 +487,86 @@ impl Floor {
                 cell.surface = Surface::Door;
                 return Some(GameMsg::SecretDoor);
             }
+            // as in rogue, a hidden trap is found with a probability of 1/2
+            let can_hide = matches!(cell.surface, Surface::Floor | Surface::Passage);
+            let hidden_trap = can_hide && self.traps.contains_key(&cd);
+            if hidden_trap && rng.does_happen(probinc + 2) {
+                return self.find_trap(cd).map(GameMsg::FoundTrap);
+            }
             None
         })
     }
 
+    /// reveals walls, doors, passages and the stair, as the scroll of magic mapping
+    pub(super) fn magic_map(&mut self) {
+        for cd in self.field.size() {
+            let cell = self.field.get_mut_p(cd);
+            match cell.surface {
+                Surface::Floor | Surface::Trap | Surface::None => {}
+                _ => cell.unlock(),
+            }
+        }
+    }
+
+    fn can_pass_ray(&self, cd: Coord) -> bool {
+        self.field
+            .try_get_p(cd)
+            .ok()
+            .is_some_and(|cell| cell.surface.can_walk() && !cell.is_hidden() && !cell.is_locked())
+    }
+
+    /// cells which a ray passes through, as `fire_bolt` in rogue
+    pub(super) fn ray(
+        &self,
+        start: Coord,
+        mut direction: Direction,
+        length: usize,
+        bounce: bool,
+    ) -> Vec<Coord> {
+        let mut res = Vec::new();
+        if direction == Direction::Stay {
+            return res;
+        }
+        let mut cd = start;
+        // bouncing also takes a step
+        for _ in 0..length {
+            let next = cd + direction.to_cd();
+            if self.can_pass_ray(next) {
+                cd = next;
+                res.push(cd);
+            } else if bounce {
+                direction = direction.reverse();
+            } else {
+                break;
+            }
+        }
+        res
+    }
+
+    /// lights up the room, as the wand of light
+    pub(super) fn light(&mut self, cd: Coord) -> GameResult<bool> {
+        let in_room = self.cd_to_room_id(cd).is_some_and(|id| {
+            let room = &self.rooms[id];
+            room.is_normal() && room.range().is_some_and(|r| r.contains(cd))
+        });
+        if !in_room {
+            return Ok(false);
+        }
+        self.with_current_room(
+            cd,
+            |room| {
+                room.is_dark = false;
+                true
+            },
+            |cell, _| {
+                cell.attr |= CellAttr::HAS_DRAWN;
+                cell.visible(true);
+            },
+        )
+        .context("Floor::light")?;
+        Ok(true)
+    }
+
     pub(super) fn history_map(&self) -> Array2<bool> {
         let size = self.field.size();
         let mut array = Array2::from_elem([size.ylen() as usize, size.xlen() as usize], false);
@@ -378,10 +576,16 @@ impl Floor {
         array
     }
 
+    fn is_in_open_area(&self, cd: Coord) -> bool {
+        self.cd_to_room_id(cd)
+            .is_some_and(|id| self.rooms[id].is_open())
+    }
+
     pub(super) fn in_same_room(&self, a: Coord, b: Coord) -> bool {
         let id = match self.cd_to_room_id(a) {
-            Some(i) => i,
-            None => return false,
+            // open areas aren't rooms
+            Some(i) if !self.rooms[i].is_open() => i,
+            _ => return false,
         };
         if self.cd_to_room_id(b) != Some(id) {
             return false;
@@ -392,7 +596,39 @@ impl Floor {
             .unwrap_or(true)
     }
 
-    pub(super) fn make_dist_map(&self, from: Coord, is_enemy: bool) -> Array2<u32> {
+    /// judges if the player at `player` can see a character at `cd`
+    pub(super) fn is_in_sight(&self, player: Coord, cd: Coord) -> bool {
+        if player.is_adjacent(cd) || self.in_same_room(player, cd) {
+            return true;
+        }
+        // in a floor without rooms, characters in lit cells the player has seen are visible
+        self.is_in_open_area(cd)
+            && self
+                .field
+                .try_get_p(cd)
+                .is_ok_and(|cell| cell.is_visible() && !cell.attr.contains(CellAttr::IS_DARK))
+    }
+
+    /// the nearest gold a greedy enemy at `cd` heads for, which is in the same room,
+    /// or within `OPEN_GOLD_DIST` in a floor without rooms
+    pub(super) fn nearest_gold(&self, cd: Coord) -> Option<Coord> {
+        let open = self.is_in_open_area(cd);
+        // items are in a HashMap, so we choose the nearest gold to keep the game deterministic
+        self.items
+            .iter()
+            .filter(|(_, item)| item.kind == ItemKind::Gold)
+            .map(|(&gold, _)| gold)
+            .filter(|&gold| {
+                if open {
+                    cd.move_dist(gold) <= OPEN_GOLD_DIST
+                } else {
+                    self.in_same_room(cd, gold)
+                }
+            })
+            .min_by_key(|&gold| (cd.move_dist(gold), gold))
+    }
+
+    pub(crate) fn make_dist_map(&self, from: Coord, is_enemy: bool) -> Array2<u32> {
         let (w, h) = (self.field.width(), self.field.height());
         let inf = u32::max_value();
         let mut dist = Array2::from_elem([h.0 as usize, w.0 as usize], inf);
@@ -416,6 +652,60 @@ impl Floor {
     }
 }
 
+/// draws rooms on an empty field
+pub(crate) fn draw_rooms(
+    rooms: &[Room],
+    level: u32,
+    config: &Config,
+    width: X,
+    height: Y,
+    rng: &mut RngHandle,
+) -> GameResult<Field<Surface>> {
+    let mut field = Field::new(width, height, Cell::with_default_attr(Surface::None));
+    // in this phase, we can draw surfaces 'as is'
+    rooms.iter().try_for_each(|room| {
+        room.draw(|Positioned(cd, surface)| {
+            field
+                .try_get_mut_p(cd)
+                .map(|mut_cell| {
+                    mut_cell.surface = surface;
+                    mut_cell.attr = gen_attr(surface, room.is_dark, rng, level, config);
+                })
+                .context("Error in gen_floor")
+        })
+    })?;
+    Ok(field)
+}
+
+/// draws passages and doors, some of which are hidden, and returns coordinates of doors
+pub(crate) fn draw_passages(
+    field: &mut Field<Surface>,
+    passages: Vec<Positioned<Surface>>,
+    level: u32,
+    config: &Config,
+    rng: &mut RngHandle,
+) -> GameResult<HashSet<Coord>> {
+    let mut doors = HashSet::new();
+    passages
+        .into_iter()
+        .try_for_each(|Positioned(cd, surface)| {
+            if surface == Surface::Door {
+                doors.insert(cd);
+            }
+            field
+                .try_get_mut_p(cd)
+                .map(|cell| {
+                    cell.attr = gen_attr(surface, false, rng, level, config);
+                    // if the passage is not hiddden, let's draw
+                    if !cell.is_hidden() && !cell.is_locked() {
+                        cell.surface = surface;
+                    }
+                })
+                .context("Floor::new dig_passges returned invalid index")
+        })?;
+    Ok(doors)
+}
+
 // generate initial attribute of cell
 fn gen_attr(
     surface: Surface,
@@ -453,6 +743,7 @@ fn gen_attr(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::dungeon::rogue::{bsp, Layout};
     use rect_iter::RectRange;
     #[test]
     #[ignore]
@@ -487,6 +778,44 @@ mod test {
         }
     }
     #[test]
+    fn bsp_layout() {
+        let bsp = bsp::Config::default();
+        let config = Config {
+            layout: Layout::Bsp(bsp.clone()),
+            ..Config::default()
+        };
+        let mut rng = RngHandle::new();
+        for level in 1..=20 {
+            let mut floor = Floor::gen_floor(level, &config, X(80), Y(24), &mut rng).unwrap();
+            assert!(floor.rooms.len() <= bsp.max_rooms);
+            for (i, a) in floor.rooms.iter().enumerate() {
+                assert!(floor.rooms[i + 1..]
+                    .iter()
+                    .all(|b| !a.assigned_area.intersects(&b.assigned_area)));
+                if let Some(range) = a.range() {
+                    assert!(range.xlen() <= bsp.max_room_size.x.0);
+                    assert!(range.ylen() <= bsp.max_room_size.y.0);
+                }
+            }
+            // reveal secret doors and passages as searching does
+            for cd in floor.field.size() {
+                let cell = floor.field.get_mut_p(cd);
+                if cell.is_hidden() {
+                    cell.surface = Surface::Passage;
+                } else if cell.is_locked() {
+                    cell.surface = Surface::Door;
+                }
+            }
+            let start = floor.select_cell(&mut rng, false).unwrap();
+            let dist = floor.make_dist_map(start, true);
+            for cd in floor.field.size() {
+                if floor.field.get_p(cd).surface.can_walk() {
+                    assert_ne!(*dist.get_p(cd), u32::MAX, "{}", floor.field);
+                }
+            }
+        }
+    }
+    #[test]
     fn select_cell() {
         let config = Config::default();
         let mut rng = RngHandle::new();
@@ -504,4 +833,33 @@ mod test {
         }
         assert!(cnt > 15);
     }
+    #[test]
+    fn traps() {
+        let config = Config::default();
+        let mut rng = RngHandle::new();
+        let mut floor = Floor::gen_floor(20, &config, X(80), Y(24), &mut rng).unwrap();
+        floor.setup_traps(20, &mut rng);
+        assert!(!floor.traps.is_empty() && floor.traps.len() <= MAX_TRAPS as usize);
+        let (&cd, &kind) = floor.traps.iter().next().unwrap();
+        // traps are hidden until found
+        assert_eq!(floor.field.get_p(cd).surface, Surface::Floor);
+        assert_eq!(floor.find_trap(cd), Some(kind));
+        assert_eq!(floor.field.get_p(cd).surface, Surface::Trap);
+    }
+    #[test]
+    fn ray() {
+        let config = Config::default();
+        let mut rng = RngHandle::new();
+        let floor = Floor::gen_floor(1, &config, X(80), Y(24), &mut rng).unwrap();
+        let start = floor.select_cell(&mut rng, true).unwrap();
+        for d in Direction::into_enum_iter().take(8) {
+            // a ray without bouncing stops in front of a wall
+            let ray = floor.ray(start, d, usize::MAX, false);
+            let last = ray.last().cloned().unwrap_or(start);
+            assert!(!floor.can_pass_ray(last + d.to_cd()));
+            // a bouncing ray comes back to the start
+            let ray = floor.ray(start, d, 2 * ray.len() + 1, true);
+            assert_eq!(ray.last().cloned().unwrap_or(start), start);
+        }
+    }
 }
@@ -1,22 +1,26 @@
+pub mod bsp;
 pub mod floor;
 pub mod maze;
 pub mod passages;
 pub mod rooms;
 
 use self::floor::Floor;
-pub use self::rooms::{Room, RoomKind};
+pub use self::rooms::{Layout, Room, RoomKind};
 use crate::character::{player::Status as PlayerStatus, EnemyHandler};
 use crate::dungeon::{
-    Coord, Direction, Dungeon as DungeonTrait, DungeonPath, MoveResult, Positioned, X, Y,
+    Coord, Direction, Dungeon as DungeonTrait, DungeonPath, DungeonSnapshot, MoveResult,
+    Positioned, TrapKind, X, Y,
 };
 use crate::item::{ItemHandler, ItemToken};
 use crate::tile::{Drawable, Tile};
-use crate::{error::*, rng::RngHandle, GameInfo, GameMsg, GlobalConfig};
+use crate::{error::*, hash::hash_serialized, rng::RngHandle, GameInfo, GameMsg, GlobalConfig};
 use anyhow::{bail, Context};
 use enum_iterator::IntoEnumIterator;
 use ndarray::Array2;
 use rect_iter::{Get2D, GetMut2D, RectRange};
+use serde::Serialize;
 use std::collections::VecDeque;
+use std::hash::Hasher;
 use tuple_map::TupleMap2;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -30,6 +34,9 @@ pub struct Config {
     /// minimum size of a room
     #[serde(default = "default_min_room_size")]
     pub min_room_size: Coord,
+    /// how rooms are laid out, where `room_num_x` and `room_num_y` are used only by the grid
+    #[serde(default)]
+    pub layout: Layout,
     /// enables trap or not
     #[serde(default = "default_trap")]
     pub enable_trap: bool,
@@ -119,6 +126,7 @@ impl Default for Config {
             room_num_x: default_room_num_x(),
             room_num_y: default_room_num_y(),
             min_room_size: default_min_room_size(),
+            layout: Layout::default(),
             enable_trap: default_trap(),
             max_empty_rooms: default_max_empty_rooms(),
             amulet_level: default_amulet_level(),
@@ -174,17 +182,86 @@ impl Default for Surface {
 }
 
 impl Surface {
-    fn can_walk(&self) -> bool {
+    pub(crate) fn can_walk(&self) -> bool {
         match *self {
             Surface::WallX | Surface::WallY | Surface::None => false,
             _ => true,
         }
     }
+    /// judges if items and characters can be put on the surface
+    pub(crate) fn is_open(&self) -> bool {
+        matches!(*self, Surface::Floor | Surface::Passage)
+    }
+}
+
+/// what a generator uses to fill a floor with items, stairs, enemies and traps
+pub struct FloorEnv<'a> {
+    pub level: u32,
+    pub set_gold: bool,
+    pub set_amulet: bool,
+    pub lev_add: u32,
+    pub item: &'a mut ItemHandler,
+    pub enemies: &'a mut EnemyHandler,
+    pub rng: &'a mut RngHandle,
+}
+
+/// generator of floors, which all dungeon styles except the default one customize
+pub trait FloorGen: Clone + Serialize + Send + Sync + Sized + 'static {
+    /// settings shared with rogue, e.g., traps and secret doors
+    fn base(&self) -> &Config;
+    /// generates a floor without items, stairs and traps
+    fn gen_floor(&self, level: u32, width: X, height: Y, rng: &mut RngHandle) -> GameResult<Floor>;
+    /// the number of stairs in a floor
+    fn num_stairs(&self) -> usize {
+        1
+    }
+    /// judges if the player can go down from `level`
+    fn has_lower_level(&self, _level: u32) -> bool {
+        true
+    }
+    /// where the player starts in `level`, or a random place if `None`
+    fn player_start(&self, _level: u32) -> Option<Coord> {
+        None
+    }
+    /// puts items, stairs, enemies and traps on a floor made by `gen_floor`
+    fn setup_floor(&self, floor: &mut Floor, env: FloorEnv<'_>) -> GameResult<()> {
+        let FloorEnv {
+            level,
+            set_gold,
+            set_amulet,
+            lev_add,
+            item,
+            enemies,
+            rng,
+        } = env;
+        floor.setup_items(level, item, set_gold, set_amulet, rng);
+        for _ in 0..self.num_stairs() {
+            floor.setup_stair(rng).context("in FloorGen::setup_floor")?;
+        }
+        floor.place_enemies(level, lev_add, enemies, rng);
+        if self.base().enable_trap {
+            floor.setup_traps(level, rng);
+        }
+        Ok(())
+    }
+    fn snapshot(dungeon: &Dungeon<Self>) -> DungeonSnapshot;
+}
+
+impl FloorGen for Config {
+    fn base(&self) -> &Config {
+        self
+    }
+    fn gen_floor(&self, level: u32, width: X, height: Y, rng: &mut RngHandle) -> GameResult<Floor> {
+        Floor::gen_floor(level, self, width, height, rng)
+    }
+    fn snapshot(dungeon: &Dungeon) -> DungeonSnapshot {
+        DungeonSnapshot::Rogue(dungeon.clone())
+    }
 }
 
 /// representation of rogue dungeon
-#[derive(Clone)]
-pub struct Dungeon {
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Dungeon<G = Config> {
     /// current level
     pub level: u32,
     /// amulet level or more deeper level the player visited
@@ -192,27 +269,30 @@ pub struct Dungeon {
     /// current floor
     pub current_floor: Floor,
     /// dungeon specific configuration(constant)
-    pub config: Config,
+    pub config: G,
     /// global configuration(constant)
     pub config_global: GlobalConfig,
-    /// past floors
+    /// floors of all levels the player visited, indexed by `level - 1`.
+    /// The slot for the current level is left empty.
     pub past_floors: Vec<Floor>,
     /// random number generator
     pub rng: RngHandle,
+    #[serde(skip)]
     dist_cache: DistCache,
 }
 
-impl DungeonTrait for Dungeon {
+impl<G: FloorGen> DungeonTrait for Dungeon<G> {
     fn is_downstair(&self, path: &DungeonPath) -> bool {
         let address = Address::from_path(path);
         if address.level != self.level {
             return false;
         }
-        if let Ok(cell) = self.current_floor.field.try_get_p(address.cd) {
-            cell.surface == Surface::Stair
-        } else {
-            false
-        }
+        self.is_stair(address) && self.config.has_lower_level(self.level)
+    }
+    fn is_upstair(&self, path: &DungeonPath) -> bool {
+        // as in the original rogue, the same stair leads both upward and downward
+        let address = Address::from_path(path);
+        address.level == self.level && self.is_stair(address)
     }
     fn level(&self) -> u32 {
         self.level
@@ -222,8 +302,32 @@ impl DungeonTrait for Dungeon {
         game_info: &GameInfo,
         item: &mut ItemHandler,
         enemies: &mut EnemyHandler,
-    ) -> GameResult<()> {
-        self.new_level_(game_info, item, enemies, false)
+    ) -> GameResult<Option<DungeonPath>> {
+        if !self.config.has_lower_level(self.level) {
+            bail!(ErrorKind::MaybeBug(
+                "[rogue::Dungeon::new_level] no lower level"
+            ));
+        }
+        self.leave_floor(enemies);
+        if self.restore_floor(self.level + 1) {
+            return self.stair_path().map(Some);
+        }
+        self.new_level_(game_info, item, enemies)?;
+        Ok(self.player_start())
+    }
+    fn prev_level(&mut self, enemies: &mut EnemyHandler) -> GameResult<DungeonPath> {
+        if self.level <= 1 {
+            bail!(ErrorKind::MaybeBug(
+                "[rogue::Dungeon::prev_level] no upper level"
+            ));
+        }
+        self.leave_floor(enemies);
+        if !self.restore_floor(self.level - 1) {
+            bail!(ErrorKind::MaybeBug(
+                "[rogue::Dungeon::prev_level] upper floor is lost"
+            ));
+        }
+        self.stair_path()
     }
     fn can_move_player(&self, path: &DungeonPath, direction: Direction) -> Option<DungeonPath> {
         let address = Address::from_path(path);
@@ -252,7 +356,7 @@ impl DungeonTrait for Dungeon {
             cd,
         };
         self.current_floor
-            .player_in(cd, false, enemies)
+            .player_in(address, false, enemies)
             .context(ERR_STR)?;
         Ok(address.into())
     }
@@ -261,11 +365,91 @@ impl DungeonTrait for Dungeon {
         if address.level != self.level {
             bail!(ErrorKind::MaybeBug("[rogue::Dungeon::search]"));
         }
+        // found passages change distances, so cached ones are no longer valid
+        self.dist_cache.clear();
         Ok(self
             .current_floor
-            .search(address.cd, &mut self.rng, &self.config)
+            .search(address.cd, &mut self.rng, self.config.base())
             .collect())
     }
+    fn step_on_trap(&mut self, path: &DungeonPath) -> Option<TrapKind> {
+        let address = Address::from_path(path);
+        if address.level != self.level {
+            return None;
+        }
+        self.current_floor.find_trap(address.cd)
+    }
+    fn magic_map(&mut self) {
+        // found passages change distances, so cached ones are no longer valid
+        self.dist_cache.clear();
+        self.current_floor.magic_map();
+    }
+    fn teleport(
+        &mut self,
+        path: &DungeonPath,
+        enemies: &mut EnemyHandler,
+    ) -> GameResult<DungeonPath> {
+        const ERR_STR: &str = "[rogue::Dungeon::teleport]";
+        let address = Address::from_path(path);
+        if address.level != self.level {
+            bail!(ErrorKind::MaybeBug(ERR_STR));
+        }
+        self.current_floor.player_out(address.cd).context(ERR_STR)?;
+        let cd = self
+            .current_floor
+            .select_cell(&mut self.rng, true)
+            .ok_or(ErrorKind::MaybeBug(ERR_STR))?;
+        let address = Address::new(self.level, cd);
+        self.current_floor
+            .player_in(address, true, enemies)
+            .context(ERR_STR)?;
+        Ok(address.into())
+    }
+    fn create_enemy(
+        &mut self,
+        path: &DungeonPath,
+        enemies: &mut EnemyHandler,
+    ) -> Option<DungeonPath> {
+        let address = Address::from_path(path);
+        let candidates: Vec<DungeonPath> = Direction::into_enum_iter()
+            .take(8)
+            .filter_map(|d| self.current_floor.can_move_player(address.cd, d))
+            .map(|cd| Address::new(self.level, cd).into())
+            .filter(|p| enemies.get_id(p).is_none())
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let place = candidates[self.rng.range(0..candidates.len())].clone();
+        let level = self.level;
+        let min = level.saturating_sub(4);
+        enemies.create_enemy(place.clone(), min..level + 6, i64::from(self.lev_add()))?;
+        Some(place)
+    }
+    fn ray(
+        &self,
+        path: &DungeonPath,
+        direction: Direction,
+        length: usize,
+        bounce: bool,
+    ) -> Vec<DungeonPath> {
+        let address = Address::from_path(path);
+        if address.level != self.level {
+            return vec![];
+        }
+        self.current_floor
+            .ray(address.cd, direction, length, bounce)
+            .into_iter()
+            .map(|cd| Address::new(self.level, cd).into())
+            .collect()
+    }
+    fn light(&mut self, path: &DungeonPath) -> GameResult<bool> {
+        let address = Address::from_path(path);
+        if address.level != self.level {
+            bail!(ErrorKind::MaybeBug("[rogue::Dungeon::light]"));
+        }
+        self.current_floor.light(address.cd)
+    }
     fn select_cell(&mut self, is_character: bool) -> Option<DungeonPath> {
         self.current_floor
             .select_cell(&mut self.rng, is_character)
@@ -273,7 +457,7 @@ impl DungeonTrait for Dungeon {
     }
     fn enter_room(&mut self, path: &DungeonPath, enemies: &mut EnemyHandler) -> GameResult<()> {
         let address = Address::from_path(path);
-        self.current_floor.player_in(address.cd, true, enemies)
+        self.current_floor.player_in(address, true, enemies)
     }
     fn draw(&self, drawer: &mut dyn FnMut(Positioned<Tile>) -> GameResult<()>) -> GameResult<()> {
         const ERR_STR: &str = "in rogue::Dungeon::move_player";
@@ -318,6 +502,32 @@ impl DungeonTrait for Dungeon {
         }
         self.current_floor.items.remove(&addr.cd)
     }
+    fn put_item(&mut self, path: &DungeonPath, item: ItemToken) -> Result<(), ItemToken> {
+        let addr = Address::from_path(path);
+        if addr.level != self.level {
+            return Err(item);
+        }
+        self.current_floor.put_item(addr.cd, item)
+    }
+    fn items(&self) -> Vec<(DungeonPath, &ItemToken)> {
+        self.current_floor
+            .items
+            .iter()
+            .map(|(&cd, item)| (Address::new(self.level, cd).into(), item))
+            .collect()
+    }
+    fn gold_in_room(&self, path: &DungeonPath) -> Option<DungeonPath> {
+        let addr = Address::from_path(path);
+        if addr.level != self.level {
+            return None;
+        }
+        self.current_floor
+            .nearest_gold(addr.cd)
+            .map(|cd| Address::new(self.level, cd).into())
+    }
+    fn is_current_level(&self, path: &DungeonPath) -> bool {
+        Address::from_path(path).level == self.level
+    }
     fn tile(&mut self, path: &DungeonPath) -> Option<Tile> {
         let cd = self.path_to_cd(path);
         self.current_floor
@@ -330,10 +540,9 @@ impl DungeonTrait for Dungeon {
         let level = status.dungeon_level;
         if level == self.level {
             Some(self.current_floor.history_map())
-        } else if let Some(floor) = self.past_floors.get(level as usize - 1) {
-            Some(floor.history_map())
         } else {
-            None
+            let idx = (level as usize).checked_sub(1)?;
+            self.past_floors.get(idx).map(Floor::history_map)
         }
     }
     fn move_enemy(
@@ -352,7 +561,8 @@ impl DungeonTrait for Dungeon {
             dist_cache,
             ..
         } = self;
-        let dist_map = dist_cache.make_dist_map(current_floor, dist.cd, true);
+        let dist_map =
+            dist_cache.make_dist_map(dist.cd, |cd| current_floor.make_dist_map(cd, true));
         for d in Direction::into_enum_iter() {
             let next = cur.cd + d.to_cd();
             if skip(&DungeonPath::from(Address::new(cur.level, next))) {
@@ -400,14 +610,24 @@ impl DungeonTrait for Dungeon {
         if p.level != e.level {
             return false;
         }
-        p.cd.is_adjacent(e.cd) || self.current_floor.in_same_room(p.cd, e.cd)
+        self.current_floor.is_in_sight(p.cd, e.cd)
+    }
+    fn player_start(&self) -> Option<DungeonPath> {
+        let cd = self.config.player_start(self.level)?;
+        Some(Address::new(self.level, cd).into())
+    }
+    fn snapshot(&self) -> DungeonSnapshot {
+        G::snapshot(self)
+    }
+    fn hash_state(&self, hasher: &mut dyn Hasher) {
+        hash_serialized(self, hasher)
     }
 }
 
-impl Dungeon {
+impl<G: FloorGen> Dungeon<G> {
     /// make new dungeon
     pub fn new(
-        config: Config,
+        config: G,
         config_global: &GlobalConfig,
         game_info: &GameInfo,
         item_handle: &mut ItemHandler,
@@ -417,7 +637,7 @@ impl Dungeon {
         let rng = RngHandle::from_seed(seed);
         let mut dungeon = Dungeon {
             level: 0,
-            max_level: config.amulet_level,
+            max_level: config.base().amulet_level,
             current_floor: Floor::default(),
             config,
             config_global: config_global.clone(),
@@ -426,7 +646,7 @@ impl Dungeon {
             dist_cache: DistCache::new(),
         };
         dungeon
-            .new_level_(game_info, item_handle, enemies, true)
+            .new_level_(game_info, item_handle, enemies)
             .context("rogue::Dungeon::new")?;
         Ok(dungeon)
     }
@@ -436,7 +656,6 @@ impl Dungeon {
         game_info: &GameInfo,
         item_handle: &mut ItemHandler,
         enemies: &mut EnemyHandler,
-        is_initial: bool,
     ) -> GameResult<()> {
         const ERR_STR: &str = "in rogue::Dungeon::new_level";
         let level = {
@@ -447,21 +666,25 @@ impl Dungeon {
             self.max_level = level;
         }
         let (width, height) = (self.config_global.width, self.config_global.height);
-        let mut floor =
-            Floor::gen_floor(level, &self.config, width, height, &mut self.rng).context(ERR_STR)?;
+        let mut floor = self
+            .config
+            .gen_floor(level, width, height, &mut self.rng)
+            .context(ERR_STR)?;
         debug!("[Dungeon::new_level] field: {}", floor.field);
         // setup gold
         let set_gold = !game_info.is_cleared || level >= self.max_level;
         debug!("[Dungeon::new_level] set_gold: {}", set_gold);
-        floor.setup_items(level, item_handle, set_gold, &mut self.rng);
-        // place stair
-        floor.setup_stair(&mut self.rng).context(ERR_STR)?;
-        // place enemies
-        if !is_initial {
-            enemies.remove_enemies();
-        }
-        floor.place_enemies(level, self.lev_add(), enemies, &mut self.rng);
-        // place traps (STUB)
+        let set_amulet = !game_info.is_cleared && level == self.config.base().amulet_level;
+        let env = FloorEnv {
+            level,
+            set_gold,
+            set_amulet,
+            lev_add: self.lev_add(),
+            item: item_handle,
+            enemies,
+            rng: &mut self.rng,
+        };
+        self.config.setup_floor(&mut floor, env).context(ERR_STR)?;
         if !self.config_global.hide_dungeon {
             let xmax = self.config_global.width.0;
             let ymax = self.config_global.height.0 - 1;
@@ -473,39 +696,79 @@ impl Dungeon {
                     cell.visible(true);
                 });
         }
-        ::std::mem::swap(&mut self.current_floor, &mut floor);
-        if !is_initial {
-            self.past_floors.push(floor);
-        }
+        self.current_floor = floor;
+        self.dist_cache.clear();
         Ok(())
     }
 
-    fn lev_add(&self) -> u32 {
-        if self.config.amulet_level < self.level {
-            self.level - self.config.amulet_level
+    /// stores the current floor to past_floors, to go to another level
+    fn leave_floor(&mut self, enemies: &mut EnemyHandler) {
+        enemies.deactivate_all();
+        let idx = self.level as usize - 1;
+        let floor = ::std::mem::take(&mut self.current_floor);
+        if idx < self.past_floors.len() {
+            self.past_floors[idx] = floor;
         } else {
-            0
+            self.past_floors.push(floor);
         }
     }
+
+    /// takes a floor the player visited before out of past_floors
+    fn restore_floor(&mut self, level: u32) -> bool {
+        match self.past_floors.get_mut(level as usize - 1) {
+            Some(floor) => {
+                self.current_floor = ::std::mem::take(floor);
+                self.level = level;
+                self.dist_cache.clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_stair(&self, address: Address) -> bool {
+        self.current_floor
+            .field
+            .try_get_p(address.cd)
+            .is_ok_and(|cell| cell.surface == Surface::Stair)
+    }
+
+    fn stair_path(&self) -> GameResult<DungeonPath> {
+        let cd = self.current_floor.stair().ok_or(ErrorKind::MaybeBug(
+            "[rogue::Dungeon] no stair in the floor",
+        ))?;
+        Ok(Address::new(self.level, cd).into())
+    }
+
+    fn lev_add(&self) -> u32 {
+        self.level.saturating_sub(self.config.base().amulet_level)
+    }
 }
 
-#[derive(Clone)]
-struct DistCache {
+/// cache of distance maps for enemy movement, keyed by the destination
+#[derive(Clone, Default)]
+pub(crate) struct DistCache {
     cache: VecDeque<(Array2<u32>, Coord)>,
 }
 
 impl DistCache {
     const MAX_CACHED_DIST: usize = 8;
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         DistCache {
             cache: VecDeque::with_capacity(Self::MAX_CACHED_DIST),
         }
     }
-    fn make_dist_map(&mut self, floor: &Floor, cd: Coord, is_enemy: bool) -> &Array2<u32> {
+    pub(crate) fn clear(&mut self) {
+        self.cache.clear();
+    }
+    pub(crate) fn make_dist_map<F>(&mut self, cd: Coord, make: F) -> &Array2<u32>
+    where
+        F: FnOnce(Coord) -> Array2<u32>,
+    {
         if let Some(pos) = self.cache.iter().position(|t| t.1 == cd) {
             return &self.cache[pos].0;
         }
-        let dist_map = floor.make_dist_map(cd, is_enemy);
+        let dist_map = make(cd);
         let len = self.cache.len();
         self.cache.push_back((dist_map, cd));
         if len > Self::MAX_CACHED_DIST {
@@ -541,8 +804,14 @@ impl Address {
 
 #[cfg(test)]
 mod test {
-    use super::{Address, Coord, Direction, DungeonPath, MoveResult, TupleMap2};
-    use crate::{GameConfig, RunTime};
+    use super::{Address, Coord, Direction, DungeonPath, MoveResult, TupleMap2, X, Y};
+    use super::{Config, Dungeon, DungeonTrait};
+    use crate::character::enemies;
+    use crate::input::Key;
+    use crate::item::{self, ItemHandler, ItemKind, ItemNum};
+    use crate::ui::{MordalKind, UiState};
+    use crate::{GameConfig, GameInfo, GameMsg, GlobalConfig, Reaction, RunTime};
+    use enum_iterator::IntoEnumIterator;
     // tiny dungeon setting
     const CONFIG: &str = r#"
 {
@@ -576,4 +845,161 @@ mod test {
         };
         check_move(Coord::new(9, 9), Coord::new(28, 4), Direction::Right);
     }
+    fn find_stair(runtime: &RunTime) -> DungeonPath {
+        let level = runtime.dungeon.level();
+        (0..32)
+            .flat_map(|x| (0..16).map(move |y| Address::new(level, Coord::new(x, y))))
+            .map(DungeonPath::from)
+            .find(|path| runtime.dungeon.is_downstair(path))
+            .unwrap()
+    }
+    #[test]
+    fn upstair() {
+        let mut runtime = setup_runtime();
+        let res = runtime.react_to_key(Key::Char('<')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::NoUpStair)));
+        let stair1 = find_stair(&runtime);
+        runtime.player.pos = stair1.clone();
+        let res = runtime.react_to_key(Key::Char('<')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::NoUpStair)));
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        let history1 = runtime.history(&runtime.player_status()).unwrap();
+        let stair2 = find_stair(&runtime);
+        runtime.player.pos = stair2.clone();
+        // go back to level 1 and arrive at the stair there
+        runtime.react_to_key(Key::Char('<')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 1);
+        assert_eq!(runtime.player.pos, stair1);
+        // level 2 is kept as it was
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        assert_eq!(runtime.player.pos, stair2);
+        let history2 = runtime.history(&runtime.player_status()).unwrap();
+        assert!(history1.iter().zip(history2.iter()).all(|(&a, &b)| !a || b));
+        // a new level is generated for level 3
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 3);
+        let saved = runtime.save().unwrap();
+        let loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), runtime.state_hash());
+    }
+    #[test]
+    fn nearest_gold() {
+        let global = GlobalConfig {
+            width: X(80),
+            height: Y(24),
+            seed: 1,
+            hide_dungeon: true,
+        };
+        let mut item = ItemHandler::new(item::Config::default(), 1);
+        let mut enemies = enemies::Config::default().build(1);
+        let mut dungeon = Dungeon::new(
+            Config::default(),
+            &global,
+            &GameInfo::new(),
+            &mut item,
+            &mut enemies,
+            1,
+        )
+        .unwrap();
+        let floor = &mut dungeon.current_floor;
+        floor.items.clear();
+        let range = floor
+            .rooms
+            .iter()
+            .filter_map(|room| room.range().filter(|_| room.is_normal()))
+            .find(|range| range.xlen() >= 5)
+            .unwrap()
+            .clone();
+        let (x, y) = (range.get_x().start, range.get_y().start + 1);
+        let at = |i| Coord::new(x + i, y);
+        let (left, mid, right) = (at(1), at(2), at(3));
+        for cd in [left, right] {
+            let gold = item.gen_item(ItemKind::Gold.numbered(ItemNum(10)).unwrap().many());
+            floor.put_item(cd, gold).unwrap();
+        }
+        // the left one is chosen from 2 gold piles at the same distance
+        let path = DungeonPath::from(Address::new(1, mid));
+        for _ in 0..10 {
+            let gold = dungeon.gold_in_room(&path).unwrap();
+            assert_eq!(Address::from_path(&gold).cd, left);
+        }
+        let path = DungeonPath::from(Address::new(1, right));
+        let gold = dungeon.gold_in_room(&path).unwrap();
+        assert_eq!(Address::from_path(&gold).cd, right);
+    }
+    #[test]
+    fn bsp_layout() {
+        let config = CONFIG.replace(
+            r#""style": "rogue","#,
+            r#""style": "rogue", "layout": { "kind": "bsp", "min_rooms": 2, "max_rooms": 4 },"#,
+        );
+        let mut runtime = GameConfig::from_json(&config).unwrap().build().unwrap();
+        runtime.player.pos = find_stair(&runtime);
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 2);
+        let saved = runtime.save().unwrap();
+        let loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(loaded.state_hash(), runtime.state_hash());
+        let invalid = CONFIG.replace(
+            r#""style": "rogue","#,
+            r#""style": "rogue", "layout": { "kind": "bsp", "min_rooms": 5, "max_rooms": 4 },"#,
+        );
+        assert!(GameConfig::from_json(&invalid).unwrap().build().is_err());
+    }
+    #[test]
+    fn amulet() {
+        let config = CONFIG.replace(
+            r#""style": "rogue","#,
+            r#""style": "rogue", "amulet_level": 2,"#,
+        );
+        let mut runtime = GameConfig::from_json(&config).unwrap().build().unwrap();
+        let stair1 = find_stair(&runtime);
+        runtime.player.pos = stair1.clone();
+        runtime.react_to_key(Key::Char('>')).unwrap();
+        let amulet = (0..32)
+            .flat_map(|x| (0..16).map(move |y| Address::new(2, Coord::new(x, y))))
+            .map(DungeonPath::from)
+            .find(|path| {
+                matches!(runtime.dungeon.get_item(path), Some(item) if item.kind == ItemKind::Amulet)
+            })
+            .unwrap();
+        // walk onto the amulet from a neighboring cell
+        let (from, key) = Direction::into_enum_iter()
+            .take(8)
+            .find_map(|d| {
+                let cd = Address::from_path(&amulet).cd - d.to_cd();
+                let from = DungeonPath::from(Address::new(2, cd));
+                let key = match d {
+                    Direction::Left => 'h',
+                    Direction::Down => 'j',
+                    Direction::Up => 'k',
+                    Direction::Right => 'l',
+                    Direction::LeftUp => 'y',
+                    Direction::RightUp => 'u',
+                    Direction::LeftDown => 'b',
+                    Direction::RightDown => 'n',
+                    Direction::Stay => return None,
+                };
+                if runtime.dungeon.can_move_player(&from, d) == Some(amulet.clone()) {
+                    Some((from, Key::Char(key)))
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        runtime.player.pos = from;
+        assert!(!runtime.game_info.is_cleared);
+        // an enemy can be on the amulet
+        runtime.enemies.remove(amulet.clone());
+        runtime.react_to_key(key).unwrap();
+        assert!(runtime.game_info.is_cleared);
+        runtime.player.pos = find_stair(&runtime);
+        runtime.react_to_key(Key::Char('<')).unwrap();
+        assert_eq!(runtime.dungeon.level(), 1);
+        let res = runtime.react_to_key(Key::Char('<')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Victory)));
+        assert_eq!(runtime.ui, UiState::Mordal(MordalKind::Victory));
+    }
 }
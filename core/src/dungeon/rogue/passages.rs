@@ -15,8 +15,7 @@ use tuple_map::TupleMap2;
 /// make passages between rooms
 pub(super) fn dig_passges<F>(
     rooms: &[Room],
-    xrooms: X,
-    yrooms: Y,
+    mut graph: RoomGraph,
     rng: &mut RngHandle,
     max_extra_edges: u32,
     mut register: F,
@@ -24,7 +23,6 @@ pub(super) fn dig_passges<F>(
 where
     F: FnMut(Positioned<Surface>) -> GameResult<()>,
 {
-    let mut graph = RoomGraph::new(xrooms, yrooms);
     let num_rooms = rooms.len();
     let mut selected = FenwickSet::with_capacity(num_rooms);
     let mut cur_room = rng.range(0..num_rooms);
@@ -175,6 +173,7 @@ fn select_start_or_end(room: &Room, direction: Direction, rng: &mut RngHandle) -
             unreachable!("cannot find maze floor in passages::select_start_or_end")
         }
         RoomKind::Empty { up_left } => up_left,
+        RoomKind::Open { .. } => unreachable!("open areas aren't connected by passages"),
     }
 }
 
@@ -220,12 +219,13 @@ fn edges(range: &RectRange<i32>, direction: Direction, is_inclusive: bool) -> Ve
 
 /// a representation of room connectivity
 #[derive(Clone, Debug, Index)]
-struct RoomGraph {
+pub(super) struct RoomGraph {
     inner: Vec<Node>,
 }
 
 impl RoomGraph {
-    fn new(xrooms: X, yrooms: Y) -> Self {
+    /// rooms on a grid, each of which can be connected with its neighbors
+    pub(super) fn new(xrooms: X, yrooms: Y) -> Self {
         let range = RectRange::zero_start(xrooms.0, yrooms.0).unwrap();
         let inner: Vec<_> = range
             .into_iter()
@@ -234,15 +234,93 @@ impl RoomGraph {
             .collect();
         RoomGraph { inner }
     }
+    /// rooms placed freely, where 2 rooms can be connected if a passage between them
+    /// doesn't cross any other room.
+    /// Returns None if some rooms can't be reached.
+    pub(super) fn from_rooms(rooms: &[Room]) -> Option<Self> {
+        let occupied: Vec<_> = rooms.iter().map(occupied_range).collect();
+        let num_rooms = rooms.len();
+        let mut candidates = vec![HashMap::new(); num_rooms];
+        for i in 0..num_rooms {
+            for j in i + 1..num_rooms {
+                let (ri, rj) = (&occupied[i], &occupied[j]);
+                let (direction, between) = match range_between(ri, rj) {
+                    Some(t) => t,
+                    None => continue,
+                };
+                let crosses = (0..num_rooms)
+                    .filter(|&k| k != i && k != j)
+                    .any(|k| occupied[k].intersects(&between));
+                if !crosses {
+                    candidates[i].insert(j, direction);
+                    candidates[j].insert(i, direction.reverse());
+                }
+            }
+        }
+        // check connectivity
+        let mut visited = FixedBitSet::with_capacity(num_rooms);
+        let mut stack = vec![0];
+        visited.insert(0);
+        while let Some(cur) = stack.pop() {
+            for &nxt in candidates[cur].keys() {
+                if !visited.put(nxt) {
+                    stack.push(nxt);
+                }
+            }
+        }
+        if visited.count_ones(..) < num_rooms {
+            return None;
+        }
+        let inner = candidates
+            .into_iter()
+            .enumerate()
+            .map(|(id, candidates)| Node {
+                connections: FixedBitSet::with_capacity(num_rooms),
+                candidates,
+                id,
+            })
+            .collect();
+        Some(RoomGraph { inner })
+    }
     fn coonect(&mut self, node1: usize, node2: usize) {
         self.inner[node1].connections.insert(node2);
         self.inner[node2].connections.insert(node1);
     }
 }
 
+/// the range a room occupies, which passages can't go through
+fn occupied_range(room: &Room) -> RectRange<i32> {
+    match room.kind {
+        RoomKind::Empty { up_left } => {
+            RectRange::from_corners(up_left, up_left + Coord::new(1, 1)).unwrap()
+        }
+        _ => room.range().unwrap().clone(),
+    }
+}
+
+/// the direction from `r1` to `r2` and the range where a passage between them can go
+fn range_between(r1: &RectRange<i32>, r2: &RectRange<i32>) -> Option<(Direction, RectRange<i32>)> {
+    let (x1, y1, x2, y2) = (r1.get_x(), r1.get_y(), r2.get_x(), r2.get_y());
+    let xs = x1.start.min(x2.start)..x1.end.max(x2.end);
+    let ys = y1.start.min(y2.start)..y1.end.max(y2.end);
+    // connect_2rooms needs at least 1 cell between rooms to turn
+    let (direction, x, y) = if x2.start > x1.end {
+        (Direction::Right, x1.end - 1..x2.start + 1, ys)
+    } else if x1.start > x2.end {
+        (Direction::Left, x2.end - 1..x1.start + 1, ys)
+    } else if y2.start > y1.end {
+        (Direction::Down, xs, y1.end - 1..y2.start + 1)
+    } else if y1.start > y2.end {
+        (Direction::Up, xs, y2.end - 1..y1.start + 1)
+    } else {
+        return None;
+    };
+    RectRange::from_ranges(x, y).map(|range| (direction, range))
+}
+
 /// a node of room graph
 #[derive(Clone, Debug)]
-struct Node {
+pub(super) struct Node {
     connections: FixedBitSet,
     candidates: HashMap<usize, Direction>,
     id: usize,
@@ -308,8 +386,7 @@ mod test {
         let mut rng = RngHandle::new();
         dig_passges(
             &rooms,
-            X(3),
-            Y(3),
+            RoomGraph::new(X(3), Y(3)),
             &mut rng,
             5,
             |Positioned(cd, surface)| {
@@ -1,4 +1,5 @@
-use super::{maze, Config, Surface};
+use super::passages::RoomGraph;
+use super::{bsp, maze, Config, Surface};
 use crate::dungeon::{Coord, Positioned, X, Y};
 use crate::{error::*, fenwick::FenwickSet, rng::RngHandle};
 use anyhow::{bail, Context};
@@ -7,6 +8,21 @@ use log::warn;
 use rect_iter::{IntoTuple2, RectRange};
 use tuple_map::TupleMap2;
 
+/// how rooms are laid out in a floor
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(tag = "kind")]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// `room_num_x` x `room_num_y` grid, as in rogue
+    #[default]
+    Grid,
+    /// areas split by binary space partitioning
+    Bsp(bsp::Config),
+}
+
+/// how many times we try to make connectable rooms by BSP
+const MAX_TRIES: usize = 100;
+
 /// type of room
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum RoomKind {
@@ -16,6 +32,11 @@ pub enum RoomKind {
     Maze(Box<maze::Maze>),
     /// passage only(gone room)
     Empty { up_left: Coord },
+    /// a part of a floor without rooms, as caves, where all open cells can be used
+    Open {
+        range: RectRange<i32>,
+        cells: FenwickSet,
+    },
 }
 
 /// A data structure representing a room in the dungeon
@@ -54,6 +75,30 @@ impl Room {
             is_visited: false,
         }
     }
+    /// makes a normal room whose assigned area is just its range
+    pub(crate) fn normal(range: RectRange<i32>, is_dark: bool, id: usize) -> Self {
+        let kind = RoomKind::Normal {
+            range: range.clone(),
+        };
+        Room::new(kind, is_dark, id, range)
+    }
+    /// makes an open area, which is always dark, from cells in `range` judged by `is_open`
+    pub(crate) fn open<F>(range: RectRange<i32>, id: usize, is_open: F) -> Self
+    where
+        F: Fn(Coord) -> bool,
+    {
+        let mut cells = FenwickSet::with_capacity(range.len());
+        range.iter().enumerate().for_each(|(i, cd)| {
+            if is_open(cd.into()) {
+                cells.insert(i);
+            }
+        });
+        let kind = RoomKind::Open {
+            range: range.clone(),
+            cells,
+        };
+        Room::new(kind, true, id, range)
+    }
     /// takes a closure `register` and draw room by it
     pub fn draw<R>(&self, mut register: R) -> GameResult<()>
     where
@@ -77,7 +122,8 @@ impl Room {
                 .passages()
                 .try_for_each(|cd| register(Positioned(cd, Surface::Passage)))
                 .context("Room::draw"),
-            RoomKind::Empty { .. } => Ok(()),
+            // open areas are drawn by the generator of the floor
+            RoomKind::Empty { .. } | RoomKind::Open { .. } => Ok(()),
         }
     }
     /// Returns the 'room' range
@@ -85,6 +131,7 @@ impl Room {
         match self.kind {
             RoomKind::Normal { ref range } => Some(range),
             RoomKind::Maze(ref maze) => Some(&maze.range),
+            RoomKind::Open { ref range, .. } => Some(range),
             _ => None,
         }
     }
@@ -126,6 +173,9 @@ impl Room {
             _ => false,
         }
     }
+    pub fn is_open(&self) -> bool {
+        matches!(self.kind, RoomKind::Open { .. })
+    }
     pub fn contains(&self, cd: Coord) -> bool {
         self.assigned_area.contains(cd)
     }
@@ -157,37 +207,56 @@ fn gen_empty_cells(kind: &RoomKind) -> FenwickSet {
             set
         }
         RoomKind::Maze(ref maze) => maze.passages.clone(),
+        RoomKind::Open { ref cells, .. } => cells.clone(),
         RoomKind::Empty { .. } => FenwickSet::with_capacity(1),
     }
 }
 
-/// generate rooms
+/// generate rooms and the graph of rooms which can be connected by passages
 pub(super) fn gen_rooms(
     level: u32,
     config: &Config,
     width: X,
     height: Y,
     rng: &mut RngHandle,
+) -> GameResult<(Vec<Room>, RoomGraph)> {
+    match config.layout {
+        Layout::Grid => {
+            let rooms = gen_grid_rooms(level, config, width, height, rng)?;
+            Ok((rooms, RoomGraph::new(config.room_num_x, config.room_num_y)))
+        }
+        Layout::Bsp(ref bsp) => gen_bsp_rooms(level, config, bsp, width, height, rng),
+    }
+}
+
+/// select empty rooms, leaving at least one normal room
+fn select_empty_rooms(config: &Config, room_num: usize, rng: &mut RngHandle) -> FixedBitSet {
+    let empty_num = match rng.range(0..=config.max_empty_rooms) {
+        n if n >= room_num as u32 => {
+            warn!(
+                "Specified max_empty_rooms is {}, but room num is {}",
+                n, room_num
+            );
+            room_num as u32 - 1
+        }
+        n => n,
+    };
+    rng.select(0..room_num).take(empty_num as usize).collect()
+}
+
+fn gen_grid_rooms(
+    level: u32,
+    config: &Config,
+    width: X,
+    height: Y,
+    rng: &mut RngHandle,
 ) -> GameResult<Vec<Room>> {
     let (rn_x, rn_y) = (config.room_num_x, config.room_num_y);
     let room_num = (rn_x.0 * rn_y.0) as usize;
     // Be aware that it's **screen** size!
     let (width, height) = (width, height);
     let room_size = Coord::new(width / rn_x.0, height / rn_y.0);
-    // set empty rooms
-    let empty_rooms: FixedBitSet = {
-        let empty_num = match rng.range(0..=config.max_empty_rooms) {
-            n if n >= room_num as u32 => {
-                warn!(
-                    "Specified max_empty_rooms is {}, but room num is {}",
-                    n, room_num
-                );
-                room_num as u32 - 1
-            }
-            n => n,
-        };
-        rng.select(0..room_num).take(empty_num as usize).collect()
-    };
+    let empty_rooms = select_empty_rooms(config, room_num, rng);
     RectRange::zero_start(rn_x.0, rn_y.0)
         .unwrap()
         .into_iter()
@@ -205,11 +274,80 @@ pub(super) fn gen_rooms(
                 room_size.y -= Y(1);
             }
             let is_empty = empty_rooms.contains(i);
-            make_room(is_empty, room_size, lower_left, i, &config, level, rng)
+            make_room(is_empty, room_size, lower_left, i, config, level, rng)
         })
         .collect()
 }
 
+fn gen_bsp_rooms(
+    level: u32,
+    config: &Config,
+    bsp: &bsp::Config,
+    width: X,
+    height: Y,
+    rng: &mut RngHandle,
+) -> GameResult<(Vec<Room>, RoomGraph)> {
+    let min_size = config.min_room_size;
+    if min_size.x.0 < 3 || min_size.y.0 < 3 {
+        bail!(ErrorKind::InvalidSetting(
+            "min_room_size must be 3x3 or larger".into()
+        ));
+    }
+    if bsp.min_rooms == 0 || bsp.min_rooms > bsp.max_rooms {
+        bail!(ErrorKind::InvalidSetting(
+            "min_rooms must be positive and not larger than max_rooms".into()
+        ));
+    }
+    if bsp.max_room_size.x < min_size.x || bsp.max_room_size.y < min_size.y {
+        bail!(ErrorKind::InvalidSetting(
+            "max_room_size is smaller than min_room_size".into()
+        ));
+    }
+    // leave the message line and the status line, as the grid layout does
+    let area = RectRange::from_ranges(0..width.0, 1..height.0 - 1).ok_or(ErrorKind::MaybeBug(
+        "[rooms::gen_bsp_rooms] too small dungeon",
+    ))?;
+    // make_room doesn't use the last row and column of an area
+    let (min_area, max_size) = (min_size, bsp.max_room_size).map(|s| s + Coord::new(1, 1));
+    for _ in 0..MAX_TRIES {
+        let room_num = rng.range(bsp.min_rooms..=bsp.max_rooms);
+        let areas = bsp::partition(area.clone(), room_num, min_area, rng);
+        let empty_rooms = select_empty_rooms(config, areas.len(), rng);
+        let rooms = areas
+            .into_iter()
+            .enumerate()
+            .map(|(i, area)| {
+                // a room is made in a random part of the area no larger than max_room_size
+                let area_size = Coord::new(area.xlen(), area.ylen());
+                let room_size =
+                    Coord::new(area_size.x.min(max_size.x), area_size.y.min(max_size.y));
+                let lower_left = (area_size - room_size)
+                    .into_tuple2()
+                    .map(|rest| rng.range(0..=rest))
+                    .add(area.lower_left());
+                let is_empty = empty_rooms.contains(i);
+                let mut room = make_room(
+                    is_empty,
+                    room_size,
+                    lower_left.into(),
+                    i,
+                    config,
+                    level,
+                    rng,
+                )?;
+                room.assigned_area = area;
+                Ok(room)
+            })
+            .collect::<GameResult<Vec<_>>>()?;
+        if let Some(graph) = RoomGraph::from_rooms(&rooms) {
+            return Ok((rooms, graph));
+        }
+    }
+    bail!(ErrorKind::MaybeBug(
+        "[rooms::gen_bsp_rooms] failed to generate connectable rooms"
+    ))
+}
+
 /// generata a room
 pub(super) fn make_room(
     is_empty: bool,
@@ -279,7 +417,7 @@ pub(super) mod test {
         config.maze_rate_inv = 5;
         let (w, h) = (X(80), Y(24));
         let mut rng = RngHandle::new();
-        gen_rooms(level, &config, w, h, &mut rng).unwrap()
+        gen_rooms(level, &config, w, h, &mut rng).unwrap().0
     }
     pub fn draw_to_buffer(rooms: &[Room]) -> Vec<Vec<Surface>> {
         let mut buffer = vec![vec![Surface::None; 80]; 24];
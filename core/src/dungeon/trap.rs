@@ -0,0 +1,45 @@
+//! traps hidden in the dungeon
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// kinds of traps, as in rogue
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, IntoEnumIterator)]
+pub enum TrapKind {
+    /// drops the player to the next level
+    TrapDoor,
+    /// shoots an arrow at the player
+    Arrow,
+    /// puts the player to sleep
+    SleepingGas,
+    /// holds the player for some turns
+    BearTrap,
+    /// moves the player to a random place
+    Teleport,
+    /// shoots a dart which may reduce the strength
+    PoisonDart,
+    /// rusts the armor
+    Rust,
+    /// does nothing but confuses the player
+    Mysterious,
+}
+
+impl TrapKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TrapKind::TrapDoor => "trapdoor",
+            TrapKind::Arrow => "arrow trap",
+            TrapKind::SleepingGas => "sleeping gas trap",
+            TrapKind::BearTrap => "beartrap",
+            TrapKind::Teleport => "teleport trap",
+            TrapKind::PoisonDart => "poison dart trap",
+            TrapKind::Rust => "rust trap",
+            TrapKind::Mysterious => "mysterious trap",
+        }
+    }
+}
+
+impl fmt::Display for TrapKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
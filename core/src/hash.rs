@@ -0,0 +1,67 @@
+//! deterministic hashing of game states
+use serde::Serialize;
+use std::hash::Hasher;
+use std::io;
+
+/// 64bit FNV-1a hasher.
+/// Unlike std's DefaultHasher, the result is stable across runs, platforms and rust versions.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv64(u64);
+
+impl Fnv64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    pub fn new() -> Self {
+        Fnv64(Self::OFFSET_BASIS)
+    }
+}
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+/// feeds the serialized representation of `value` to the hasher
+pub(crate) fn hash_serialized<T: Serialize + ?Sized>(value: &T, hasher: &mut dyn Hasher) {
+    struct HashWriter<'a>(&'a mut dyn Hasher);
+    impl<'a> io::Write for HashWriter<'a> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    // serialization of game states never fails, because all map keys are strings or sequences
+    serde_json::to_writer(HashWriter(hasher), value).expect("Failed to serialize game state");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn fnv_known_values() {
+        let hash = |s: &str| {
+            let mut hasher = Fnv64::new();
+            hasher.write(s.as_bytes());
+            hasher.finish()
+        };
+        assert_eq!(hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -55,6 +55,7 @@ impl Default for KeyMap {
             (Key::Char('s'), InputCode::Act(Action::Search)),
             (Key::Char('.'), InputCode::Act(Action::NoOp)),
             (Key::Char('>'), InputCode::Act(Action::DownStair)),
+            (Key::Char('<'), InputCode::Act(Action::UpStair)),
             (Key::Up, InputCode::Act(Action::Move(Up))),
             (Key::Down, InputCode::Act(Action::Move(Down))),
             (Key::Left, InputCode::Act(Action::Move(Left))),
@@ -63,6 +64,18 @@ impl Default for KeyMap {
             (Key::Char('S'), InputCode::Sys(System::Save)),
             (Key::Char('Q'), InputCode::Sys(System::Quit)),
             (Key::Char('i'), InputCode::Sys(System::Inventory)),
+            (Key::Char('e'), InputCode::Sys(System::Eat)),
+            (Key::Char('q'), InputCode::Sys(System::Quaff)),
+            (Key::Char('r'), InputCode::Sys(System::Read)),
+            (Key::Char('P'), InputCode::Sys(System::PutOn)),
+            (Key::Char('R'), InputCode::Sys(System::Remove)),
+            (Key::Char('z'), InputCode::Sys(System::Zap)),
+            (Key::Char('w'), InputCode::Sys(System::Wield)),
+            (Key::Char('W'), InputCode::Sys(System::Wear)),
+            (Key::Char('T'), InputCode::Sys(System::TakeOff)),
+            (Key::Char('d'), InputCode::Sys(System::Drop)),
+            (Key::Char('t'), InputCode::Sys(System::Throw)),
+            (Key::Char('c'), InputCode::Sys(System::Call)),
             (Key::Char(' '), InputCode::Sys(System::Continue)),
         ];
         let inner: HashMap<_, _> = map.into_iter().collect();
@@ -93,6 +106,18 @@ impl KeyMap {
             (Key::Char('B'), InputCode::Act(Action::MoveUntil(LeftDown))),
             (Key::Char('s'), InputCode::Act(Action::Search)),
             (Key::Char('>'), InputCode::Act(Action::DownStair)),
+            (Key::Char('<'), InputCode::Act(Action::UpStair)),
+            (Key::Char('e'), InputCode::Sys(System::Eat)),
+            (Key::Char('q'), InputCode::Sys(System::Quaff)),
+            (Key::Char('r'), InputCode::Sys(System::Read)),
+            (Key::Char('P'), InputCode::Sys(System::PutOn)),
+            (Key::Char('R'), InputCode::Sys(System::Remove)),
+            (Key::Char('z'), InputCode::Sys(System::Zap)),
+            (Key::Char('w'), InputCode::Sys(System::Wield)),
+            (Key::Char('W'), InputCode::Sys(System::Wear)),
+            (Key::Char('T'), InputCode::Sys(System::TakeOff)),
+            (Key::Char('d'), InputCode::Sys(System::Drop)),
+            (Key::Char('t'), InputCode::Sys(System::Throw)),
         ];
         let inner: HashMap<_, _> = map.into_iter().collect();
         KeyMap { inner }
@@ -166,13 +191,39 @@ pub enum InputCode {
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq)]
 pub enum System {
     Cancel,
+    /// opens the prompt to select an item to name
+    Call,
     Continue,
+    /// opens the prompt to select an item to drop
+    Drop,
+    /// opens the prompt to select food
+    Eat,
     Enter,
     Inventory,
     No,
+    /// opens the prompt to select a ring to put on
+    PutOn,
+    /// opens the prompt to select a potion
+    Quaff,
+    /// opens the prompt to select a scroll
+    Read,
+    /// opens the prompt to select a ring to remove
+    Remove,
     Save,
     Quit,
+    /// selects an item by its letter
+    Select(char),
+    /// opens the prompt to select armor to take off
+    TakeOff,
+    /// opens the prompt to select an item to throw
+    Throw,
+    /// opens the prompt to select armor to wear
+    Wear,
+    /// opens the prompt to select a weapon to wield
+    Wield,
     Yes,
+    /// opens the prompt to select a wand
+    Zap,
 }
 
 /// a representation of Keyboard input
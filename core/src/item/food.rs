@@ -1,3 +1,4 @@
+use crate::rng::RngHandle;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -18,3 +19,56 @@ impl fmt::Display for Food {
         }
     }
 }
+
+impl Food {
+    /// turns of food which eating this gives.
+    /// A ration gives about `hunger_time`(`HUNGERTIME - 200 + rnd(400)` in rogue)
+    /// and a slime-mold gives half of it.
+    pub fn nutrition(&self, hunger_time: u32, rng: &mut RngHandle) -> u32 {
+        let base = hunger_time - hunger_time * 2 / 13 + rng.range(0..hunger_time * 4 / 13 + 1);
+        match self {
+            Food::Ration | Food::Custom => base,
+            Food::Slime => base / 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actions::test::runtime;
+    use crate::input::Key;
+    use crate::item::ItemKind;
+    use crate::ui::{ItemAction, MordalKind, UiState};
+    use crate::{GameMsg, Reaction, RunTime};
+    #[test]
+    fn eat() {
+        let mut runtime = runtime();
+        let has_food = |runtime: &RunTime| {
+            runtime
+                .itembox()
+                .items()
+                .any(|item| matches!(item.kind, ItemKind::Food(_)))
+        };
+        assert!(has_food(&runtime));
+        let res = runtime.react_to_key(Key::Char('e')).unwrap();
+        let select = UiState::Mordal(MordalKind::SelectItem(ItemAction::Eat));
+        assert_eq!(res, vec![Reaction::UiTransition(select.clone())]);
+        // gold can't be eaten
+        let res = runtime.react_to_key(Key::Char('a')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantEat)));
+        assert_eq!(runtime.ui, UiState::Dungeon);
+        runtime.react_to_key(Key::Char('e')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Ate(Food::Ration))));
+        assert!(res.contains(&Reaction::StatusUpdated));
+        assert!(!has_food(&runtime));
+        runtime.react_to_key(Key::Char('e')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::InvalidItem)));
+        // cancel
+        runtime.react_to_key(Key::Char('e')).unwrap();
+        runtime.react_to_key(Key::Esc).unwrap();
+        assert_eq!(runtime.ui, UiState::Dungeon);
+    }
+}
@@ -20,13 +20,17 @@ where
     S: 'i + ItemStat,
     I: Iterator<Item = &'i S>,
 {
-    let rate = rng.range(1..100);
-    let mut sum = 0;
-    for (i, p) in iter.enumerate() {
-        if sum < rate && rate <= sum {
+    let rates: Vec<_> = iter.map(|p| p.appear_rate().0).collect();
+    let total: u32 = rates.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let mut rate = rng.range(0..total);
+    for (i, r) in rates.into_iter().enumerate() {
+        if rate < r {
             return i;
         }
-        sum += p.appear_rate().0;
+        rate -= r;
     }
     0
 }
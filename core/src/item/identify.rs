@@ -0,0 +1,403 @@
+//! unidentified names of magic items, which are shuffled per game
+use super::potion::PotionKind;
+use super::ring::{Ring, RingKind};
+use super::scroll::ScrollKind;
+use super::wand::{Wand, WandKind};
+use super::{Item, ItemKind};
+use crate::{rng::RngHandle, smallstr::SmallStr};
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// what the player knows about a kind of magic items
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+struct Appearance<K> {
+    kind: K,
+    /// the name shown before the kind is identified
+    name: SmallStr,
+    is_known: bool,
+    /// the name given by the player
+    called: Option<SmallStr>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+struct Appearances<K>(Vec<Appearance<K>>);
+
+impl<K: IntoEnumIterator + PartialEq> Appearances<K> {
+    fn new(names: impl IntoIterator<Item = SmallStr>) -> Self {
+        let list = K::into_enum_iter()
+            .zip(names)
+            .map(|(kind, name)| Appearance {
+                kind,
+                name,
+                is_known: false,
+                called: None,
+            })
+            .collect();
+        Appearances(list)
+    }
+    fn get(&self, kind: &K) -> Option<&Appearance<K>> {
+        self.0.iter().find(|a| a.kind == *kind)
+    }
+    fn get_mut(&mut self, kind: &K) -> Option<&mut Appearance<K>> {
+        self.0.iter_mut().find(|a| a.kind == *kind)
+    }
+}
+
+/// selects `num` different names from the list
+fn select_names(list: &[&str], num: usize, rng: &mut RngHandle) -> Vec<SmallStr> {
+    rng.select(0..list.len())
+        .take(num)
+        .map(|i| SmallStr::from_str(list[i]))
+        .collect()
+}
+
+/// gibberish title of a scroll, as in rogue
+fn scroll_title(rng: &mut RngHandle) -> SmallStr {
+    let words: Vec<String> = (0..rng.range(2..5))
+        .map(|_| {
+            (0..rng.range(1..4))
+                .map(|_| SYLLABLES[rng.range(0..SYLLABLES.len())])
+                .collect()
+        })
+        .collect();
+    SmallStr::from_string(words.join(" "))
+}
+
+/// the player's knowledge about potions, rings, scrolls and wands
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Knowledge {
+    potions: Appearances<PotionKind>,
+    rings: Appearances<RingKind>,
+    scrolls: Appearances<ScrollKind>,
+    wands: Appearances<WandKind>,
+}
+
+impl Knowledge {
+    pub(super) fn new(rng: &mut RngHandle) -> Self {
+        let potions = select_names(&COLORS, PotionKind::VARIANT_COUNT, rng);
+        let rings = select_names(&STONES, RingKind::VARIANT_COUNT, rng);
+        let scrolls: Vec<_> = (0..ScrollKind::VARIANT_COUNT)
+            .map(|_| scroll_title(rng))
+            .collect();
+        // as in rogue, each kind of sticks is made of either wood or metal
+        let (mut woods, mut metals) = (
+            select_names(&WOODS, WandKind::VARIANT_COUNT, rng).into_iter(),
+            select_names(&METALS, WandKind::VARIANT_COUNT, rng).into_iter(),
+        );
+        let wands: Vec<_> = (0..WandKind::VARIANT_COUNT)
+            .filter_map(|_| {
+                if rng.does_happen(2) {
+                    woods.next()
+                } else {
+                    metals.next()
+                }
+            })
+            .collect();
+        Knowledge {
+            potions: Appearances::new(potions),
+            rings: Appearances::new(rings),
+            scrolls: Appearances::new(scrolls),
+            wands: Appearances::new(wands),
+        }
+    }
+    /// non-magic items are always known
+    pub fn is_known(&self, kind: &ItemKind) -> bool {
+        match kind {
+            ItemKind::Potion(p) => self.potions.get(&p.kind).is_none_or(|a| a.is_known),
+            ItemKind::Ring(r) => self.rings.get(&r.kind).is_none_or(|a| a.is_known),
+            ItemKind::Scroll(s) => self.scrolls.get(&s.kind).is_none_or(|a| a.is_known),
+            ItemKind::Wand(w) => self.wands.get(&w.kind).is_none_or(|a| a.is_known),
+            _ => true,
+        }
+    }
+    pub(crate) fn identify(&mut self, kind: &ItemKind) {
+        let known = match kind {
+            ItemKind::Potion(p) => self.potions.get_mut(&p.kind).map(|a| &mut a.is_known),
+            ItemKind::Ring(r) => self.rings.get_mut(&r.kind).map(|a| &mut a.is_known),
+            ItemKind::Scroll(s) => self.scrolls.get_mut(&s.kind).map(|a| &mut a.is_known),
+            ItemKind::Wand(w) => self.wands.get_mut(&w.kind).map(|a| &mut a.is_known),
+            _ => None,
+        };
+        if let Some(known) = known {
+            *known = true;
+        }
+    }
+    /// names an unknown kind, and returns if it's successfully named
+    pub(crate) fn call(&mut self, kind: &ItemKind, name: SmallStr) -> bool {
+        if self.is_known(kind) {
+            return false;
+        }
+        let called = match kind {
+            ItemKind::Potion(p) => self.potions.get_mut(&p.kind).map(|a| &mut a.called),
+            ItemKind::Ring(r) => self.rings.get_mut(&r.kind).map(|a| &mut a.called),
+            ItemKind::Scroll(s) => self.scrolls.get_mut(&s.kind).map(|a| &mut a.called),
+            ItemKind::Wand(w) => self.wands.get_mut(&w.kind).map(|a| &mut a.called),
+            _ => None,
+        };
+        match called {
+            Some(called) => {
+                *called = Some(name);
+                true
+            }
+            None => false,
+        }
+    }
+    /// the name of the kind the player sees
+    pub fn kind_name<'a>(&'a self, kind: &'a ItemKind) -> KindName<'a> {
+        KindName {
+            knowledge: self,
+            kind,
+        }
+    }
+    /// the name of the item the player sees
+    pub fn item_name<'a>(&'a self, item: &'a Item) -> ItemName<'a> {
+        ItemName {
+            knowledge: self,
+            item,
+        }
+    }
+    /// the name of the ring kind the player sees
+    pub fn ring_name(&self, kind: RingKind) -> String {
+        match self.rings.get(&kind) {
+            Some(a) if a.is_known => format!("ring of {}({})", kind, a.name),
+            Some(a) => match &a.called {
+                Some(called) => format!("ring called {}({})", called, a.name),
+                None => format!("{} ring", a.name),
+            },
+            None => format!("ring of {}", kind),
+        }
+    }
+    fn fmt_ring(&self, ring: &Ring, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.rings.get(&ring.kind) {
+            Some(a) if a.is_known => write!(f, "{}({})", ring, a.name),
+            _ => write!(f, "{}", self.ring_name(ring.kind)),
+        }
+    }
+    fn fmt_wand(&self, wand: &Wand, f: &mut fmt::Formatter) -> fmt::Result {
+        let a = match self.wands.get(&wand.kind) {
+            Some(a) => a,
+            None => return write!(f, "{}", wand),
+        };
+        let stick = if WOODS.contains(&a.name.as_str()) {
+            "staff"
+        } else {
+            "wand"
+        };
+        if a.is_known {
+            let charges = wand.charges();
+            write!(f, "{} of {} [{} charges]", stick, wand.kind, charges)
+        } else if let Some(called) = &a.called {
+            write!(f, "{} called {}({})", stick, called, a.name)
+        } else {
+            write!(f, "{} {}", a.name, stick)
+        }
+    }
+}
+
+/// the name of an item kind shown to the player
+pub struct KindName<'a> {
+    knowledge: &'a Knowledge,
+    kind: &'a ItemKind,
+}
+
+impl fmt::Display for KindName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let knowledge = self.knowledge;
+        match self.kind {
+            ItemKind::Potion(p) => match knowledge.potions.get(&p.kind) {
+                Some(a) if a.is_known => write!(f, "{}({})", p, a.name),
+                Some(a) => match &a.called {
+                    Some(called) => write!(f, "potion called {}({})", called, a.name),
+                    None => write!(f, "{} potion", a.name),
+                },
+                None => write!(f, "{}", p),
+            },
+            ItemKind::Ring(r) => knowledge.fmt_ring(r, f),
+            ItemKind::Scroll(s) => match knowledge.scrolls.get(&s.kind) {
+                Some(a) if a.is_known => write!(f, "{}", s),
+                Some(a) => match &a.called {
+                    Some(called) => write!(f, "scroll called {}", called),
+                    None => write!(f, "scroll titled '{}'", a.name),
+                },
+                None => write!(f, "{}", s),
+            },
+            ItemKind::Wand(w) => knowledge.fmt_wand(w, f),
+            // the enchantment is shown only with the item itself
+            ItemKind::Armor(a) => write!(f, "{}", a.name()),
+            ItemKind::Weapon(w) => write!(f, "{}", w.name()),
+            kind => write!(f, "{}", kind),
+        }
+    }
+}
+
+/// the name of an item shown to the player
+pub struct ItemName<'a> {
+    knowledge: &'a Knowledge,
+    item: &'a Item,
+}
+
+impl fmt::Display for ItemName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let item = self.item;
+        match &item.kind {
+            ItemKind::Armor(_) | ItemKind::Weapon(_) if item.is_known() => {
+                item.fmt_with(f, &item.kind)
+            }
+            kind => item.fmt_with(f, self.knowledge.kind_name(kind)),
+        }
+    }
+}
+
+const COLORS: [&str; 27] = [
+    "amber",
+    "aquamarine",
+    "black",
+    "blue",
+    "brown",
+    "clear",
+    "crimson",
+    "cyan",
+    "ecru",
+    "gold",
+    "green",
+    "grey",
+    "magenta",
+    "orange",
+    "pink",
+    "plaid",
+    "purple",
+    "red",
+    "silver",
+    "tan",
+    "tangerine",
+    "topaz",
+    "turquoise",
+    "vermilion",
+    "violet",
+    "white",
+    "yellow",
+];
+
+const STONES: [&str; 26] = [
+    "agate",
+    "alexandrite",
+    "amethyst",
+    "carnelian",
+    "diamond",
+    "emerald",
+    "germanium",
+    "granite",
+    "garnet",
+    "jade",
+    "kryptonite",
+    "lapis lazuli",
+    "moonstone",
+    "obsidian",
+    "onyx",
+    "opal",
+    "pearl",
+    "peridot",
+    "ruby",
+    "sapphire",
+    "stibotantalite",
+    "tiger eye",
+    "topaz",
+    "turquoise",
+    "taaffeite",
+    "zircon",
+];
+
+const WOODS: [&str; 33] = [
+    "avocado wood",
+    "balsa",
+    "bamboo",
+    "banyan",
+    "birch",
+    "cedar",
+    "cherry",
+    "cinnibar",
+    "cypress",
+    "dogwood",
+    "driftwood",
+    "ebony",
+    "elm",
+    "eucalyptus",
+    "fall",
+    "hemlock",
+    "holly",
+    "ironwood",
+    "kukui wood",
+    "mahogany",
+    "manzanita",
+    "maple",
+    "oaken",
+    "persimmon wood",
+    "pecan",
+    "pine",
+    "poplar",
+    "redwood",
+    "rosewood",
+    "spruce",
+    "teak",
+    "walnut",
+    "zebrawood",
+];
+
+const METALS: [&str; 22] = [
+    "aluminum",
+    "beryllium",
+    "bone",
+    "brass",
+    "bronze",
+    "copper",
+    "electrum",
+    "gold",
+    "iron",
+    "lead",
+    "magnesium",
+    "mercurial",
+    "nickel",
+    "pewter",
+    "platinum",
+    "steel",
+    "silver",
+    "silicon",
+    "tin",
+    "titanium",
+    "tungsten",
+    "zinc",
+];
+
+// syllables for scroll titles, from rogue 5.4.4
+const SYLLABLES: [&str; 147] = [
+    "a", "ab", "ag", "aks", "ala", "an", "app", "arg", "arze", "ash", "bek", "bie", "bit", "bjor",
+    "blu", "bot", "bu", "byt", "comp", "con", "cos", "cre", "dalf", "dan", "den", "do", "e", "eep",
+    "el", "eng", "er", "ere", "erk", "esh", "evs", "fa", "fid", "fri", "fu", "gan", "gar", "glen",
+    "gop", "gre", "ha", "hyd", "i", "ing", "ip", "ish", "it", "ite", "iv", "jo", "kho", "kli",
+    "klis", "la", "lech", "mar", "me", "mi", "mic", "mik", "mon", "mung", "mur", "nej", "nelg",
+    "nep", "ner", "nes", "nes", "nih", "nin", "o", "od", "ood", "org", "orn", "ox", "oxy", "pay",
+    "ple", "plu", "po", "pot", "prok", "re", "rea", "rhov", "ri", "ro", "rog", "rok", "rol", "sa",
+    "san", "sat", "sef", "seh", "shu", "ski", "sna", "sne", "snik", "sno", "so", "sol", "sri",
+    "sta", "sun", "ta", "tab", "tem", "ther", "ti", "tox", "trol", "tue", "turs", "u", "ulk", "um",
+    "un", "uni", "ur", "val", "viv", "vly", "vom", "wah", "wed", "werg", "wex", "whon", "wun",
+    "xo", "y", "yot", "yu", "zant", "zeb", "zim", "zok", "zon", "zum",
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn shuffled_names() {
+        let mut rng = RngHandle::from_seed(1);
+        let knowledge = Knowledge::new(&mut rng);
+        assert_eq!(knowledge.potions.0.len(), PotionKind::VARIANT_COUNT);
+        assert_eq!(knowledge.wands.0.len(), WandKind::VARIANT_COUNT);
+        let mut colors: Vec<_> = knowledge.potions.0.iter().map(|a| &a.name).collect();
+        colors.sort();
+        colors.dedup();
+        assert_eq!(colors.len(), PotionKind::VARIANT_COUNT);
+        // the same seed gives the same names
+        let mut rng = RngHandle::from_seed(1);
+        assert_eq!(knowledge, Knowledge::new(&mut rng));
+    }
+}
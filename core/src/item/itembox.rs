@@ -1,14 +1,16 @@
 //! utility for managing character's items
 use log::debug;
 
-use super::{Item, ItemToken};
+use super::{Item, ItemId, ItemToken};
 use crate::fenwick::FenwickSet;
 use std::collections::BTreeMap;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ItemBox {
     empty_chars: FenwickSet,
     items: BTreeMap<usize, ItemToken>,
+    /// slots of items keyed by their ids
+    slots: BTreeMap<ItemId, usize>,
 }
 
 impl ItemBox {
@@ -16,6 +18,7 @@ impl ItemBox {
         ItemBox {
             empty_chars: FenwickSet::from_range(0..max_len),
             items: BTreeMap::new(),
+            slots: BTreeMap::new(),
         }
     }
     pub fn add(&mut self, item: ItemToken) -> bool {
@@ -41,9 +44,46 @@ impl ItemBox {
     pub fn tokens(&self) -> impl Iterator<Item = &ItemToken> {
         self.items.values()
     }
+    pub fn tokens_mut(&mut self) -> impl Iterator<Item = &mut ItemToken> {
+        self.items.values_mut()
+    }
+    pub fn get(&self, id: ItemId) -> Option<&ItemToken> {
+        self.items.get(self.slots.get(&id)?)
+    }
+    pub fn get_mut(&mut self, id: ItemId) -> Option<&mut ItemToken> {
+        self.items.get_mut(self.slots.get(&id)?)
+    }
     pub fn items(&self) -> impl Iterator<Item = &Item> {
         self.tokens().map(|t| t.get())
     }
+    /// items with their slots, which are shown as 'a', 'b', ... to the player
+    pub fn slots(&self) -> impl Iterator<Item = (usize, &ItemToken)> {
+        self.items.iter().map(|(&slot, token)| (slot, token))
+    }
+    pub fn get_slot(&self, slot: usize) -> Option<&ItemToken> {
+        self.items.get(&slot)
+    }
+    pub fn get_slot_mut(&mut self, slot: usize) -> Option<&mut ItemToken> {
+        self.items.get_mut(&slot)
+    }
+    /// takes one item out of the slot, removing the slot if it becomes empty
+    pub fn take_one(&mut self, slot: usize) -> Option<Item> {
+        let token = self.items.get_mut(&slot)?;
+        let mut item = token.get_cloned();
+        item.how_many = 1.into();
+        token.get_mut().how_many -= 1.into();
+        if token.how_many.0 == 0 {
+            self.remove(slot);
+        }
+        Some(item)
+    }
+    /// removes the slot
+    pub fn remove(&mut self, slot: usize) -> Option<ItemToken> {
+        let token = self.items.remove(&slot)?;
+        self.slots.remove(&token.id());
+        self.empty_chars.insert(slot);
+        Some(token)
+    }
     pub fn find_by(&self, mut query: impl FnMut(&Item) -> bool) -> Option<&ItemToken> {
         self.items
             .iter()
@@ -58,6 +98,7 @@ impl ItemBox {
             .map(|t| *t.0)
     }
     fn insert(&mut self, ch: usize, item: ItemToken) {
+        self.slots.insert(item.id(), ch);
         self.items.insert(ch, item);
         self.empty_chars.remove(ch);
     }
@@ -89,3 +130,25 @@ impl<'a> InsertEntry<'a> {
         res
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::item::{Config, ItemHandler, ItemKind};
+    #[test]
+    fn get_by_id() {
+        let mut handler = ItemHandler::new(Config::default(), 1);
+        let mut itembox = ItemBox::with_capacity(3);
+        let tokens: Vec<_> = (0..3)
+            .map(|_| handler.gen_item(Item::new(ItemKind::Gold, 1)))
+            .collect();
+        let ids: Vec<_> = tokens.iter().map(|t| t.id()).collect();
+        tokens.into_iter().for_each(|t| assert!(itembox.add(t)));
+        assert_eq!(itembox.get(ids[1]).map(|t| t.id()), Some(ids[1]));
+        assert_eq!(itembox.remove(1).map(|t| t.id()), Some(ids[1]));
+        assert!(itembox.get(ids[1]).is_none());
+        assert!(itembox.take_one(2).is_some());
+        assert!(itembox.get(ids[2]).is_none());
+        assert_eq!(itembox.get(ids[0]).map(|t| t.id()), Some(ids[0]));
+    }
+}
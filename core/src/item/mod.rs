@@ -3,69 +3,96 @@ pub mod armor;
 pub mod food;
 mod gold;
 mod handler;
+pub mod identify;
 pub mod itembox;
+pub mod potion;
+pub mod ring;
+pub mod scroll;
+pub mod wand;
 pub mod weapon;
 
 use self::armor::{Armor, ArmorStatus};
 use self::food::Food;
 use self::handler::Handler;
-use self::handler::ItemStat;
+use self::handler::{ItemInner, ItemStat};
+use self::identify::Knowledge;
 pub use self::itembox::ItemBox;
+use self::potion::{Potion, PotionStatus};
+use self::ring::{Ring, RingStatus};
+use self::scroll::{Scroll, ScrollStatus};
+use self::wand::{Wand, WandStatus};
 use self::weapon::{Weapon, WeaponStatus};
-use crate::character::{Dice, HitPoint, Level};
+use crate::character::{Defense, Dice, HitPoint, Level};
 use crate::tile::{Drawable, Tile};
 use crate::{error::*, rng::RngHandle, smallstr::SmallStr};
 use anyhow::bail;
+use std::fmt;
 use std::ops::{Deref, DerefMut};
-use std::rc::{Rc, Weak};
-use std::{cell::UnsafeCell, collections::BTreeMap, fmt};
 
 /// Item configuration
 #[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Config {
     armor: armor::Config,
     gold: gold::Config,
+    #[serde(default)]
+    potion: potion::Config,
+    #[serde(default)]
+    ring: ring::Config,
+    #[serde(default)]
+    scroll: scroll::Config,
+    #[serde(default)]
+    wand: wand::Config,
     weapon: weapon::Config,
 }
 
 /// item tag
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ItemKind {
+    /// the Amulet of Yendor
+    Amulet,
     Armor(Armor),
     Food(Food),
     Gold,
-    Potion,
-    Ring,
-    Scroll,
-    Wand,
+    Potion(Potion),
+    Ring(Ring),
+    Scroll(Scroll),
+    Wand(Wand),
     Weapon(Weapon),
 }
 
 impl ItemKind {
     /// construct item from ItemNum & default attribute setting
-    pub fn numbered(self, num: ItemNum) -> Item {
+    pub fn numbered(self, num: ItemNum) -> GameResult<Item> {
         let attr = match self {
-            ItemKind::Gold => ItemAttr::empty(),
-            _ => unimplemented!(),
+            ItemKind::Gold | ItemKind::Amulet => ItemAttr::empty(),
+            ItemKind::Armor(_) | ItemKind::Ring(_) => ItemAttr::empty(),
+            ItemKind::Food(_) | ItemKind::Scroll(_) => ItemAttr::IS_MANY,
+            ItemKind::Potion(_) => ItemAttr::IS_MANY.merge(ItemAttr::CAN_THROW),
+            ItemKind::Wand(_) => ItemAttr::CAN_THROW,
+            // attributes of a weapon depend on its status, e.g., arrows are thrown and darts aren't
+            ItemKind::Weapon(_) => bail!(ErrorKind::MaybeBug(
+                "[ItemKind::numbered] weapons have no default attribute"
+            )),
         };
-        Item {
+        Ok(Item {
             kind: self,
             how_many: num,
             attr,
-        }
+        })
     }
 }
 
 impl Drawable for ItemKind {
     fn tile(&self) -> Tile {
         match *self {
+            ItemKind::Amulet => b',',
             ItemKind::Armor(_) => b']',
             ItemKind::Food(_) => b':',
             ItemKind::Gold => b'*',
-            ItemKind::Potion => b'!',
-            ItemKind::Ring => b'=',
-            ItemKind::Scroll => b'?',
-            ItemKind::Wand => b'/',
+            ItemKind::Potion(_) => b'!',
+            ItemKind::Ring(_) => b'=',
+            ItemKind::Scroll(_) => b'?',
+            ItemKind::Wand(_) => b'/',
             ItemKind::Weapon(_) => b')',
         }
         .into()
@@ -127,6 +154,10 @@ impl ItemAttr {
     /// we can merge 2 sets of the item or not
     pub const IS_MANY: ItemAttr = ItemAttr(0b00_000_100);
     pub const IS_EQUIPPED: ItemAttr = ItemAttr(0b00_001_000);
+    /// the armor is protected from rust
+    pub const IS_PROTECTED: ItemAttr = ItemAttr(0b00_010_000);
+    /// the player knows the enchantment of the weapon or armor
+    pub const IS_KNOWN: ItemAttr = ItemAttr(0b00_100_000);
 }
 
 impl ItemAttr {
@@ -145,6 +176,9 @@ impl ItemAttr {
     pub fn or(&mut self, other: ItemAttr) {
         self.0 |= other.0;
     }
+    pub fn remove(&mut self, other: ItemAttr) {
+        self.0 &= !other.0;
+    }
     pub fn equip(&mut self) {
         self.0 |= ItemAttr::IS_EQUIPPED.0;
     }
@@ -194,7 +228,7 @@ impl InitItem {
                     weapon.dam_plus += dam_plus.into();
                     Item {
                         kind: ItemKind::Weapon(weapon),
-                        attr,
+                        attr: attr.merge(ItemAttr::IS_KNOWN),
                         how_many: num + num_plus.into(),
                     }
                 })
@@ -206,7 +240,7 @@ impl InitItem {
                     armor.def_plus += def_plus.into();
                     Item {
                         kind: ItemKind::Armor(armor),
-                        attr,
+                        attr: attr.merge(ItemAttr::IS_KNOWN),
                         how_many: num,
                     }
                 })
@@ -251,6 +285,12 @@ impl Item {
     pub fn is_many(&self) -> bool {
         self.attr.contains(ItemAttr::IS_MANY)
     }
+    pub fn is_equipped(&self) -> bool {
+        self.attr.is_equiped()
+    }
+    pub fn is_cursed(&self) -> bool {
+        self.attr.contains(ItemAttr::IS_CURSED)
+    }
     pub fn hit_plus(&self) -> Level {
         match &self.kind {
             ItemKind::Weapon(w) => w.hit_plus,
@@ -263,6 +303,45 @@ impl Item {
             _ => HitPoint(0),
         }
     }
+    /// the Amulet, potions, rings, scrolls and wands are magic
+    pub fn is_magic(&self) -> bool {
+        matches!(
+            self.kind,
+            ItemKind::Amulet
+                | ItemKind::Potion(_)
+                | ItemKind::Ring(_)
+                | ItemKind::Scroll(_)
+                | ItemKind::Wand(_)
+        )
+    }
+    pub fn is_known(&self) -> bool {
+        self.attr.contains(ItemAttr::IS_KNOWN)
+    }
+    /// weakens the armor by 1 unless it's protected, and returns if it rusted
+    pub fn rust(&mut self) -> bool {
+        if self.attr.contains(ItemAttr::IS_PROTECTED) {
+            return false;
+        }
+        match &mut self.kind {
+            // as in rogue, leather armor never rusts
+            ItemKind::Armor(a) if a.name() != "leather armor" => {
+                a.def_plus -= Defense(1);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// enchants the armor or weapon by +1, and removes its curse
+    pub(crate) fn enchant(&mut self, rng: &mut RngHandle) -> bool {
+        match &mut self.kind {
+            ItemKind::Armor(a) => a.def_plus += Defense(1),
+            ItemKind::Weapon(w) if rng.does_happen(2) => w.hit_plus += Level(1),
+            ItemKind::Weapon(w) => w.dam_plus += HitPoint(1),
+            _ => return false,
+        }
+        self.attr.remove(ItemAttr::IS_CURSED);
+        true
+    }
     pub fn name(&self) -> Option<&str> {
         match &self.kind {
             ItemKind::Armor(a) => Some(a.name()),
@@ -302,23 +381,31 @@ impl Drawable for Item {
     }
 }
 
-impl fmt::Display for Item {
+impl fmt::Display for ItemKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ItemKind::Amulet => write!(f, "Amulet of Yendor"),
+            ItemKind::Armor(armor) => write!(f, "{}", armor),
+            ItemKind::Food(food) => write!(f, "{}", food),
+            ItemKind::Gold => write!(f, "golds"),
+            ItemKind::Potion(p) => write!(f, "{}", p),
+            ItemKind::Ring(r) => write!(f, "{}", r),
+            ItemKind::Scroll(s) => write!(f, "{}", s),
+            ItemKind::Wand(w) => write!(f, "{}", w),
+            ItemKind::Weapon(w) => write!(f, "{}", w),
+        }
+    }
+}
+
+impl Item {
+    /// writes the item with the given name of its kind
+    fn fmt_with(&self, f: &mut fmt::Formatter, kind: impl fmt::Display) -> fmt::Result {
         if self.how_many == ItemNum(1) {
             write!(f, "A ")?;
         } else {
             write!(f, "{} ", self.how_many.0)?;
         }
-        match &self.kind {
-            ItemKind::Armor(armor) => write!(f, "{}", armor),
-            ItemKind::Food(food) => write!(f, "{}", food),
-            ItemKind::Gold => write!(f, "golds"),
-            ItemKind::Potion => write!(f, "potion"), // STUB
-            ItemKind::Ring => write!(f, "ring"),     // STUB
-            ItemKind::Scroll => write!(f, "scroll"), // STUB
-            ItemKind::Wand => write!(f, "wand"),     // STUB
-            ItemKind::Weapon(w) => write!(f, "{}", w),
-        }?;
+        write!(f, "{}", kind)?;
         if self.attr.is_equiped() {
             write!(f, " [equipped]")?;
         }
@@ -326,52 +413,62 @@ impl fmt::Display for Item {
     }
 }
 
-#[derive(Clone, Debug)]
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, &self.kind)
+    }
+}
+
+/// Item with its unique id
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ItemToken {
-    inner: Rc<UnsafeCell<Item>>,
+    item: Item,
     id: ItemId,
 }
 
 impl Deref for ItemToken {
     type Target = Item;
     fn deref(&self) -> &Item {
-        self.get()
+        &self.item
     }
 }
 
 impl DerefMut for ItemToken {
     fn deref_mut(&mut self) -> &mut Item {
-        self.get_mut()
+        &mut self.item
     }
 }
 
 impl ItemToken {
     #[inline(always)]
     pub fn get(&self) -> &Item {
-        unsafe { &*UnsafeCell::get(&self.inner) }
+        &self.item
     }
     #[inline(always)]
     pub fn get_mut(&mut self) -> &mut Item {
-        unsafe { &mut *UnsafeCell::get(&self.inner) }
+        &mut self.item
     }
     pub fn get_cloned(&self) -> Item {
-        self.get().clone()
+        self.item.clone()
     }
     /// Returns the unique id of item
     pub fn id(&self) -> ItemId {
-        self.id.clone()
+        self.id
     }
 }
 
 /// generate and management all items
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ItemHandler {
-    /// stores all items in the game
-    /// only for save/load
-    items: BTreeMap<ItemId, Weak<UnsafeCell<Item>>>,
     config: Config,
     rng: RngHandle,
     armor_handle: Handler<ArmorStatus>,
+    potion_handle: Handler<PotionStatus>,
+    ring_handle: Handler<RingStatus>,
+    scroll_handle: Handler<ScrollStatus>,
+    wand_handle: Handler<WandStatus>,
     weapon_handle: Handler<WeaponStatus>,
+    knowledge: Knowledge,
     next_id: ItemId,
 }
 
@@ -382,31 +479,67 @@ impl ItemHandler {
         let Config {
             armor,
             gold: _,
+            potion,
+            ring,
+            scroll,
+            wand,
             weapon,
         } = config_;
         ItemHandler {
-            items: BTreeMap::new(),
             config,
             rng: RngHandle::from_seed(seed),
             armor_handle: armor.build(),
+            potion_handle: potion.build(),
+            ring_handle: ring.build(),
+            scroll_handle: scroll.build(),
+            wand_handle: wand.build(),
             weapon_handle: weapon.build(),
+            knowledge: Knowledge::new(&mut RngHandle::from_seed(seed)),
             next_id: ItemId(0),
         }
     }
-    /// generate and register an item
-    fn gen_item(&mut self, item: Item) -> ItemToken {
+    /// what the player knows about magic items
+    pub fn knowledge(&self) -> &Knowledge {
+        &self.knowledge
+    }
+    pub(crate) fn knowledge_mut(&mut self) -> &mut Knowledge {
+        &mut self.knowledge
+    }
+    /// generate an item with a new id
+    pub(crate) fn gen_item(&mut self, item: Item) -> ItemToken {
         let id = self.next_id;
         debug!("[gen_item] now new item {:?} is generated", item);
-        // register the generated item
-        let item_rc = Rc::new(UnsafeCell::new(item));
-        self.items.insert(id, Rc::downgrade(&item_rc));
         self.next_id.increment();
-        ItemToken { inner: item_rc, id }
+        ItemToken { item, id }
     }
     /// Sets up gold for 1 room
     pub fn setup_gold(&mut self, level: u32) -> Option<ItemToken> {
         let num = self.config.gold.gen(&mut self.rng, level)?;
-        Some(self.gen_item(ItemKind::Gold.numbered(num).many()))
+        Some(self.gen_item(Item::new(ItemKind::Gold, num).many()))
+    }
+    /// Generates an item put on the floor
+    pub fn gen_floor_item(&mut self) -> ItemToken {
+        // as in rogue, potions : scrolls : rings : wands : weapons : armors = 26 : 36 : 4 : 4 : 7 : 7
+        let item = match self.rng.range(0..84) {
+            x if x < 26 => self.potion_handle.gen_item(&mut self.rng),
+            x if x < 62 => self.scroll_handle.gen_item(&mut self.rng),
+            x if x < 66 => self.ring_handle.gen_item(&mut self.rng),
+            x if x < 70 => self.wand_handle.gen_item(&mut self.rng),
+            x if x < 77 => self.weapon_handle.gen_item(&mut self.rng),
+            _ => self.armor_handle.gen_item(&mut self.rng),
+        };
+        self.gen_item(item)
+    }
+    /// Generates the Amulet of Yendor
+    /// generates one weapon by its name, as the arrow shot by a trap
+    pub(crate) fn gen_weapon(&mut self, name: &str) -> Option<ItemToken> {
+        let (weapon, attr, _) = self
+            .weapon_handle
+            .gen_item_by(|stat| stat.name() == name, &mut self.rng)?;
+        Some(self.gen_item(weapon.into_item(attr, ItemNum(1))))
+    }
+    pub fn gen_amulet(&mut self) -> ItemToken {
+        self.gen_item(Item::new(ItemKind::Amulet, 1))
     }
     /// Sets up player items
     pub fn init_player_items(&mut self, pack: &mut ItemBox, items: &[InitItem]) -> GameResult<()> {
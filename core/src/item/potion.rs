@@ -0,0 +1,203 @@
+use super::handler::{Handler, ItemInner, ItemStat};
+use super::{Item, ItemAttr, ItemKind, ItemNum};
+use crate::rng::{Parcent, RngHandle};
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// Potion configuration
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_potions")]
+    pub potions: Vec<Preset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            potions: default_potions(),
+        }
+    }
+}
+
+impl Config {
+    pub(super) fn build(self) -> Handler<PotionStatus> {
+        Handler {
+            // potions are never cursed or powered up
+            cursed_rate: Parcent(0),
+            powerup_rate: Parcent(0),
+            stats: self.potions.into_iter().map(Preset::build).collect(),
+        }
+    }
+}
+
+fn default_potions() -> Vec<Preset> {
+    (0..BUILTIN_POTIONS.len()).map(Preset::Builtin).collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase", untagged)]
+pub enum Preset {
+    Builtin(usize),
+    Custom(PotionStatus),
+}
+
+impl Preset {
+    fn build(self) -> PotionStatus {
+        match self {
+            Preset::Builtin(i) => BUILTIN_POTIONS[i].clone(),
+            Preset::Custom(v) => v,
+        }
+    }
+}
+
+/// effects of potions
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, IntoEnumIterator)]
+pub enum PotionKind {
+    Confusion,
+    Poison,
+    GainStrength,
+    SeeInvisible,
+    Healing,
+    DetectMonsters,
+    DetectMagic,
+    RaiseLevel,
+    ExtraHealing,
+    Haste,
+    RestoreStrength,
+    Blindness,
+}
+
+impl PotionKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            PotionKind::Confusion => "confusion",
+            PotionKind::Poison => "poison",
+            PotionKind::GainStrength => "gain strength",
+            PotionKind::SeeInvisible => "see invisible",
+            PotionKind::Healing => "healing",
+            PotionKind::DetectMonsters => "monster detection",
+            PotionKind::DetectMagic => "magic detection",
+            PotionKind::RaiseLevel => "raise level",
+            PotionKind::ExtraHealing => "extra healing",
+            PotionKind::Haste => "haste self",
+            PotionKind::RestoreStrength => "restore strength",
+            PotionKind::Blindness => "blindness",
+        }
+    }
+}
+
+impl fmt::Display for PotionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Potion {
+    pub kind: PotionKind,
+    worth: ItemNum,
+}
+
+impl ItemInner for Potion {
+    fn into_item(self, attr: ItemAttr, how_many: ItemNum) -> Item {
+        Item {
+            kind: ItemKind::Potion(self),
+            attr,
+            how_many,
+        }
+    }
+}
+
+impl fmt::Display for Potion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "potion of {}", self.kind)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PotionStatus {
+    kind: PotionKind,
+    appear_rate: Parcent,
+    worth: ItemNum,
+}
+
+impl ItemStat for PotionStatus {
+    type Item = Potion;
+    fn appear_rate(&self) -> Parcent {
+        self.appear_rate
+    }
+    fn build(self, _rng: &mut RngHandle) -> (Potion, ItemAttr, ItemNum) {
+        let potion = Potion {
+            kind: self.kind,
+            worth: self.worth,
+        };
+        (
+            potion,
+            ItemAttr::IS_MANY.merge(ItemAttr::CAN_THROW),
+            1.into(),
+        )
+    }
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+    fn worth(&self) -> ItemNum {
+        self.worth
+    }
+}
+
+macro_rules! potion {
+    ($kind: ident, $rate: expr, $worth: expr) => {
+        PotionStatus {
+            kind: PotionKind::$kind,
+            appear_rate: Parcent($rate),
+            worth: ItemNum($worth),
+        }
+    };
+}
+
+// appear rates and worths are same as rogue 5.4.4, except hallucination and levitation
+const BUILTIN_POTIONS: [PotionStatus; 12] = [
+    potion!(Confusion, 7, 5),
+    potion!(Poison, 8, 5),
+    potion!(GainStrength, 13, 150),
+    potion!(SeeInvisible, 3, 100),
+    potion!(Healing, 13, 130),
+    potion!(DetectMonsters, 6, 130),
+    potion!(DetectMagic, 6, 105),
+    potion!(RaiseLevel, 2, 250),
+    potion!(ExtraHealing, 5, 200),
+    potion!(Haste, 5, 190),
+    potion!(RestoreStrength, 13, 130),
+    potion!(Blindness, 5, 5),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actions::test::{give_item, runtime, slot_key};
+    use crate::input::Key;
+    use crate::ui::{ItemAction, MordalKind, UiState};
+    use crate::{GameMsg, Reaction};
+    #[test]
+    fn quaff() {
+        let mut runtime = runtime();
+        let slot = give_item(&mut runtime, |kind| matches!(kind, ItemKind::Potion(_)));
+        let kind = match &runtime.player.itembox.get_slot(slot).unwrap().kind {
+            ItemKind::Potion(p) => p.kind,
+            _ => unreachable!(),
+        };
+        // food can't be quaffed
+        runtime.react_to_key(Key::Char('q')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantQuaff)));
+        let res = runtime.react_to_key(Key::Char('q')).unwrap();
+        let select = UiState::Mordal(MordalKind::SelectItem(ItemAction::Quaff));
+        assert_eq!(res, vec![Reaction::UiTransition(select)]);
+        let res = runtime.react_to_key(slot_key(slot)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Quaffed(kind))));
+        assert!(!runtime
+            .itembox()
+            .items()
+            .any(|item| matches!(item.kind, ItemKind::Potion(_))));
+    }
+}
@@ -0,0 +1,279 @@
+use super::handler::{Handler, ItemInner, ItemStat};
+use super::{Item, ItemAttr, ItemKind, ItemNum};
+use crate::rng::{Parcent, RngHandle};
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// Ring configuration
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_rings")]
+    pub rings: Vec<Preset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            rings: default_rings(),
+        }
+    }
+}
+
+impl Config {
+    pub(super) fn build(self) -> Handler<RingStatus> {
+        Handler {
+            // curses of rings are decided by their kinds and pluses
+            cursed_rate: Parcent(0),
+            powerup_rate: Parcent(0),
+            stats: self.rings.into_iter().map(Preset::build).collect(),
+        }
+    }
+}
+
+fn default_rings() -> Vec<Preset> {
+    (0..BUILTIN_RINGS.len()).map(Preset::Builtin).collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase", untagged)]
+pub enum Preset {
+    Builtin(usize),
+    Custom(RingStatus),
+}
+
+impl Preset {
+    fn build(self) -> RingStatus {
+        match self {
+            Preset::Builtin(i) => BUILTIN_RINGS[i].clone(),
+            Preset::Custom(v) => v,
+        }
+    }
+}
+
+/// effects of rings
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, IntoEnumIterator)]
+pub enum RingKind {
+    Protection,
+    AddStrength,
+    SustainStrength,
+    Searching,
+    SeeInvisible,
+    Dexterity,
+    IncreaseDamage,
+    Regeneration,
+    SlowDigestion,
+    Teleportation,
+    Stealth,
+    MaintainArmor,
+}
+
+impl RingKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            RingKind::Protection => "protection",
+            RingKind::AddStrength => "add strength",
+            RingKind::SustainStrength => "sustain strength",
+            RingKind::Searching => "searching",
+            RingKind::SeeInvisible => "see invisible",
+            RingKind::Dexterity => "dexterity",
+            RingKind::IncreaseDamage => "increase damage",
+            RingKind::Regeneration => "regeneration",
+            RingKind::SlowDigestion => "slow digestion",
+            RingKind::Teleportation => "teleportation",
+            RingKind::Stealth => "stealth",
+            RingKind::MaintainArmor => "maintain armor",
+        }
+    }
+    /// rings which have pluses
+    pub fn has_plus(self) -> bool {
+        matches!(
+            self,
+            RingKind::Protection
+                | RingKind::AddStrength
+                | RingKind::Dexterity
+                | RingKind::IncreaseDamage
+        )
+    }
+    /// food consumption per turn, as `ring_eat` in rogue.
+    /// Negative values `-n` mean that it consumes 1 food with a probability of 1/n.
+    fn food_use(self) -> i64 {
+        match self {
+            RingKind::Protection
+            | RingKind::AddStrength
+            | RingKind::SustainStrength
+            | RingKind::Stealth
+            | RingKind::MaintainArmor => 1,
+            RingKind::Searching | RingKind::Dexterity | RingKind::IncreaseDamage => -3,
+            RingKind::SeeInvisible => -5,
+            RingKind::Regeneration => 2,
+            RingKind::SlowDigestion => -2,
+            RingKind::Teleportation => 0,
+        }
+    }
+}
+
+impl fmt::Display for RingKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Ring {
+    pub kind: RingKind,
+    pub(super) plus: i64,
+    worth: ItemNum,
+}
+
+impl Ring {
+    pub fn plus(&self) -> i64 {
+        self.plus
+    }
+    /// food consumed by wearing this ring in a turn
+    pub fn eat(&self, rng: &mut RngHandle) -> i64 {
+        let eat = match self.kind.food_use() {
+            x if x < 0 => i64::from(rng.range(0..-x) == 0),
+            x => x,
+        };
+        if self.kind == RingKind::SlowDigestion {
+            -eat
+        } else {
+            eat
+        }
+    }
+}
+
+impl ItemInner for Ring {
+    fn into_item(self, attr: ItemAttr, how_many: ItemNum) -> Item {
+        Item {
+            kind: ItemKind::Ring(self),
+            attr,
+            how_many,
+        }
+    }
+}
+
+impl fmt::Display for Ring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ring of {}", self.kind)?;
+        if self.kind.has_plus() {
+            write!(f, " [")?;
+            super::display_plus_types(self.plus, f)?;
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RingStatus {
+    kind: RingKind,
+    appear_rate: Parcent,
+    worth: ItemNum,
+}
+
+impl ItemStat for RingStatus {
+    type Item = Ring;
+    fn appear_rate(&self) -> Parcent {
+        self.appear_rate
+    }
+    fn build(self, rng: &mut RngHandle) -> (Ring, ItemAttr, ItemNum) {
+        let mut attr = ItemAttr::empty();
+        // as in rogue, a ring with plus is cursed if its plus is negative
+        let plus = if self.kind.has_plus() {
+            match rng.range(0..3) {
+                0 => -1,
+                x => x,
+            }
+        } else {
+            0
+        };
+        if plus < 0 || self.kind == RingKind::Teleportation {
+            attr.or(ItemAttr::IS_CURSED);
+        }
+        let ring = Ring {
+            kind: self.kind,
+            plus,
+            worth: self.worth,
+        };
+        (ring, attr, 1.into())
+    }
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+    fn worth(&self) -> ItemNum {
+        self.worth
+    }
+}
+
+macro_rules! ring {
+    ($kind: ident, $rate: expr, $worth: expr) => {
+        RingStatus {
+            kind: RingKind::$kind,
+            appear_rate: Parcent($rate),
+            worth: ItemNum($worth),
+        }
+    };
+}
+
+// appear rates and worths are same as rogue 5.4.4, except adornment and aggravate monster
+const BUILTIN_RINGS: [RingStatus; 12] = [
+    ring!(Protection, 9, 400),
+    ring!(AddStrength, 9, 400),
+    ring!(SustainStrength, 5, 280),
+    ring!(Searching, 10, 420),
+    ring!(SeeInvisible, 10, 310),
+    ring!(Dexterity, 8, 440),
+    ring!(IncreaseDamage, 8, 400),
+    ring!(Regeneration, 4, 460),
+    ring!(SlowDigestion, 9, 240),
+    ring!(Teleportation, 5, 30),
+    ring!(Stealth, 7, 470),
+    ring!(MaintainArmor, 5, 380),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actions::test::{give_item, runtime, slot_key};
+    use crate::input::Key;
+    use crate::{GameMsg, Reaction};
+    #[test]
+    fn rings() {
+        let mut runtime = runtime();
+        let is_ring = |kind: RingKind| move |item: &ItemKind| matches!(item, ItemKind::Ring(r) if r.kind == kind && r.plus() >= 0);
+        let teleport = give_item(&mut runtime, is_ring(RingKind::Teleportation));
+        let protect = give_item(&mut runtime, is_ring(RingKind::Protection));
+        let search = give_item(&mut runtime, is_ring(RingKind::Searching));
+        // food isn't a ring
+        runtime.react_to_key(Key::Char('P')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantPutOn)));
+        let arm = runtime.player.arm();
+        runtime.react_to_key(Key::Char('P')).unwrap();
+        let res = runtime.react_to_key(slot_key(protect)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::PutOnRing(RingKind::Protection))));
+        assert!(runtime.player.arm() > arm);
+        runtime.react_to_key(Key::Char('P')).unwrap();
+        let res = runtime.react_to_key(slot_key(protect)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::AlreadyWearing)));
+        runtime.react_to_key(Key::Char('P')).unwrap();
+        runtime.react_to_key(slot_key(teleport)).unwrap();
+        runtime.react_to_key(Key::Char('P')).unwrap();
+        let res = runtime.react_to_key(slot_key(search)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::NoFreeHand)));
+        runtime.react_to_key(Key::Char('R')).unwrap();
+        let res = runtime.react_to_key(slot_key(search)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::NotWearing)));
+        // a ring of teleportation is always cursed
+        runtime.react_to_key(Key::Char('R')).unwrap();
+        let res = runtime.react_to_key(slot_key(teleport)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Cursed)));
+        runtime.react_to_key(Key::Char('R')).unwrap();
+        let res = runtime.react_to_key(slot_key(protect)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::RemovedRing(
+            RingKind::Protection
+        ))));
+        assert_eq!(runtime.player.arm(), arm);
+    }
+}
@@ -0,0 +1,235 @@
+use super::handler::{Handler, ItemInner, ItemStat};
+use super::{Item, ItemAttr, ItemKind, ItemNum};
+use crate::rng::{Parcent, RngHandle};
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// Scroll configuration
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_scrolls")]
+    pub scrolls: Vec<Preset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            scrolls: default_scrolls(),
+        }
+    }
+}
+
+impl Config {
+    pub(super) fn build(self) -> Handler<ScrollStatus> {
+        Handler {
+            // scrolls are never cursed or powered up
+            cursed_rate: Parcent(0),
+            powerup_rate: Parcent(0),
+            stats: self.scrolls.into_iter().map(Preset::build).collect(),
+        }
+    }
+}
+
+fn default_scrolls() -> Vec<Preset> {
+    (0..BUILTIN_SCROLLS.len()).map(Preset::Builtin).collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase", untagged)]
+pub enum Preset {
+    Builtin(usize),
+    Custom(ScrollStatus),
+}
+
+impl Preset {
+    fn build(self) -> ScrollStatus {
+        match self {
+            Preset::Builtin(i) => BUILTIN_SCROLLS[i].clone(),
+            Preset::Custom(v) => v,
+        }
+    }
+}
+
+/// effects of scrolls
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, IntoEnumIterator)]
+pub enum ScrollKind {
+    Identify,
+    EnchantArmor,
+    EnchantWeapon,
+    RemoveCurse,
+    Teleportation,
+    MagicMapping,
+    HoldMonster,
+    Sleep,
+    ScareMonster,
+    CreateMonster,
+    FoodDetection,
+    AggravateMonsters,
+    ProtectArmor,
+}
+
+impl ScrollKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScrollKind::Identify => "identify",
+            ScrollKind::EnchantArmor => "enchant armor",
+            ScrollKind::EnchantWeapon => "enchant weapon",
+            ScrollKind::RemoveCurse => "remove curse",
+            ScrollKind::Teleportation => "teleportation",
+            ScrollKind::MagicMapping => "magic mapping",
+            ScrollKind::HoldMonster => "hold monster",
+            ScrollKind::Sleep => "sleep",
+            ScrollKind::ScareMonster => "scare monster",
+            ScrollKind::CreateMonster => "create monster",
+            ScrollKind::FoodDetection => "food detection",
+            ScrollKind::AggravateMonsters => "aggravate monsters",
+            ScrollKind::ProtectArmor => "protect armor",
+        }
+    }
+}
+
+impl fmt::Display for ScrollKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Scroll {
+    pub kind: ScrollKind,
+    worth: ItemNum,
+}
+
+impl ItemInner for Scroll {
+    fn into_item(self, attr: ItemAttr, how_many: ItemNum) -> Item {
+        Item {
+            kind: ItemKind::Scroll(self),
+            attr,
+            how_many,
+        }
+    }
+}
+
+impl fmt::Display for Scroll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "scroll of {}", self.kind)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ScrollStatus {
+    kind: ScrollKind,
+    appear_rate: Parcent,
+    worth: ItemNum,
+}
+
+impl ItemStat for ScrollStatus {
+    type Item = Scroll;
+    fn appear_rate(&self) -> Parcent {
+        self.appear_rate
+    }
+    fn build(self, _rng: &mut RngHandle) -> (Scroll, ItemAttr, ItemNum) {
+        let scroll = Scroll {
+            kind: self.kind,
+            worth: self.worth,
+        };
+        (scroll, ItemAttr::IS_MANY, 1.into())
+    }
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+    fn worth(&self) -> ItemNum {
+        self.worth
+    }
+}
+
+macro_rules! scroll {
+    ($kind: ident, $rate: expr, $worth: expr) => {
+        ScrollStatus {
+            kind: ScrollKind::$kind,
+            appear_rate: Parcent($rate),
+            worth: ItemNum($worth),
+        }
+    };
+}
+
+// appear rates and worths are based on rogue 5.4.4, where 5 identify scrolls are merged into
+// one and confuse monster is not implemented
+const BUILTIN_SCROLLS: [ScrollStatus; 13] = [
+    scroll!(Identify, 43, 115),
+    scroll!(EnchantArmor, 7, 130),
+    scroll!(EnchantWeapon, 8, 150),
+    scroll!(RemoveCurse, 7, 105),
+    scroll!(Teleportation, 5, 165),
+    scroll!(MagicMapping, 4, 150),
+    scroll!(HoldMonster, 2, 180),
+    scroll!(Sleep, 3, 5),
+    scroll!(ScareMonster, 3, 200),
+    scroll!(CreateMonster, 4, 75),
+    scroll!(FoodDetection, 2, 60),
+    scroll!(AggravateMonsters, 3, 20),
+    scroll!(ProtectArmor, 2, 250),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actions::test::{give_item, runtime, slot_key};
+    use crate::input::Key;
+    use crate::ui::{ItemAction, MordalKind, UiState};
+    use crate::{GameMsg, Reaction};
+    #[test]
+    fn read() {
+        let mut runtime = runtime();
+        let is_teleport = |kind: &ItemKind| match kind {
+            ItemKind::Scroll(s) => s.kind == ScrollKind::Teleportation,
+            _ => false,
+        };
+        let slot = give_item(&mut runtime, is_teleport);
+        // food can't be read
+        runtime.react_to_key(Key::Char('r')).unwrap();
+        let res = runtime.react_to_key(Key::Char('b')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantRead)));
+        let pos = runtime.player.pos.clone();
+        runtime.react_to_key(Key::Char('r')).unwrap();
+        let res = runtime.react_to_key(slot_key(slot)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Read(ScrollKind::Teleportation))));
+        assert_ne!(runtime.player.pos, pos);
+        assert!(runtime.player.itembox.get_slot(slot).is_none());
+    }
+    #[test]
+    fn identify() {
+        let mut runtime = runtime();
+        let scroll = give_item(&mut runtime, |kind| match kind {
+            ItemKind::Scroll(s) => s.kind == ScrollKind::Identify,
+            _ => false,
+        });
+        let potion = give_item(&mut runtime, |kind| matches!(kind, ItemKind::Potion(_)));
+        let kind = runtime.itembox().get_slot(potion).unwrap().kind.clone();
+        assert!(!runtime.knowledge().is_known(&kind));
+        // names an unknown potion
+        runtime.react_to_key(Key::Char('c')).unwrap();
+        runtime.react_to_key(slot_key(potion)).unwrap();
+        for c in "foo\n".chars() {
+            runtime.react_to_key(Key::Char(c)).unwrap();
+        }
+        assert_eq!(runtime.ui, UiState::Dungeon);
+        let name = runtime.knowledge().kind_name(&kind).to_string();
+        assert!(name.starts_with("potion called foo"));
+        runtime.react_to_key(Key::Char('r')).unwrap();
+        let res = runtime.react_to_key(slot_key(scroll)).unwrap();
+        let select = UiState::Mordal(MordalKind::SelectItem(ItemAction::Identify));
+        assert!(res.contains(&Reaction::UiTransition(select)));
+        // the scroll of identify is used and the slot may be reused
+        let potion = runtime
+            .itembox()
+            .slots()
+            .find(|(_, token)| token.kind == kind)
+            .map(|(slot, _)| slot)
+            .unwrap();
+        let res = runtime.react_to_key(slot_key(potion)).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::Identified(kind.clone()))));
+        assert!(runtime.knowledge().is_known(&kind));
+        assert_eq!(runtime.ui, UiState::Dungeon);
+    }
+}
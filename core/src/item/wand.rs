@@ -0,0 +1,235 @@
+use super::handler::{Handler, ItemInner, ItemStat};
+use super::{Item, ItemAttr, ItemKind, ItemNum};
+use crate::rng::{Parcent, RngHandle};
+use enum_iterator::IntoEnumIterator;
+use std::fmt;
+
+/// Wand configuration
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_wands")]
+    pub wands: Vec<Preset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            wands: default_wands(),
+        }
+    }
+}
+
+impl Config {
+    pub(super) fn build(self) -> Handler<WandStatus> {
+        Handler {
+            // wands are never cursed or powered up
+            cursed_rate: Parcent(0),
+            powerup_rate: Parcent(0),
+            stats: self.wands.into_iter().map(Preset::build).collect(),
+        }
+    }
+}
+
+fn default_wands() -> Vec<Preset> {
+    (0..BUILTIN_WANDS.len()).map(Preset::Builtin).collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase", untagged)]
+pub enum Preset {
+    Builtin(usize),
+    Custom(WandStatus),
+}
+
+impl Preset {
+    fn build(self) -> WandStatus {
+        match self {
+            Preset::Builtin(i) => BUILTIN_WANDS[i].clone(),
+            Preset::Custom(v) => v,
+        }
+    }
+}
+
+/// effects of wands and staffs
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, IntoEnumIterator)]
+pub enum WandKind {
+    Light,
+    Striking,
+    Lightning,
+    Fire,
+    Cold,
+    Sleep,
+    SlowMonster,
+    HasteMonster,
+    Polymorph,
+    TeleportAway,
+    Cancellation,
+    DrainLife,
+    Nothing,
+}
+
+impl WandKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WandKind::Light => "light",
+            WandKind::Striking => "striking",
+            WandKind::Lightning => "lightning",
+            WandKind::Fire => "fire",
+            WandKind::Cold => "cold",
+            WandKind::Sleep => "sleep",
+            WandKind::SlowMonster => "slow monster",
+            WandKind::HasteMonster => "haste monster",
+            WandKind::Polymorph => "polymorph",
+            WandKind::TeleportAway => "teleport away",
+            WandKind::Cancellation => "cancellation",
+            WandKind::DrainLife => "drain life",
+            WandKind::Nothing => "nothing",
+        }
+    }
+    /// wands which shoot a bolt bouncing off walls
+    pub fn is_bolt(self) -> bool {
+        matches!(self, WandKind::Lightning | WandKind::Fire | WandKind::Cold)
+    }
+}
+
+impl fmt::Display for WandKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Wand {
+    pub kind: WandKind,
+    pub(super) charges: u32,
+    worth: ItemNum,
+}
+
+impl Wand {
+    pub fn charges(&self) -> u32 {
+        self.charges
+    }
+    /// uses a charge and returns if the wand had a charge
+    pub(crate) fn use_charge(&mut self) -> bool {
+        if self.charges == 0 {
+            return false;
+        }
+        self.charges -= 1;
+        true
+    }
+}
+
+impl ItemInner for Wand {
+    fn into_item(self, attr: ItemAttr, how_many: ItemNum) -> Item {
+        Item {
+            kind: ItemKind::Wand(self),
+            attr,
+            how_many,
+        }
+    }
+}
+
+impl fmt::Display for Wand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "wand of {} [{} charges]", self.kind, self.charges)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct WandStatus {
+    kind: WandKind,
+    appear_rate: Parcent,
+    worth: ItemNum,
+}
+
+impl ItemStat for WandStatus {
+    type Item = Wand;
+    fn appear_rate(&self) -> Parcent {
+        self.appear_rate
+    }
+    fn build(self, rng: &mut RngHandle) -> (Wand, ItemAttr, ItemNum) {
+        // as in rogue, a wand of light has 10..20 charges and others have 3..8 charges
+        let charges = match self.kind {
+            WandKind::Light => rng.range(10..20),
+            _ => rng.range(3..8),
+        };
+        let wand = Wand {
+            kind: self.kind,
+            charges,
+            worth: self.worth,
+        };
+        (wand, ItemAttr::CAN_THROW, 1.into())
+    }
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+    fn worth(&self) -> ItemNum {
+        self.worth
+    }
+}
+
+macro_rules! wand {
+    ($kind: ident, $rate: expr, $worth: expr) => {
+        WandStatus {
+            kind: WandKind::$kind,
+            appear_rate: Parcent($rate),
+            worth: ItemNum($worth),
+        }
+    };
+}
+
+// appear rates and worths are based on rogue 5.4.4,
+// where striking takes magic missile's place and sleep takes invisibility's place
+const BUILTIN_WANDS: [WandStatus; 13] = [
+    wand!(Light, 12, 250),
+    wand!(Striking, 10, 170),
+    wand!(Lightning, 3, 330),
+    wand!(Fire, 3, 330),
+    wand!(Cold, 3, 330),
+    wand!(Sleep, 6, 300),
+    wand!(SlowMonster, 11, 350),
+    wand!(HasteMonster, 10, 5),
+    wand!(Polymorph, 15, 310),
+    wand!(TeleportAway, 12, 340),
+    wand!(Cancellation, 5, 280),
+    wand!(DrainLife, 9, 300),
+    wand!(Nothing, 1, 5),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actions::test::{direction_key, enemy_nearby, give_item, runtime, slot_key};
+    use crate::input::Key;
+    use crate::ui::{ItemAction, MordalKind, UiState};
+    use crate::{GameMsg, Reaction, RunTime};
+    #[test]
+    fn zap() {
+        let mut runtime = runtime();
+        let is_teleport = |kind: &ItemKind| match kind {
+            ItemKind::Wand(w) => w.kind == WandKind::TeleportAway,
+            _ => false,
+        };
+        let slot = give_item(&mut runtime, is_teleport);
+        // food can't be zapped
+        runtime.react_to_key(Key::Char('z')).unwrap();
+        runtime.react_to_key(Key::Char('b')).unwrap();
+        let res = runtime.react_to_key(Key::Char('h')).unwrap();
+        assert!(res.contains(&Reaction::Notify(GameMsg::CantZap)));
+        let RunTime {
+            dungeon,
+            enemies,
+            player,
+            ..
+        } = &mut runtime;
+        let (place, direction) = enemy_nearby(&mut **dungeon, player, enemies);
+        runtime.react_to_key(Key::Char('z')).unwrap();
+        let res = runtime.react_to_key(slot_key(slot)).unwrap();
+        let select = UiState::Mordal(MordalKind::SelectDirection(ItemAction::Zap, slot));
+        assert_eq!(res, vec![Reaction::UiTransition(select)]);
+        let res = runtime.react_to_key(direction_key(direction)).unwrap();
+        let msg = GameMsg::Zapped(WandKind::TeleportAway);
+        assert!(res.contains(&Reaction::Notify(msg)));
+        assert!(runtime.enemies.get_id(&place).is_none());
+    }
+}
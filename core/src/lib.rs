@@ -11,31 +11,41 @@ extern crate test;
 
 mod actions;
 use std::fs::File;
+use std::hash::Hasher;
 use std::io::{self, Read};
 pub mod character;
 pub mod dungeon;
 pub mod error;
 mod fenwick;
+mod hash;
 pub mod input;
 pub mod item;
+pub mod replay;
 mod rng;
 mod smallstr;
 pub mod symbol;
 pub mod tile;
 pub mod ui;
 
-use crate::character::{enemies, player, EnemyHandler, Player};
-use crate::dungeon::{Direction, Dungeon, DungeonStyle, Positioned, X, Y};
+use crate::character::{enemies, player, Action, EnemyHandler, Player};
+use crate::dungeon::{
+    Direction, Dungeon, DungeonSnapshot, DungeonStyle, Positioned, TrapKind, X, Y,
+};
 use anyhow::{bail, Context};
 use error::*;
+use hash::{hash_serialized, Fnv64};
 use input::{InputCode, Key, KeyMap};
-use item::{ItemHandler, ItemKind};
+use item::{
+    food::Food, potion::PotionKind, ring::RingKind, scroll::ScrollKind, wand::WandKind,
+    ItemHandler, ItemKind,
+};
 use log::{debug, trace};
 use ndarray::Array2;
+use replay::Replay;
 use serde::{Deserialize, Serialize};
 pub use smallstr::SmallStr;
 use tile::{Drawable, Tile};
-use ui::{MordalKind, MordalMsg, UiState};
+use ui::{ItemAction, MordalKind, MordalMsg, UiState};
 
 /// Game configuration
 /// it's inteded to construct from json
@@ -85,8 +95,6 @@ pub struct GameConfig {
     pub hide_dungeon: bool,
 }
 
-unsafe impl Send for GameConfig {}
-
 fn is_default<T>(s: &T) -> bool
 where
     T: Default + PartialEq,
@@ -192,6 +200,7 @@ impl GameConfig {
     /// get runtime from config
     pub fn build(self) -> GameResult<RunTime> {
         const ERR_STR: &str = "GameConfig::build";
+        let game_config = self.clone();
         let game_info = GameInfo::new();
         let config = self.to_global().context(ERR_STR)?;
         debug!("Building dungeon with seed {}", config.seed);
@@ -224,6 +233,7 @@ impl GameConfig {
             ui: UiState::Dungeon,
             saved_inputs: vec![],
             keymap: self.keymap,
+            game_config,
         })
     }
 }
@@ -239,26 +249,37 @@ pub struct RunTime {
     saved_inputs: Vec<InputCode>,
     enemies: EnemyHandler,
     pub keymap: KeyMap,
+    /// configuration which the game was built from
+    game_config: GameConfig,
 }
 
 impl RunTime {
     fn check_interrupting(&mut self, input: input::System) -> GameResult<Vec<Reaction>> {
         use input::System;
-        match input {
-            System::Quit => {
-                let ui = UiState::Mordal(MordalKind::Quit);
-                self.ui = ui.clone();
-                Ok(vec![Reaction::UiTransition(ui)])
-            }
-            System::Inventory => {
-                let ui = UiState::Mordal(MordalKind::Inventory);
-                self.ui = ui.clone();
-                Ok(vec![Reaction::UiTransition(ui)])
+        let mordal = match input {
+            System::Quit => MordalKind::Quit,
+            System::Inventory => MordalKind::Inventory,
+            System::Save => MordalKind::Save,
+            System::Eat => MordalKind::SelectItem(ItemAction::Eat),
+            System::Quaff => MordalKind::SelectItem(ItemAction::Quaff),
+            System::Read => MordalKind::SelectItem(ItemAction::Read),
+            System::PutOn => MordalKind::SelectItem(ItemAction::PutOn),
+            System::Remove => MordalKind::SelectItem(ItemAction::Remove),
+            System::Zap => MordalKind::SelectItem(ItemAction::Zap),
+            System::Wield => MordalKind::SelectItem(ItemAction::Wield),
+            System::Wear => MordalKind::SelectItem(ItemAction::Wear),
+            System::TakeOff => MordalKind::SelectItem(ItemAction::TakeOff),
+            System::Call => MordalKind::SelectItem(ItemAction::Call),
+            System::Throw => MordalKind::SelectItem(ItemAction::Throw),
+            System::Drop => MordalKind::SelectItem(ItemAction::Drop),
+            _ => {
+                return Err(ErrorKind::IgnoredInput(InputCode::Sys(input)))
+                    .context("rogue_gym_core::RunTime::check_interuppting")
             }
-            System::Save => bail!(ErrorKind::Unimplemented("Save command")),
-            _ => Err(ErrorKind::IgnoredInput(InputCode::Sys(input)))
-                .context("rogue_gym_core::RunTime::check_interuppting"),
-        }
+        };
+        let ui = UiState::Mordal(mordal);
+        self.ui = ui.clone();
+        Ok(vec![Reaction::UiTransition(ui)])
     }
     /// take draw function F and draw screen with it
     pub fn draw_screen(
@@ -267,21 +288,50 @@ impl RunTime {
     ) -> GameResult<()> {
         // floor => item & character
         self.dungeon.draw(&mut drawer)?;
-        self.dungeon.draw_ranges().into_iter().try_for_each(|path| {
-            let cd = self.dungeon.path_to_cd(&path);
-            if self.player.pos == path {
-                return drawer(Positioned(cd, self.player.tile()));
-            };
-            if let Some(item) = self.dungeon.get_item(&path) {
-                return drawer(Positioned(cd, item.tile()));
+        let is_blind = self.player.has_effect(player::Effect::Blind);
+        let see_invisible = self.player.has_effect(player::Effect::SeeInvisible);
+        self.dungeon
+            .draw_ranges()
+            .into_iter()
+            .try_for_each(|path| {
+                let cd = self.dungeon.path_to_cd(&path);
+                if self.player.pos == path {
+                    return drawer(Positioned(cd, self.player.tile()));
+                };
+                if is_blind {
+                    return Ok(());
+                }
+                if let Some(item) = self.dungeon.get_item(&path) {
+                    return drawer(Positioned(cd, item.tile()));
+                }
+                if let Some(enemy) = self.enemies.get_enemy(&path) {
+                    let is_visible = see_invisible || !enemy.is_invisible();
+                    if is_visible && self.dungeon.draw_enemy(&self.player.pos, &path) {
+                        return drawer(Positioned(cd, enemy.tile()));
+                    }
+                }
+                Ok(())
+            })?;
+        // detected objects are drawn even if they are out of sight
+        let detect_magic = self.player.has_effect(player::Effect::DetectMagic);
+        let detect_food = self.player.has_effect(player::Effect::DetectFood);
+        if detect_magic || detect_food {
+            for (path, item) in self.dungeon.items() {
+                let detected = (detect_magic && item.is_magic())
+                    || (detect_food && matches!(item.kind, ItemKind::Food(_)));
+                if detected && path != self.player.pos {
+                    drawer(Positioned(self.dungeon.path_to_cd(&path), item.tile()))?;
+                }
             }
-            if let Some(enemy) = self.enemies.get_enemy(&path) {
-                if self.dungeon.draw_enemy(&self.player.pos, &path) {
-                    return drawer(Positioned(cd, enemy.tile()));
+        }
+        if self.player.has_effect(player::Effect::DetectMonsters) {
+            for (path, enemy) in self.enemies.placed() {
+                if self.dungeon.is_current_level(path) {
+                    drawer(Positioned(self.dungeon.path_to_cd(path), enemy.tile()))?;
                 }
             }
-            Ok(())
-        })
+        }
+        Ok(())
     }
     pub fn react_to_input(&mut self, input: InputCode) -> GameResult<Vec<Reaction>> {
         trace!("[react_to_input] input: {:?} ui: {:?}", input, self.ui);
@@ -298,21 +348,56 @@ impl RunTime {
                     &mut self.enemies,
                 )?,
             },
-            UiState::Mordal(ref mut kind) => match input {
-                InputCode::Sys(sys) | InputCode::Both { sys, .. } => {
-                    let res = kind.process(sys);
-                    match res {
-                        MordalMsg::Cancel => (
-                            Some(UiState::Dungeon),
-                            vec![Reaction::UiTransition(UiState::Dungeon)],
-                        ),
-                        MordalMsg::Save => bail!(ErrorKind::Unimplemented("Save command")),
-                        MordalMsg::Quit => (None, vec![Reaction::Notify(GameMsg::Quit)]),
-                        MordalMsg::None => (None, vec![]),
+            UiState::Mordal(ref mut kind) => {
+                let res = match input {
+                    InputCode::Act(Action::Move(dir))
+                    | InputCode::Both {
+                        act: Action::Move(dir),
+                        ..
+                    } if kind.selects_direction() => kind.select_direction(dir),
+                    InputCode::Sys(sys) | InputCode::Both { sys, .. } => kind.process(sys),
+                    InputCode::Act(_) => bail!(ErrorKind::IgnoredInput(input)),
+                };
+                match res {
+                    MordalMsg::Cancel => (
+                        Some(UiState::Dungeon),
+                        vec![Reaction::UiTransition(UiState::Dungeon)],
+                    ),
+                    MordalMsg::Save => (
+                        Some(UiState::Dungeon),
+                        vec![Reaction::Notify(GameMsg::Save)],
+                    ),
+                    MordalMsg::Act(act) => {
+                        let (ui, mut res) = actions::process_action(
+                            act,
+                            &mut self.game_info,
+                            &mut *self.dungeon,
+                            &mut self.item,
+                            &mut self.player,
+                            &mut self.enemies,
+                        )?;
+                        res.insert(0, Reaction::UiTransition(UiState::Dungeon));
+                        (Some(ui.unwrap_or(UiState::Dungeon)), res)
+                    }
+                    MordalMsg::Call(slot, name) => {
+                        let called = match self.player.itembox.get_slot(slot) {
+                            Some(token) => self.item.knowledge_mut().call(&token.kind, name),
+                            None => false,
+                        };
+                        let mut res = vec![Reaction::UiTransition(UiState::Dungeon)];
+                        if !called {
+                            res.push(Reaction::Notify(GameMsg::CantCall));
+                        }
+                        (Some(UiState::Dungeon), res)
                     }
+                    MordalMsg::Next(next) => {
+                        let ui = UiState::Mordal(next);
+                        (Some(ui.clone()), vec![Reaction::UiTransition(ui)])
+                    }
+                    MordalMsg::Quit => (None, vec![Reaction::Notify(GameMsg::Quit)]),
+                    MordalMsg::None => (None, vec![]),
                 }
-                InputCode::Act(_) => bail!(ErrorKind::IgnoredInput(input)),
-            },
+            }
         };
         if let Some(next_ui) = next_ui {
             self.ui = next_ui;
@@ -320,6 +405,18 @@ impl RunTime {
         Ok(res)
     }
     pub fn react_to_key(&mut self, key: Key) -> GameResult<Vec<Reaction>> {
+        if let (UiState::Mordal(kind), Key::Char(c)) = (&self.ui, key) {
+            if kind.selects_item() && c.is_ascii_lowercase() {
+                return self.react_to_input(InputCode::Sys(input::System::Select(c)));
+            }
+            if kind.takes_text() {
+                let sys = match c {
+                    '\n' | '\r' => input::System::Enter,
+                    _ => input::System::Select(c),
+                };
+                return self.react_to_input(InputCode::Sys(sys));
+            }
+        }
         match self.keymap.get(key) {
             Some(i) => self.react_to_input(i),
             None => Err(ErrorKind::InvalidInput(key).into()),
@@ -361,15 +458,134 @@ impl RunTime {
         serde_json::to_string_pretty(&self.saved_inputs)
             .context("Runtime::saved_inputs_json: Failed to serialize")
     }
+    /// Returns the replay of the game, without state hashes
+    pub fn replay(&self) -> Replay {
+        Replay::new(
+            self.game_config.clone(),
+            self.config.seed,
+            self.saved_inputs.clone(),
+        )
+    }
+    /// Saves the whole game state as json.
+    /// The game loaded from the returned string continues exactly as this one does.
+    pub fn save(&self) -> GameResult<String> {
+        let data = SaveDataRef {
+            game_info: &self.game_info,
+            config: &self.config,
+            dungeon: self.dungeon.snapshot(),
+            item: &self.item,
+            player: &self.player,
+            ui: &self.ui,
+            saved_inputs: &self.saved_inputs,
+            enemies: &self.enemies,
+            keymap: &self.keymap,
+            game_config: &self.game_config,
+        };
+        serde_json::to_string(&data).context("RunTime::save: Failed to serialize")
+    }
+    /// Loads the game saved by `RunTime::save`
+    pub fn load(saved: &str) -> GameResult<RunTime> {
+        let data: SaveData =
+            serde_json::from_str(saved).context("RunTime::load: Failed to deserialize")?;
+        let SaveData {
+            game_info,
+            config,
+            dungeon,
+            item,
+            player,
+            ui,
+            saved_inputs,
+            enemies,
+            keymap,
+            game_config,
+        } = data;
+        Ok(RunTime {
+            game_info,
+            config,
+            dungeon: dungeon.restore(),
+            item,
+            player,
+            ui,
+            saved_inputs,
+            enemies,
+            keymap,
+            game_config,
+        })
+    }
+    /// Returns a deterministic fingerprint of the game state, which doesn't depend on the history
+    /// of inputs or the keymap, but does depend on the configurations stored in the game state,
+    /// e.g., dungeon settings.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = Fnv64::new();
+        self.dungeon.hash_state(&mut hasher);
+        let rest = (
+            &self.game_info,
+            &self.item,
+            &self.player,
+            &self.ui,
+            &self.enemies,
+        );
+        hash_serialized(&rest, &mut hasher);
+        hasher.finish()
+    }
+    /// Returns an independent deep copy of the game, including its RNG states.
+    pub fn fork(&self) -> RunTime {
+        RunTime {
+            game_info: self.game_info.clone(),
+            config: self.config.clone(),
+            dungeon: self.dungeon.snapshot().restore(),
+            item: self.item.clone(),
+            player: self.player.clone(),
+            ui: self.ui.clone(),
+            saved_inputs: self.saved_inputs.clone(),
+            enemies: self.enemies.clone(),
+            keymap: self.keymap.clone(),
+            game_config: self.game_config.clone(),
+        }
+    }
     pub fn history(&self, player_stat: &player::Status) -> Option<Array2<bool>> {
         self.dungeon.get_history(&player_stat)
     }
+    /// what the player knows about magic items
+    pub fn knowledge(&self) -> &item::identify::Knowledge {
+        self.item.knowledge()
+    }
     pub fn itembox(&self) -> &item::ItemBox {
         debug!("itembox {:?}", self.player.itembox);
         &self.player.itembox
     }
 }
 
+/// Borrowed version of SaveData, for saving
+#[derive(Serialize)]
+struct SaveDataRef<'a> {
+    game_info: &'a GameInfo,
+    config: &'a GlobalConfig,
+    dungeon: DungeonSnapshot,
+    item: &'a ItemHandler,
+    player: &'a Player,
+    ui: &'a UiState,
+    saved_inputs: &'a [InputCode],
+    enemies: &'a EnemyHandler,
+    keymap: &'a KeyMap,
+    game_config: &'a GameConfig,
+}
+
+/// Serialized form of RunTime
+#[derive(Deserialize)]
+struct SaveData {
+    game_info: GameInfo,
+    config: GlobalConfig,
+    dungeon: DungeonSnapshot,
+    item: ItemHandler,
+    player: Player,
+    ui: UiState,
+    saved_inputs: Vec<InputCode>,
+    enemies: EnemyHandler,
+    keymap: KeyMap,
+    game_config: GameConfig,
+}
+
 pub fn json_to_inputs(json: &str) -> GameResult<Vec<InputCode>> {
     serde_json::from_str(json).context("json_to_inputs: Failed to deserialize")
 }
@@ -391,14 +607,136 @@ pub enum Reaction {
 pub enum GameMsg {
     CantMove(Direction),
     CantGetItem(ItemKind),
-    GotItem { kind: ItemKind, num: u32 },
+    GotItem {
+        kind: ItemKind,
+        num: u32,
+    },
     HitTo(SmallStr),
     HitFrom(SmallStr),
     MissTo(SmallStr),
     MissFrom(SmallStr),
     Killed(SmallStr),
+    /// hunger level of the player changed
+    HungerChanged(player::Hunger),
+    /// the player fainted from hunger and lost some turns
+    Fainted,
+    Ate(Food),
+    /// the selected item is not food
+    CantEat,
+    /// no item in the selected slot
+    InvalidItem,
+    Quaffed(PotionKind),
+    /// the selected item is not a potion
+    CantQuaff,
+    Read(ScrollKind),
+    /// the scroll was read but had no effect
+    ScrollFailed(ScrollKind),
+    /// the selected item is not a scroll
+    CantRead,
+    PutOnRing(RingKind),
+    RemovedRing(RingKind),
+    /// the selected item is not a ring
+    CantPutOn,
+    /// the player already wears rings on both hands
+    NoFreeHand,
+    /// the selected item is already worn
+    AlreadyWearing,
+    /// the selected item is not worn
+    NotWearing,
+    /// the selected item is cursed and can't be removed
+    Cursed,
+    Zapped(WandKind),
+    /// the wand was zapped but had no effect
+    ZapFailed(WandKind),
+    /// the selected wand has no charge
+    NoCharges,
+    /// the selected item is not a wand
+    CantZap,
+    /// the player is too weak to use the wand of drain life
+    TooWeak,
+    /// the bolt or the wand of striking hit the enemy
+    BoltHitTo(WandKind, SmallStr),
+    /// the bolt or the wand of striking missed the enemy
+    BoltMissTo(WandKind, SmallStr),
+    /// the bolt bounced and hit the player
+    BoltHitFrom(WandKind),
+    /// the bolt bounced and missed the player
+    BoltMissFrom(WandKind),
+    Wielded(ItemKind),
+    /// the selected item is not a weapon
+    CantWield,
+    /// the selected weapon is already wielded
+    AlreadyWielding,
+    Wore(ItemKind),
+    /// the selected item is not armor
+    CantWear,
+    /// the player has to take off the current armor before wearing another
+    TakeOffFirst,
+    TookOff(ItemKind),
+    Dropped(ItemKind),
+    /// there is already an item on the floor
+    SomethingThere,
+    /// the selected item is equipped
+    InUse,
+    /// the selected item can't be thrown
+    CantThrow,
+    /// the thrown item hit the enemy
+    ThrowHit(SmallStr, SmallStr),
+    /// the thrown item missed the enemy
+    ThrowMiss(SmallStr, SmallStr),
+    /// the missed projectile found no place to fall
+    Vanished(ItemKind),
+    Identified(ItemKind),
+    /// the selected item can't be named
+    CantCall,
+    /// the enemy's special attack rusted the armor
+    ArmorRusted,
+    /// the ring of maintain armor protected the armor from rust
+    RustVanished,
+    /// the enemy stole gold and ran away
+    GoldStolen(u32),
+    /// the enemy's bite reduced the strength
+    StrengthDrained,
+    /// the enemy's bite was resisted
+    BiteResisted,
+    /// the enemy froze the player
+    Frozen(SmallStr),
+    /// the enemy drained a level of the player
+    LevelDrained,
+    /// the enemy drained the max HP of the player
+    MaxHpDrained,
+    /// an effect on the player wore off
+    EffectEnded(player::Effect),
     NoDownStair,
+    NoUpStair,
     SecretDoor,
+    /// a hidden trap was found by searching
+    FoundTrap(TrapKind),
+    /// the player fell through a trapdoor
+    FellIntoTrap,
+    /// the player was caught in a bear trap
+    CaughtInBearTrap,
+    /// the player can't move while stuck in a bear trap
+    StuckInBearTrap,
+    /// the player fell asleep by a sleeping gas trap
+    SleepingGas,
+    /// an arrow of the trap hit the player
+    ArrowHit,
+    /// an arrow of the trap missed the player
+    ArrowMiss,
+    /// a dart of the trap hit the player
+    DartHit,
+    /// a dart of the trap missed the player
+    DartMiss,
+    /// the player was teleported by a trap
+    TeleportTrap,
+    /// water of a rust trap hit the player
+    RustTrap,
+    /// the player stepped on a mysterious trap
+    MysteriousTrap,
+    /// the player escaped from the dungeon with the Amulet of Yendor
+    Victory,
+    Save,
     Quit,
 }
 
@@ -456,3 +794,123 @@ mod config_test {
         assert_eq!(config, GameConfig::default());
     }
 }
+
+#[cfg(test)]
+mod save_test {
+    use super::*;
+    use crate::dungeon::Coord;
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    fn screen(runtime: &RunTime) -> Vec<(Coord, Tile)> {
+        let mut res = vec![];
+        runtime
+            .draw_screen(|Positioned(cd, tile)| {
+                res.push((cd, tile));
+                Ok(())
+            })
+            .unwrap();
+        res
+    }
+    fn random_keys(rng: &mut XorShiftRng, n: usize) -> Vec<Key> {
+        const KEYS: &[u8] = b"hjklyubn>s";
+        (0..n)
+            .map(|_| Key::Char(KEYS[rng.gen_range(0, KEYS.len())] as char))
+            .collect()
+    }
+    #[test]
+    fn save_and_load() {
+        let mut rng = XorShiftRng::seed_from_u64(10);
+        let config = GameConfig {
+            seed: Some(5),
+            ..Default::default()
+        };
+        let mut runtime = config.build().unwrap();
+        for key in random_keys(&mut rng, 200) {
+            let _ = runtime.react_to_key(key);
+        }
+        let saved = runtime.save().unwrap();
+        let mut loaded = RunTime::load(&saved).unwrap();
+        assert_eq!(screen(&runtime), screen(&loaded));
+        for key in random_keys(&mut rng, 500) {
+            let res1 = runtime.react_to_key(key).ok();
+            let res2 = loaded.react_to_key(key).ok();
+            assert_eq!(res1, res2);
+            assert_eq!(runtime.player_status(), loaded.player_status());
+        }
+        assert_eq!(screen(&runtime), screen(&loaded));
+        assert_eq!(runtime.saved_inputs, loaded.saved_inputs);
+    }
+    #[test]
+    fn state_hash() {
+        let mut rng = XorShiftRng::seed_from_u64(30);
+        let config = GameConfig {
+            seed: Some(13),
+            ..Default::default()
+        };
+        let mut runtime1 = config.clone().build().unwrap();
+        let mut runtime2 = config.build().unwrap();
+        assert_eq!(runtime1.state_hash(), runtime2.state_hash());
+        let mut hashes = vec![runtime1.state_hash()];
+        for key in random_keys(&mut rng, 300) {
+            let _ = runtime1.react_to_key(key);
+            let _ = runtime2.react_to_key(key);
+            assert_eq!(runtime1.state_hash(), runtime2.state_hash());
+            hashes.push(runtime1.state_hash());
+        }
+        hashes.dedup();
+        assert!(hashes.len() > 1);
+        let hash = runtime1.state_hash();
+        assert_eq!(runtime1.fork().state_hash(), hash);
+        let loaded = RunTime::load(&runtime1.save().unwrap()).unwrap();
+        assert_eq!(loaded.state_hash(), hash);
+    }
+    #[test]
+    fn state_hash_and_config() {
+        let config = GameConfig {
+            seed: Some(13),
+            ..Default::default()
+        };
+        let runtime = config.clone().build().unwrap();
+        let hash = runtime.state_hash();
+        // the keymap isn't a part of the state
+        let ai_keymap = GameConfig {
+            keymap: KeyMap::ai(),
+            ..config
+        };
+        assert_eq!(ai_keymap.build().unwrap().state_hash(), hash);
+        // a setting used only while searching doesn't change the initial dungeon,
+        // but the hash changes
+        let json = r#"{ "seed": 13, "dungeon": { "style": "rogue", "door_unlock_rate_inv": 6 } }"#;
+        let other = GameConfig::from_json(json).unwrap().build().unwrap();
+        assert_eq!(screen(&other), screen(&runtime));
+        assert_ne!(other.state_hash(), hash);
+    }
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RunTime>();
+        assert_send_sync::<GameConfig>();
+    }
+    #[test]
+    fn fork() {
+        let mut rng = XorShiftRng::seed_from_u64(20);
+        let config = GameConfig {
+            seed: Some(8),
+            ..Default::default()
+        };
+        let mut runtime = config.build().unwrap();
+        for key in random_keys(&mut rng, 200) {
+            let _ = runtime.react_to_key(key);
+        }
+        let before = runtime.save().unwrap();
+        let mut forked = runtime.fork();
+        let keys = random_keys(&mut rng, 500);
+        let res: Vec<_> = keys.iter().map(|&k| forked.react_to_key(k).ok()).collect();
+        assert_eq!(runtime.save().unwrap(), before);
+        for (&key, res) in keys.iter().zip(res) {
+            assert_eq!(runtime.react_to_key(key).ok(), res);
+        }
+        assert_eq!(screen(&runtime), screen(&forked));
+        assert_eq!(runtime.player_status(), forked.player_status());
+    }
+}
@@ -0,0 +1,200 @@
+//! replay file, which bundles everything needed to reproduce a game
+use crate::{error::*, input::InputCode, GameConfig, RunTime};
+use anyhow::{bail, Context};
+use log::warn;
+
+/// version of rogue-gym-core, recorded in replay files
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ReplayHeader {
+    /// version of rogue-gym-core which recorded the replay
+    pub version: String,
+    /// configuration the game was built from
+    pub config: GameConfig,
+    /// the seed actually used, which may be chosen from `seed_range` or randomly
+    pub seed: u128,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub inputs: Vec<InputCode>,
+    /// `RunTime::state_hash` after each input
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashes: Option<Vec<u64>>,
+}
+
+/// The first step where the replayed game differs from the recorded one
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Divergence {
+    /// index of the input, after which the states differ
+    pub step: usize,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig, seed: u128, inputs: Vec<InputCode>) -> Self {
+        Replay {
+            header: ReplayHeader {
+                version: CRATE_VERSION.to_owned(),
+                config,
+                seed,
+            },
+            inputs,
+            hashes: None,
+        }
+    }
+    pub fn from_json(json: &str) -> GameResult<Self> {
+        serde_json::from_str(json).context("Replay::from_json: Failed to deserialize")
+    }
+    /// Parses a replay file, accepting also the old format(a bare array of inputs).
+    /// For an old one, `config` is used as the header and it has to specify the seed.
+    pub fn from_json_or_inputs(json: &str, config: GameConfig) -> GameResult<Self> {
+        if let Ok(replay) = Self::from_json(json) {
+            return Ok(replay);
+        }
+        let inputs = crate::json_to_inputs(json).context("Replay::from_json_or_inputs")?;
+        let seed = match config.seed {
+            Some(seed) => seed,
+            None => bail!(ErrorKind::InvalidSetting(
+                "Replay::from_json_or_inputs: seed is required for replaying inputs".into()
+            )),
+        };
+        Ok(Self::new(config, seed, inputs))
+    }
+    pub fn to_json(&self) -> GameResult<String> {
+        serde_json::to_string(self).context("Replay::to_json: Failed to serialize")
+    }
+    /// Returns the configuration with the resolved seed
+    pub fn config(&self) -> GameConfig {
+        let mut config = self.header.config.clone();
+        config.seed = Some(self.header.seed);
+        config
+    }
+    /// Builds the game at its initial state
+    pub fn build(&self) -> GameResult<RunTime> {
+        if self.header.version != CRATE_VERSION {
+            warn!(
+                "Replay was recorded by version {}, but this is {}",
+                self.header.version, CRATE_VERSION
+            );
+        }
+        self.config().build()
+    }
+    /// Replays all inputs and records state hashes
+    pub fn with_hashes(mut self) -> GameResult<Self> {
+        let mut hashes = Vec::with_capacity(self.inputs.len());
+        self.run(|_, hash| {
+            hashes.push(hash);
+            true
+        })?;
+        self.hashes = Some(hashes);
+        Ok(self)
+    }
+    /// Replays all inputs and returns the first step where the state hash differs from the
+    /// recorded one, or None if the whole replay is reproduced.
+    pub fn verify(&self) -> GameResult<Option<Divergence>> {
+        let hashes = match &self.hashes {
+            Some(hashes) => hashes,
+            None => bail!(ErrorKind::InvalidSetting(
+                "Replay::verify: replay has no state hashes".into()
+            )),
+        };
+        if hashes.len() != self.inputs.len() {
+            bail!(ErrorKind::InvalidSetting(
+                "Replay::verify: numbers of inputs and hashes differ".into()
+            ));
+        }
+        let mut res = None;
+        self.run(|step, actual| {
+            let expected = hashes[step];
+            if expected != actual {
+                res = Some(Divergence {
+                    step,
+                    expected,
+                    actual,
+                });
+                return false;
+            }
+            true
+        })?;
+        Ok(res)
+    }
+    /// Replays inputs and calls `f` with the step and the state hash after each input,
+    /// while `f` returns true
+    fn run(&self, mut f: impl FnMut(usize, u64) -> bool) -> GameResult<()> {
+        let mut runtime = self.build().context("Replay::run")?;
+        for (step, &input) in self.inputs.iter().enumerate() {
+            // ignored inputs are also recorded, so errors are ignored here as in playing
+            let _ = runtime.react_to_input(input);
+            if !f(step, runtime.state_hash()) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::Key;
+    use crate::read_file;
+    fn play(config: GameConfig, keys: &str) -> RunTime {
+        let mut runtime = config.build().unwrap();
+        for c in keys.chars() {
+            let _ = runtime.react_to_key(Key::Char(c));
+        }
+        runtime
+    }
+    #[test]
+    fn roundtrip_and_verify() {
+        let config = GameConfig {
+            seed_range: Some([10, 20]),
+            ..Default::default()
+        };
+        let runtime = play(config, "jjjlllkkkhhhs>uuubbbnnnyyyjjjjjllllll");
+        let replay = runtime.replay().with_hashes().unwrap();
+        let seed = replay.header.seed;
+        assert!((10..20).contains(&seed));
+        let replay = Replay::from_json(&replay.to_json().unwrap()).unwrap();
+        assert_eq!(replay.verify().unwrap(), None);
+        let replayed = play(replay.config(), "jjjlllkkkhhhs>uuubbbnnnyyyjjjjjllllll");
+        assert_eq!(replayed.state_hash(), runtime.state_hash());
+        assert_eq!(
+            replay.hashes.as_ref().unwrap().last(),
+            Some(&runtime.state_hash())
+        );
+    }
+    #[test]
+    fn divergence() {
+        let config = GameConfig {
+            seed: Some(3),
+            ..Default::default()
+        };
+        let runtime = play(config, "jjjjllllkkkkhhhh");
+        let mut replay = runtime.replay().with_hashes().unwrap();
+        replay.hashes.as_mut().unwrap()[5] ^= 1;
+        let divergence = replay.verify().unwrap().unwrap();
+        assert_eq!(divergence.step, 5);
+        replay.hashes = None;
+        assert!(replay.verify().is_err());
+    }
+    #[test]
+    fn legacy_format() {
+        let config = read_file("../data/learned/ddqn-minidungeon/config.json").unwrap();
+        let config = GameConfig::from_json(&config).unwrap();
+        let inputs = read_file("../data/learned/ddqn-minidungeon/best-actions.json").unwrap();
+        let replay = Replay::from_json_or_inputs(&inputs, config.clone()).unwrap();
+        assert_eq!(replay.header.seed, 5);
+        assert_eq!(replay.inputs, crate::json_to_inputs(&inputs).unwrap());
+        let no_seed = GameConfig {
+            seed: None,
+            ..config
+        };
+        assert!(Replay::from_json_or_inputs(&inputs, no_seed).is_err());
+    }
+}
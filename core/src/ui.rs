@@ -1,4 +1,8 @@
+use crate::character::Action;
+use crate::dungeon::Direction;
 use crate::input::System;
+use crate::item::{Item, ItemKind};
+use crate::SmallStr;
 
 /// A representation of Ui transition
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -19,9 +23,37 @@ pub enum MordalKind {
     Grave(Box<str>),
     Inventory,
     Quit,
+    Save,
+    /// asks the player what to call the kind of the item in the slot
+    CallItem(usize, String),
+    /// asks the player in which direction to use the item in the slot
+    SelectDirection(ItemAction, usize),
+    /// asks the player which item to use
+    SelectItem(ItemAction),
+    Victory,
 }
 
 impl MordalKind {
+    /// letters are used to select items in this mordal
+    pub fn selects_item(&self) -> bool {
+        matches!(self, MordalKind::SelectItem(_))
+    }
+    /// all letters are used to input a name in this mordal
+    pub fn takes_text(&self) -> bool {
+        matches!(self, MordalKind::CallItem(..))
+    }
+    /// movement keys are used to select a direction in this mordal
+    pub fn selects_direction(&self) -> bool {
+        matches!(self, MordalKind::SelectDirection(..))
+    }
+    pub fn select_direction(&mut self, direction: Direction) -> MordalMsg {
+        match *self {
+            MordalKind::SelectDirection(act, slot) => act
+                .to_directed_action(slot, direction)
+                .map_or(MordalMsg::None, MordalMsg::Act),
+            _ => MordalMsg::None,
+        }
+    }
     pub fn process(&mut self, input: System) -> MordalMsg {
         match self {
             MordalKind::Quit => match input {
@@ -29,11 +61,44 @@ impl MordalKind {
                 System::Yes => MordalMsg::Quit,
                 _ => MordalMsg::None,
             },
+            MordalKind::Save => match input {
+                System::Cancel | System::No => MordalMsg::Cancel,
+                System::Yes => MordalMsg::Save,
+                _ => MordalMsg::None,
+            },
             MordalKind::Inventory => match input {
                 System::Cancel | System::Continue | System::Enter => MordalMsg::Cancel,
                 _ => MordalMsg::None,
             },
-            MordalKind::Grave(_) => match input {
+            MordalKind::SelectItem(act) => match input {
+                System::Cancel => MordalMsg::Cancel,
+                System::Select(c) if c.is_ascii_lowercase() => {
+                    let slot = usize::from(c as u8 - b'a');
+                    match act.to_action(slot) {
+                        Some(action) => MordalMsg::Act(action),
+                        None if *act == ItemAction::Call => {
+                            MordalMsg::Next(MordalKind::CallItem(slot, String::new()))
+                        }
+                        None => MordalMsg::Next(MordalKind::SelectDirection(*act, slot)),
+                    }
+                }
+                _ => MordalMsg::None,
+            },
+            MordalKind::CallItem(slot, name) => match input {
+                System::Cancel => MordalMsg::Cancel,
+                System::Enter if name.is_empty() => MordalMsg::Cancel,
+                System::Enter => MordalMsg::Call(*slot, SmallStr::from_str(name)),
+                System::Select(c) => {
+                    name.push(c);
+                    MordalMsg::Next(MordalKind::CallItem(*slot, name.clone()))
+                }
+                _ => MordalMsg::None,
+            },
+            MordalKind::SelectDirection(..) => match input {
+                System::Cancel => MordalMsg::Cancel,
+                _ => MordalMsg::None,
+            },
+            MordalKind::Grave(_) | MordalKind::Victory => match input {
                 System::Cancel | System::Continue | System::Enter => MordalMsg::Quit,
                 _ => MordalMsg::None,
             },
@@ -41,7 +106,93 @@ impl MordalKind {
     }
 }
 
+/// actions which need an item selected by the player
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ItemAction {
+    Eat,
+    Quaff,
+    Read,
+    PutOn,
+    Remove,
+    Zap,
+    Wield,
+    Wear,
+    TakeOff,
+    Drop,
+    Throw,
+    Identify,
+    Call,
+}
+
+impl ItemAction {
+    /// returns None if the action also needs a direction
+    pub fn to_action(self, slot: usize) -> Option<Action> {
+        let action = match self {
+            ItemAction::Eat => Action::Eat(slot),
+            ItemAction::Quaff => Action::Quaff(slot),
+            ItemAction::Read => Action::Read(slot),
+            ItemAction::PutOn => Action::PutOn(slot),
+            ItemAction::Remove => Action::Remove(slot),
+            ItemAction::Wield => Action::Wield(slot),
+            ItemAction::Wear => Action::Wear(slot),
+            ItemAction::TakeOff => Action::TakeOff(slot),
+            ItemAction::Drop => Action::Drop(slot),
+            ItemAction::Identify => Action::Identify(slot),
+            ItemAction::Zap | ItemAction::Throw | ItemAction::Call => return None,
+        };
+        Some(action)
+    }
+    /// returns None if the action doesn't need a direction
+    pub fn to_directed_action(self, slot: usize, direction: Direction) -> Option<Action> {
+        match self {
+            ItemAction::Zap => Some(Action::Zap(slot, direction)),
+            ItemAction::Throw => Some(Action::Throw(slot, direction)),
+            _ => None,
+        }
+    }
+    pub fn verb(self) -> &'static str {
+        match self {
+            ItemAction::Eat => "eat",
+            ItemAction::Quaff => "quaff",
+            ItemAction::Read => "read",
+            ItemAction::PutOn => "put on",
+            ItemAction::Remove => "remove",
+            ItemAction::Zap => "zap with",
+            ItemAction::Wield => "wield",
+            ItemAction::Wear => "wear",
+            ItemAction::TakeOff => "take off",
+            ItemAction::Drop => "drop",
+            ItemAction::Throw => "throw",
+            ItemAction::Identify => "identify",
+            ItemAction::Call => "call",
+        }
+    }
+    /// items listed in the prompt
+    pub fn can_select(self, item: &Item) -> bool {
+        match self {
+            ItemAction::Eat => matches!(item.kind, ItemKind::Food(_)),
+            ItemAction::Quaff => matches!(item.kind, ItemKind::Potion(_)),
+            ItemAction::Read => matches!(item.kind, ItemKind::Scroll(_)),
+            ItemAction::PutOn => matches!(item.kind, ItemKind::Ring(_)) && !item.is_equipped(),
+            ItemAction::Remove => matches!(item.kind, ItemKind::Ring(_)) && item.is_equipped(),
+            ItemAction::Zap => matches!(item.kind, ItemKind::Wand(_)),
+            ItemAction::Wield => matches!(item.kind, ItemKind::Weapon(_)) && !item.is_equipped(),
+            ItemAction::Wear => matches!(item.kind, ItemKind::Armor(_)) && !item.is_equipped(),
+            ItemAction::TakeOff => matches!(item.kind, ItemKind::Armor(_)) && item.is_equipped(),
+            ItemAction::Drop => true,
+            ItemAction::Throw => item.kind != ItemKind::Gold && !item.is_equipped(),
+            ItemAction::Identify => true,
+            ItemAction::Call => item.is_magic() && item.kind != ItemKind::Amulet,
+        }
+    }
+}
+
 pub enum MordalMsg {
+    Act(Action),
+    /// moves to the next mordal
+    Next(MordalKind),
+    /// names the kind of the item in the slot
+    Call(usize, SmallStr),
     Quit,
     Save,
     Cancel,
@@ -3,10 +3,11 @@ extern crate log;
 
 pub mod screen;
 use anyhow::{bail, Context};
-use rogue_gym_core::{error::GameResult, input::InputCode, GameConfig, RunTime};
+use rogue_gym_core::{error::GameResult, read_file, replay::Replay, GameConfig, RunTime};
 use rogue_gym_uilib::{process_reaction, Screen, Transition};
 use screen::{RawTerm, TermScreen};
-use std::io;
+use std::fs::File;
+use std::io::{self, Write};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
@@ -14,24 +15,47 @@ use termion::event::Key;
 use termion::input::TermRead;
 
 fn setup_screen(
-    config: GameConfig,
+    mut runtime: RunTime,
     is_default: bool,
 ) -> GameResult<(TermScreen<RawTerm>, RunTime)> {
-    let mut screen = TermScreen::from_raw(config.width, config.height)?;
+    let (w, h) = runtime.screen_size();
+    let mut screen = TermScreen::from_raw(w.0, h.0)?;
     screen.welcome()?;
     if is_default {
         screen.default_config()?;
     }
-    let mut runtime = config.build()?;
     thread::sleep(Duration::from_secs(1));
     screen.dungeon(&mut runtime)?;
     screen.status(&runtime.player_status())?;
     Ok((screen, runtime))
 }
 
+/// the file where the game is saved by default
+pub const DEFAULT_SAVE_FILE: &str = "rogue-gym.save";
+
 pub fn play_game(config: GameConfig, is_default: bool) -> GameResult<RunTime> {
     debug!("devui::play_game config: {:?}", config);
-    let (mut screen, mut runtime) = setup_screen(config, is_default)?;
+    let (screen, runtime) = setup_screen(config.build()?, is_default)?;
+    play_game_(screen, runtime, DEFAULT_SAVE_FILE)
+}
+
+/// resume the game saved in `save_file`
+pub fn resume_game(save_file: &str) -> GameResult<RunTime> {
+    let saved = read_file(save_file).context("Failed to read save file!")?;
+    let mut runtime = RunTime::load(&saved)?;
+    let (w, h) = runtime.screen_size();
+    let mut screen = TermScreen::from_raw(w.0, h.0)?;
+    screen.clear_screen()?;
+    screen.dungeon(&mut runtime)?;
+    screen.status(&runtime.player_status())?;
+    play_game_(screen, runtime, save_file)
+}
+
+fn play_game_(
+    mut screen: TermScreen<RawTerm>,
+    mut runtime: RunTime,
+    save_file: &str,
+) -> GameResult<RunTime> {
     let stdin = io::stdin();
     // let's receive keyboard inputs(our main loop)
     let mut pending = false;
@@ -58,6 +82,13 @@ pub fn play_game(config: GameConfig, is_default: bool) -> GameResult<RunTime> {
                 process_reaction(&mut screen, &mut runtime, reaction).context("in play_game")?;
             match result {
                 Transition::Exit => break 'outer,
+                Transition::Save => {
+                    let saved = runtime.save()?;
+                    let mut file =
+                        File::create(save_file).context("Failed to create save file!")?;
+                    file.write_all(saved.as_bytes())?;
+                    break 'outer;
+                }
                 Transition::None => {}
             }
         }
@@ -67,11 +98,11 @@ pub fn play_game(config: GameConfig, is_default: bool) -> GameResult<RunTime> {
     Ok(runtime)
 }
 
-pub fn show_replay(config: GameConfig, replay: Vec<InputCode>, interval_ms: u64) -> GameResult<()> {
-    debug!("devui::show_replay config: {:?}", config);
+pub fn show_replay(replay: Replay, interval_ms: u64) -> GameResult<()> {
+    debug!("devui::show_replay header: {:?}", replay.header);
     let (tx, rx) = mpsc::channel();
     let replay_thread = thread::spawn(move || {
-        let res = show_replay_(config, replay, interval_ms, rx);
+        let res = show_replay_(replay, interval_ms, rx);
         if let Err(e) = res {
             eprintln!("Error in viewer: {}", e);
         }
@@ -108,13 +139,13 @@ enum ReplayInst {
 }
 
 fn show_replay_(
-    config: GameConfig,
-    mut replay: Vec<InputCode>,
+    replay: Replay,
     interval_ms: u64,
     rx: mpsc::Receiver<ReplayInst>,
 ) -> GameResult<()> {
-    let (mut screen, mut runtime) = setup_screen(config, false)?;
+    let (mut screen, mut runtime) = setup_screen(replay.build()?, false)?;
     let mut sleeping = false;
+    let mut replay = replay.inputs;
     replay.reverse();
     loop {
         match rx.try_recv() {
@@ -151,7 +182,7 @@ fn show_replay_(
                 process_reaction(&mut screen, &mut runtime, reaction).context("in show_replay")?;
             match result {
                 Transition::Exit => return Ok(()),
-                Transition::None => {}
+                Transition::Save | Transition::None => {}
             }
         }
     }
@@ -3,8 +3,8 @@ use std::io::prelude::*;
 
 use anyhow::{bail, Context};
 use clap::ArgMatches;
-use rogue_gym_core::{error::GameResult, json_to_inputs, read_file, GameConfig};
-use rogue_gym_devui::{play_game, show_replay};
+use rogue_gym_core::{error::GameResult, read_file, replay::Replay, GameConfig};
+use rogue_gym_devui::{play_game, resume_game, show_replay};
 
 const DEFAULT_INTERVAL_MS: u64 = 500;
 
@@ -16,18 +16,33 @@ fn main() -> GameResult<()> {
     }
     setup_logger(&args)?;
     if let Some(replay_arg) = args.subcommand_matches("replay") {
-        let fname = replay_arg.value_of("file").unwrap();
-        let replay = read_file(fname).context("Failed to read replay file!")?;
-        let replay = json_to_inputs(&replay)?;
+        let replay = get_replay(replay_arg, config)?;
         let mut interval = DEFAULT_INTERVAL_MS;
         if let Some(inter) = replay_arg.value_of("interval") {
             interval = inter.parse().context("Failed to parse 'interval' arg!")?;
         }
-        show_replay(config, replay, interval)
+        show_replay(replay, interval)
+    } else if let Some(verify_arg) = args.subcommand_matches("verify") {
+        let replay = get_replay(verify_arg, config)?;
+        match replay.verify()? {
+            Some(d) => bail!(
+                "Replay diverged at step {}: expected hash {:x}, but got {:x}",
+                d.step,
+                d.expected,
+                d.actual
+            ),
+            None => {
+                println!("Replay of {} steps was verified", replay.inputs.len());
+                Ok(())
+            }
+        }
     } else {
-        let runtime = play_game(config, is_default)?;
+        let runtime = match args.value_of("load") {
+            Some(save_file) => resume_game(save_file)?,
+            None => play_game(config, is_default)?,
+        };
         if let Some(save_file) = args.value_of("save") {
-            let s = runtime.saved_inputs_as_json()?;
+            let s = runtime.replay().with_hashes()?.to_json()?;
             let mut file = File::create(save_file)?;
             file.write_all(s.as_bytes())?;
         }
@@ -35,6 +50,12 @@ fn main() -> GameResult<()> {
     }
 }
 
+fn get_replay(args: &ArgMatches, config: GameConfig) -> GameResult<Replay> {
+    let fname = args.value_of("file").unwrap();
+    let replay = read_file(fname).context("Failed to read replay file!")?;
+    Replay::from_json_or_inputs(&replay, config)
+}
+
 fn get_config(args: &ArgMatches) -> GameResult<(GameConfig, bool)> {
     let file_name = match args.value_of("config") {
         Some(fname) => fname,
@@ -93,6 +114,13 @@ fn parse_args<'a>() -> ArgMatches<'a> {
                 .help("save replay file")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("load")
+                .long("load")
+                .value_name("LOAD")
+                .help("resume the game from save file")
+                .takes_value(true),
+        )
         .subcommand(
             clap::SubCommand::with_name("replay")
                 .about("Show replay by json file")
@@ -115,6 +143,20 @@ fn parse_args<'a>() -> ArgMatches<'a> {
                         .takes_value(true),
                 ),
         )
+        .subcommand(
+            clap::SubCommand::with_name("verify")
+                .about("Replay the json file and check if it's reproduced")
+                .version("0.1")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .short("f")
+                        .long("file")
+                        .required(true)
+                        .value_name("FILE")
+                        .help("replay json file")
+                        .takes_value(true),
+                ),
+        )
         .get_matches()
 }
 
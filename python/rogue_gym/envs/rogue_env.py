@@ -153,6 +153,18 @@ class RogueEnv(gym.Env):
         "u": "MOVE_RIGHTUP",
         "y": "MOVE_LEFTUP",
         ">": "DOWNSTAIR",
+        "<": "UPSTAIR",
+        "e": "EAT",
+        "q": "QUAFF",
+        "r": "READ",
+        "P": "PUT_ON",
+        "R": "REMOVE",
+        "z": "ZAP",
+        "w": "WIELD",
+        "W": "WEAR",
+        "T": "TAKE_OFF",
+        "d": "DROP",
+        "t": "THROW",
         "s": "SEARCH",
     }
 
@@ -168,6 +180,28 @@ class RogueEnv(gym.Env):
         "y",
         ">",
         "s",
+        "e",
+        "q",
+        "r",
+        "P",
+        "R",
+        "z",
+        "w",
+        "W",
+        "T",
+        "d",
+        "t",
+        # keys to select an item in the pack, except ones already used as commands
+        "a",
+        "c",
+        "f",
+        "g",
+        "i",
+        "m",
+        "o",
+        "p",
+        "v",
+        "x",
     ]
 
     ACTION_LEN = len(ACTIONS)
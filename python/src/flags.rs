@@ -14,6 +14,20 @@ impl MessageFlagInner {
     const KILLED: u32       = 0b000_010_000;
     const SECRET_DOOR: u32  = 0b000_100_000;
     const NO_DOWNSTAIR: u32 = 0b001_000_000;
+    const NO_UPSTAIR: u32   = 0b010_000_000;
+    const HUNGRY: u32       = 0b100_000_000;
+    const FAINTED: u32      = 0b1_000_000_000;
+    const ATE: u32          = 0b10_000_000_000;
+    const QUAFFED: u32      = 0b100_000_000_000;
+    const READ: u32         = 0b1_000_000_000_000;
+    const ZAPPED: u32       = 0b10_000_000_000_000;
+    const RUSTED: u32       = 0b100_000_000_000_000;
+    const ROBBED: u32       = 0b1_000_000_000_000_000;
+    const WEAKENED: u32     = 0b10_000_000_000_000_000;
+    const FROZEN: u32       = 0b100_000_000_000_000_000;
+    const DRAINED: u32      = 0b1_000_000_000_000_000_000;
+    const FOUND_TRAP: u32   = 0b10_000_000_000_000_000_000;
+    const TRAPPED: u32      = 0b100_000_000_000_000_000_000;
 }
 
 impl MessageFlagInner {
@@ -26,13 +40,36 @@ impl MessageFlagInner {
     pub fn append(&mut self, msg: &GameMsg) {
         let mut add = |flag: u32| self.0 |= flag;
         match msg {
-            GameMsg::HitTo(_) => add(Self::HIT_TO),
+            GameMsg::HitTo(_) | GameMsg::ThrowHit(..) => add(Self::HIT_TO),
             GameMsg::HitFrom(_) => add(Self::HIT_FROM),
-            GameMsg::MissTo(_) => add(Self::MISS_TO),
+            GameMsg::MissTo(_) | GameMsg::ThrowMiss(..) => add(Self::MISS_TO),
             GameMsg::MissFrom(_) => add(Self::MISS_FROM),
             GameMsg::Killed(_) => add(Self::KILLED),
             GameMsg::SecretDoor => add(Self::SECRET_DOOR),
             GameMsg::NoDownStair => add(Self::NO_DOWNSTAIR),
+            GameMsg::NoUpStair => add(Self::NO_UPSTAIR),
+            GameMsg::HungerChanged(_) => add(Self::HUNGRY),
+            GameMsg::Fainted => add(Self::FAINTED),
+            GameMsg::Ate(_) => add(Self::ATE),
+            GameMsg::Quaffed(_) => add(Self::QUAFFED),
+            GameMsg::Read(_) | GameMsg::ScrollFailed(_) => add(Self::READ),
+            GameMsg::Zapped(_) | GameMsg::ZapFailed(_) => add(Self::ZAPPED),
+            GameMsg::ArmorRusted => add(Self::RUSTED),
+            GameMsg::GoldStolen(_) => add(Self::ROBBED),
+            GameMsg::StrengthDrained => add(Self::WEAKENED),
+            GameMsg::Frozen(_) => add(Self::FROZEN),
+            GameMsg::LevelDrained | GameMsg::MaxHpDrained => add(Self::DRAINED),
+            GameMsg::FoundTrap(_) => add(Self::FOUND_TRAP),
+            GameMsg::FellIntoTrap
+            | GameMsg::CaughtInBearTrap
+            | GameMsg::SleepingGas
+            | GameMsg::ArrowHit
+            | GameMsg::ArrowMiss
+            | GameMsg::DartHit
+            | GameMsg::DartMiss
+            | GameMsg::TeleportTrap
+            | GameMsg::RustTrap
+            | GameMsg::MysteriousTrap => add(Self::TRAPPED),
             _ => (),
         }
     }
@@ -25,6 +25,27 @@ fn pyresult_with<T, E: Display>(result: Result<T, E>, msg: &str) -> PyResult<T>
     result.map_err(|e| PyErr::new::<PyRuntimeError, _>(format!("{}: {}", msg, e)))
 }
 
+/// Why an episode ended
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TerminalReason {
+    /// the player died
+    Death,
+    /// the player escaped with the Amulet of Yendor
+    Victory,
+    /// the step limit was reached
+    MaxSteps,
+}
+
+impl TerminalReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            TerminalReason::Death => "death",
+            TerminalReason::Victory => "victory",
+            TerminalReason::MaxSteps => "max_steps",
+        }
+    }
+}
+
 /// A memory efficient representation of Agent observation.
 #[pyclass]
 #[derive(Clone, Debug, PartialEq)]
@@ -34,7 +55,7 @@ struct PlayerState {
     status: Status,
     symbols: u8,
     message: MessageFlagInner,
-    is_terminal: bool,
+    terminal: Option<TerminalReason>,
 }
 
 impl PlayerState {
@@ -46,14 +67,14 @@ impl PlayerState {
             status: Status::default(),
             symbols,
             message: MessageFlagInner::new(),
-            is_terminal: false,
+            terminal: None,
         }
     }
     fn reset(&mut self, runtime: &RunTime) -> GameResult<()> {
         self.status = runtime.player_status();
         self.draw_map(runtime)?;
         self.message = MessageFlagInner::new();
-        self.is_terminal = false;
+        self.terminal = None;
         Ok(())
     }
     fn draw_map(&mut self, runtime: &RunTime) -> GameResult<()> {
@@ -153,7 +174,12 @@ impl PlayerState {
     }
     #[getter]
     fn is_terminal(&self) -> PyResult<bool> {
-        Ok(self.is_terminal)
+        Ok(self.terminal.is_some())
+    }
+    /// "death", "victory", "max_steps", or None if the episode continues
+    #[getter]
+    fn terminal_reason(&self) -> PyResult<Option<&'static str>> {
+        Ok(self.terminal.map(TerminalReason::as_str))
     }
     fn status_vec(&self, flag: u32) -> Vec<i32> {
         let flag = StatusFlagInner(flag);
@@ -241,12 +267,17 @@ impl GameState {
     fn react(&mut self, input: u8) -> PyResult<()> {
         pyresult(self.inner.react(input))
     }
-    /// Returns action history as Json
-    fn dump_history(&self) -> PyResult<String> {
-        pyresult_with(
-            self.inner.runtime.saved_inputs_as_json(),
-            "Error when getting history",
-        )
+    /// Returns the replay(config, seed and action history) as Json
+    fn dump_history(&self, with_hashes: Option<bool>) -> PyResult<String> {
+        let mut replay = self.inner.runtime.replay();
+        if with_hashes.unwrap_or(false) {
+            replay = pyresult_with(replay.with_hashes(), "Error when hashing states")?;
+        }
+        pyresult_with(replay.to_json(), "Error when getting history")
+    }
+    /// Returns the fingerprint of current game state
+    fn state_hash(&self) -> u64 {
+        self.inner.runtime.state_hash()
     }
     /// Returns config as Json
     fn dump_config(&self) -> PyResult<String> {
@@ -338,9 +369,8 @@ impl ParallelGameState {
 #[pyfunction]
 fn replay(game: &GameState, py: Python, interval_ms: u64) -> PyResult<()> {
     use rogue_gym_devui::show_replay;
-    let inputs = game.inner.runtime.saved_inputs().to_vec();
-    let config = game.config.clone();
-    let res = py.allow_threads(move || show_replay(config, inputs, interval_ms));
+    let replay = game.inner.runtime.replay();
+    let res = py.allow_threads(move || show_replay(replay, interval_ms));
     pyresult(res)
 }
 
@@ -1,4 +1,4 @@
-use crate::PlayerState;
+use crate::{PlayerState, TerminalReason};
 use anyhow::bail;
 use rogue_gym_core::{
     error::GameResult,
@@ -14,8 +14,6 @@ pub(crate) struct GameStateImpl {
     max_steps: usize,
 }
 
-unsafe impl Send for GameStateImpl {}
-
 impl GameStateImpl {
     pub(crate) fn new(config: GameConfig, max_steps: usize) -> GameResult<Self> {
         let symbols = config
@@ -54,7 +52,7 @@ impl GameStateImpl {
         }
         let res = self.runtime.react_to_key(Key::Char(input as char))?;
         self.state.message.reset();
-        let mut dead = false;
+        let mut terminal = None;
         for reaction in res {
             match reaction {
                 Reaction::Redraw => {
@@ -64,7 +62,14 @@ impl GameStateImpl {
                     self.state.status = self.runtime.player_status();
                 }
                 Reaction::UiTransition(ui) => match ui {
-                    UiState::Mordal(MordalKind::Grave(_)) => dead = true,
+                    UiState::Mordal(MordalKind::Grave(_)) => terminal = Some(TerminalReason::Death),
+                    UiState::Mordal(MordalKind::Victory) => {
+                        terminal = Some(TerminalReason::Victory)
+                    }
+                    UiState::Mordal(MordalKind::SelectItem(_))
+                    | UiState::Mordal(MordalKind::SelectDirection(..))
+                    | UiState::Mordal(MordalKind::CallItem(..))
+                    | UiState::Dungeon => {}
                     _ => bail!(
                         "[rogue_gym_python::GameStateImpl] Invalid ui transition {:?}",
                         ui
@@ -74,7 +79,10 @@ impl GameStateImpl {
             }
         }
         self.steps += 1;
-        self.state.is_terminal = dead || self.steps >= self.max_steps;
+        if terminal.is_none() && self.steps >= self.max_steps {
+            terminal = Some(TerminalReason::MaxSteps);
+        }
+        self.state.terminal = terminal;
         Ok(())
     }
 }
@@ -67,14 +67,14 @@ impl ThreadConductor {
             result.push(res??);
         }
         for (i, res) in result.iter().enumerate() {
-            if res.is_terminal {
+            if res.terminal.is_some() {
                 self.senders[i].send(Instruction::Reset)?;
             }
         }
         for (i, res) in result.iter_mut().enumerate() {
-            if res.is_terminal {
+            if let Some(reason) = res.terminal {
                 *res = self.receivers[i].recv()??;
-                res.is_terminal = true;
+                res.terminal = Some(reason);
             }
         }
         Ok(result)
@@ -98,8 +98,6 @@ enum Instruction {
     Stop,
 }
 
-unsafe impl Send for Instruction {}
-
 struct ThreadWorker {
     game_state: GameStateImpl,
     config: GameConfig,
@@ -1,8 +1,10 @@
 //! UI abstraction for rogue-gym
+use rogue_gym_core::character::player::{Effect, Hunger, Status};
 use rogue_gym_core::dungeon::{Coord, Positioned, X, Y};
 use rogue_gym_core::error::GameResult;
-use rogue_gym_core::ui::{MordalKind, UiState};
-use rogue_gym_core::{character::player::Status, tile::Tile, GameMsg, Reaction, RunTime};
+use rogue_gym_core::item::{food::Food, potion::PotionKind, scroll::ScrollKind, wand::WandKind};
+use rogue_gym_core::ui::{ItemAction, MordalKind, UiState};
+use rogue_gym_core::{tile::Tile, GameMsg, Reaction, RunTime};
 
 /// 0-indexed 2d screen for rogue-gym
 pub trait Screen {
@@ -63,15 +65,47 @@ pub trait Screen {
         self.flush()
     }
     fn inventory(&mut self, runtime: &mut RunTime) -> GameResult<()> {
-        for (i, item) in runtime.itembox().items().enumerate() {
-            let num = (b'a' + i as u8) as char;
-            self.write_str(Coord::new(0, i as i32), format!("{}) {}", num, item))?;
+        let knowledge = runtime.knowledge();
+        for (i, (slot, item)) in runtime.itembox().slots().enumerate() {
+            let c = (b'a' + slot as u8) as char;
+            let name = knowledge.item_name(item.get());
+            self.write_str(Coord::new(0, i as i32), format!("{}) {}", c, name))?;
         }
         self.write_str(
             Coord::new(0, self.height() - 1.into()),
             "--Press space to continue--",
         )
     }
+    fn select_item(&mut self, runtime: &mut RunTime, act: ItemAction) -> GameResult<()> {
+        self.clear_dungeon()?;
+        self.message(format!("Which object do you want to {}?", act.verb()))?;
+        let items = runtime
+            .itembox()
+            .slots()
+            .filter(|(_, item)| act.can_select(item.get()));
+        let knowledge = runtime.knowledge();
+        for (i, (slot, item)) in items.enumerate() {
+            let c = (b'a' + slot as u8) as char;
+            let name = knowledge.item_name(item.get());
+            self.write_str(Coord::new(0, i as i32 + 1), format!("{}) {}", c, name))?;
+        }
+        self.write_str(
+            Coord::new(0, self.height() - 1.into()),
+            "--Press a letter or Esc--",
+        )
+    }
+    fn select_direction(&mut self, runtime: &mut RunTime) -> GameResult<()> {
+        self.clear_dungeon()?;
+        self.clear_notification()?;
+        self.dungeon(runtime)?;
+        self.status(&runtime.player_status())?;
+        self.message("Which direction?")
+    }
+    fn call_item(&mut self, runtime: &mut RunTime, name: &str) -> GameResult<()> {
+        self.clear_dungeon()?;
+        self.dungeon(runtime)?;
+        self.message(format!("What do you want to call it? {}", name))
+    }
     fn dying_msg(&mut self, sig: &str) -> GameResult<()> {
         const MESSAGES: [&'static str; 9] = [
             r"                __________        ",
@@ -99,14 +133,88 @@ pub trait Screen {
         }
         Ok(())
     }
+    fn victory_msg(&mut self, gold: u32) -> GameResult<()> {
+        const MESSAGES: [&str; 3] = [
+            "Congratulations, you have made it to the light of day!",
+            "You have joined the elite ranks of those who have escaped",
+            "the Dungeons of Doom alive.",
+        ];
+        for (i, msg) in MESSAGES.iter().enumerate() {
+            self.write_str(Coord::new(0, i as i32 + 2), msg)?;
+        }
+        self.write_str(
+            Coord::new(0, MESSAGES.len() as i32 + 3),
+            format!("You made it out with {} gold pieces.", gold),
+        )
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Transition {
     Exit,
+    Save,
     None,
 }
 
+fn quaffed_msg(kind: PotionKind) -> &'static str {
+    match kind {
+        PotionKind::Confusion => "Wait, what's going on here. Huh? What? Who?",
+        PotionKind::Poison => "You feel very sick now",
+        PotionKind::GainStrength => "You feel stronger, now. What bulging muscles!",
+        PotionKind::SeeInvisible => "This potion tastes like slime-mold juice",
+        PotionKind::Healing => "You begin to feel better",
+        PotionKind::DetectMonsters => "You sense the presence of monsters",
+        PotionKind::DetectMagic => "You sense the presence of magic",
+        PotionKind::RaiseLevel => "You suddenly feel much more skillful",
+        PotionKind::ExtraHealing => "You begin to feel much better",
+        PotionKind::Haste => "You feel yourself moving much faster",
+        PotionKind::RestoreStrength => "Hey, this tastes great. It makes you feel warm all over",
+        PotionKind::Blindness => "A cloak of darkness falls around you",
+    }
+}
+
+fn read_msg(kind: ScrollKind) -> Option<&'static str> {
+    let msg = match kind {
+        ScrollKind::Identify => "This scroll is an identify scroll",
+        ScrollKind::EnchantArmor => "Your armor glows silver for a moment",
+        ScrollKind::EnchantWeapon => "Your weapon glows blue for a moment",
+        ScrollKind::RemoveCurse => "You feel as if somebody is watching over you",
+        ScrollKind::Teleportation => return None,
+        ScrollKind::MagicMapping => "Oh, now this scroll has a map on it",
+        ScrollKind::HoldMonster => "The monsters around you freeze",
+        ScrollKind::Sleep => "You fall asleep",
+        ScrollKind::ScareMonster => "You hear maniacal laughter in the distance",
+        ScrollKind::CreateMonster => return None,
+        ScrollKind::FoodDetection => "Your nose tingles and you smell food",
+        ScrollKind::AggravateMonsters => "You hear a high pitched humming noise",
+        ScrollKind::ProtectArmor => "Your armor is covered by a shimmering gold shield",
+    };
+    Some(msg)
+}
+
+fn scroll_failed_msg(kind: ScrollKind) -> &'static str {
+    match kind {
+        ScrollKind::CreateMonster => "You hear a faint cry of anguish in the distance",
+        ScrollKind::FoodDetection => "Your nose tingles",
+        _ => "You feel a strange sense of loss",
+    }
+}
+
+fn zapped_msg(kind: WandKind) -> Option<&'static str> {
+    match kind {
+        WandKind::Light => Some("The room is lit by a shimmering blue light"),
+        _ => None,
+    }
+}
+
+fn zap_failed_msg(kind: WandKind) -> &'static str {
+    match kind {
+        WandKind::Light => "The corridor glows and then fades",
+        WandKind::DrainLife => "You have a tingling feeling",
+        _ => "You feel a strange sense of loss",
+    }
+}
+
 pub fn process_reaction<S: Screen>(
     screen: &mut S,
     runtime: &mut RunTime,
@@ -119,15 +227,154 @@ pub fn process_reaction<S: Screen>(
             GameMsg::NoDownStair => {
                 screen.pend_message(format!("Hmm... there seems to be no downstair"))
             }
+            GameMsg::NoUpStair => screen.pend_message("Hmm... there seems to be no upstair"),
             GameMsg::GotItem { kind, num } => {
-                screen.pend_message(format!("You got {} {:?}", num, kind))
+                let name = runtime.knowledge().kind_name(&kind).to_string();
+                screen.pend_message(format!("You got {} {}", num, name))
             }
             GameMsg::SecretDoor => screen.pend_message(format!("You found a secret door")),
+            GameMsg::FoundTrap(kind) => {
+                let name = kind.name();
+                let article = if name.starts_with('a') { "an" } else { "a" };
+                screen.pend_message(format!("You found {} {}", article, name))
+            }
+            GameMsg::FellIntoTrap => screen.pend_message("You fell into a trap!"),
+            GameMsg::CaughtInBearTrap => screen.pend_message("You are caught in a bear trap"),
+            GameMsg::StuckInBearTrap => screen.pend_message("You are still stuck in the bear trap"),
+            GameMsg::SleepingGas => {
+                screen.pend_message("A strange white mist envelops you and you fall asleep")
+            }
+            GameMsg::ArrowHit => screen.pend_message("Oh no! An arrow shot you"),
+            GameMsg::ArrowMiss => screen.pend_message("An arrow shoots past you"),
+            GameMsg::DartHit => screen.pend_message("A small dart just hit you in the shoulder"),
+            GameMsg::DartMiss => {
+                screen.pend_message("A small dart whizzes by your ear and vanishes")
+            }
+            GameMsg::TeleportTrap => screen.pend_message("Your surroundings suddenly change"),
+            GameMsg::RustTrap => screen.pend_message("A gush of water hits you on the head"),
+            GameMsg::MysteriousTrap => {
+                screen.pend_message("You are suddenly in a parallel dimension")
+            }
             GameMsg::HitTo(s) => screen.pend_message(format!("You swings and hit {}", s)),
             GameMsg::HitFrom(s) => screen.pend_message(format!("{} swings and hits you", s)),
             GameMsg::MissTo(s) => screen.pend_message(format!("You swing and miss {}", s)),
             GameMsg::MissFrom(s) => screen.pend_message(format!("{} swings and misses you", s)),
             GameMsg::Killed(s) => screen.pend_message(format!("You defeated the {}", s)),
+            GameMsg::HungerChanged(hunger) => match hunger {
+                Hunger::Normal => Ok(()),
+                Hunger::Hungry => screen.pend_message("You are starting to get hungry"),
+                Hunger::Weak => screen.pend_message("You are starting to feel weak"),
+                Hunger::Fainting => screen.pend_message("You feel too weak from lack of food"),
+            },
+            GameMsg::Fainted => screen.pend_message("You faint"),
+            GameMsg::Ate(Food::Slime) => screen.pend_message("My, that was a yummy slime-mold"),
+            GameMsg::Ate(_) => screen.pend_message("Yum, that tasted good"),
+            GameMsg::CantEat => screen.pend_message("Ugh, you would get ill if you ate that"),
+            GameMsg::InvalidItem => screen.pend_message("That's not a valid item"),
+            GameMsg::Quaffed(kind) => screen.pend_message(quaffed_msg(kind)),
+            GameMsg::CantQuaff => screen.pend_message("Yuk! Why would you want to drink that?"),
+            GameMsg::Read(kind) => match read_msg(kind) {
+                Some(msg) => screen.pend_message(msg),
+                None => Ok(()),
+            },
+            GameMsg::ScrollFailed(kind) => screen.pend_message(scroll_failed_msg(kind)),
+            GameMsg::CantRead => screen.pend_message("There is nothing on it to read"),
+            GameMsg::PutOnRing(kind) => screen.pend_message(format!(
+                "You are now wearing a {}",
+                runtime.knowledge().ring_name(kind)
+            )),
+            GameMsg::RemovedRing(kind) => screen.pend_message(format!(
+                "You were wearing a {}",
+                runtime.knowledge().ring_name(kind)
+            )),
+            GameMsg::CantPutOn => {
+                screen.pend_message("It would be difficult to wrap that around a finger")
+            }
+            GameMsg::NoFreeHand => screen.pend_message("You already have a ring on each hand"),
+            GameMsg::AlreadyWearing => screen.pend_message("You are already wearing that"),
+            GameMsg::NotWearing => screen.pend_message("You aren't wearing that"),
+            GameMsg::Cursed => screen.pend_message("You can't. It appears to be cursed"),
+            GameMsg::Zapped(kind) => match zapped_msg(kind) {
+                Some(msg) => screen.pend_message(msg),
+                None => Ok(()),
+            },
+            GameMsg::ZapFailed(kind) => screen.pend_message(zap_failed_msg(kind)),
+            GameMsg::NoCharges => screen.pend_message("Nothing happens"),
+            GameMsg::CantZap => screen.pend_message("You can't zap with that"),
+            GameMsg::TooWeak => screen.pend_message("You are too weak to use it"),
+            GameMsg::BoltHitTo(WandKind::Striking, s) => {
+                screen.pend_message(format!("The wand hits the {}", s))
+            }
+            GameMsg::BoltMissTo(WandKind::Striking, s) => {
+                screen.pend_message(format!("The wand misses the {}", s))
+            }
+            GameMsg::BoltHitTo(kind, s) => {
+                screen.pend_message(format!("The bolt of {} hits the {}", kind, s))
+            }
+            GameMsg::BoltMissTo(kind, s) => {
+                screen.pend_message(format!("The bolt of {} whizzes past the {}", kind, s))
+            }
+            GameMsg::BoltHitFrom(kind) => {
+                screen.pend_message(format!("You are hit by the bolt of {}", kind))
+            }
+            GameMsg::BoltMissFrom(kind) => {
+                screen.pend_message(format!("The bolt of {} whizzes by you", kind))
+            }
+            GameMsg::Wielded(kind) => screen.pend_message(format!(
+                "You are now wielding {}",
+                runtime.knowledge().kind_name(&kind)
+            )),
+            GameMsg::CantWield => screen.pend_message("You can't wield that"),
+            GameMsg::AlreadyWielding => screen.pend_message("You are already wielding that"),
+            GameMsg::Wore(kind) => screen.pend_message(format!(
+                "You are now wearing {}",
+                runtime.knowledge().kind_name(&kind)
+            )),
+            GameMsg::CantWear => screen.pend_message("You can't wear that"),
+            GameMsg::TakeOffFirst => screen
+                .pend_message("You are already wearing some. You'll have to take it off first"),
+            GameMsg::TookOff(kind) => screen.pend_message(format!(
+                "You used to be wearing {}",
+                runtime.knowledge().kind_name(&kind)
+            )),
+            GameMsg::Dropped(kind) => {
+                screen.pend_message(format!("Dropped {}", runtime.knowledge().kind_name(&kind)))
+            }
+            GameMsg::Identified(kind) => screen.pend_message(format!("It is {}", kind)),
+            GameMsg::CantCall => screen.pend_message("You can't call that anything"),
+            GameMsg::SomethingThere => screen.pend_message("There is something there already"),
+            GameMsg::InUse => screen.pend_message("That's already in use"),
+            GameMsg::CantThrow => screen.pend_message("You can't throw that"),
+            GameMsg::ThrowHit(kind, s) => screen.pend_message(format!("The {} hits {}", kind, s)),
+            GameMsg::ThrowMiss(kind, s) => {
+                screen.pend_message(format!("The {} misses {}", kind, s))
+            }
+            GameMsg::Vanished(kind) => screen.pend_message(format!(
+                "The {} vanishes as it hits the ground",
+                runtime.knowledge().kind_name(&kind)
+            )),
+            GameMsg::ArmorRusted => screen.pend_message("Your armor weakens"),
+            GameMsg::RustVanished => screen.pend_message("The rust vanishes instantly"),
+            GameMsg::GoldStolen(_) => screen.pend_message("Your purse feels lighter"),
+            GameMsg::StrengthDrained => {
+                screen.pend_message("You feel a bite in your leg and now feel weaker")
+            }
+            GameMsg::BiteResisted => screen.pend_message("A bite momentarily weakens you"),
+            GameMsg::Frozen(s) => screen.pend_message(format!("You are frozen by the {}", s)),
+            GameMsg::LevelDrained | GameMsg::MaxHpDrained => {
+                screen.pend_message("You suddenly feel weaker")
+            }
+            GameMsg::EffectEnded(effect) => match effect {
+                Effect::Confused => screen.pend_message("You feel less confused now"),
+                Effect::Blind => screen.pend_message("The veil of darkness lifts"),
+                Effect::Hasted => screen.pend_message("You feel yourself slowing down"),
+                _ => Ok(()),
+            },
+            GameMsg::Victory => screen.pend_message("You escaped with the Amulet of Yendor!"),
+            GameMsg::Save => {
+                screen.pend_message("Saving the game...")?;
+                return Ok(Transition::Save);
+            }
             GameMsg::Quit => {
                 screen.pend_message(format!("Thank you for playing!"))?;
                 return Ok(Transition::Exit);
@@ -138,8 +385,16 @@ pub fn process_reaction<S: Screen>(
         Reaction::UiTransition(ui_state) => match ui_state {
             UiState::Mordal(kind) => match kind {
                 MordalKind::Quit => screen.message(format!("You really quit game?(y/n)")),
+                MordalKind::Save => screen.message("Save and quit game?(y/n)"),
                 MordalKind::Inventory => screen.inventory(runtime),
+                MordalKind::SelectItem(act) => screen.select_item(runtime, act),
+                MordalKind::SelectDirection(..) => screen.select_direction(runtime),
+                MordalKind::CallItem(_, name) => screen.call_item(runtime, &name),
                 MordalKind::Grave(msg) => screen.dying_msg(&*msg),
+                MordalKind::Victory => {
+                    screen.clear_screen()?;
+                    screen.victory_msg(runtime.player_status().gold)
+                }
             },
             UiState::Dungeon => {
                 screen.dungeon(runtime)?;